@@ -1,8 +1,16 @@
+use std::cmp::Ordering;
+
+use crate::element::Keys;
 use crate::engine::{Action, ActionKind};
 
-pub fn bubble_sort_actions(values: &[u32]) -> Vec<Action> {
-    let mut arr: Vec<u32> = values.to_vec();
-    let n = arr.len();
+/// Not offered in `Algorithm::ALL` yet
+#[allow(dead_code)]
+pub fn bubble_sort_actions<T, F>(mut keys: Keys<T, F>) -> Vec<Action>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let n = keys.len();
+    let mut arr: Vec<usize> = (0..n).collect();
     let mut actions = Vec::new();
 
     for i in 0..n {
@@ -15,8 +23,11 @@ pub fn bubble_sort_actions(values: &[u32]) -> Vec<Action> {
                 memory: 0,
                 temp_idx: 0,
                 thread_id: 0,
+                origin: 0,
+                note: None,
+                line: None,
             });
-            if arr[j] > arr[j + 1] {
+            if keys.cmp(arr[j], arr[j + 1]) == Ordering::Greater {
                 arr.swap(j, j + 1);
                 actions.push(Action {
                     kind: ActionKind::Swap,
@@ -26,6 +37,9 @@ pub fn bubble_sort_actions(values: &[u32]) -> Vec<Action> {
                     memory: 0,
                     temp_idx: 0,
                     thread_id: 0,
+                    origin: 0,
+                    note: None,
+                    line: None,
                 });
             }
         }
@@ -33,12 +47,34 @@ pub fn bubble_sort_actions(values: &[u32]) -> Vec<Action> {
             kind: ActionKind::Done,
             i: n - 1 - i,
             j: n - 1 - i,
-            value: arr[n - 1 - i],
+            value: keys.bar_value(arr[n - 1 - i]),
             memory: 0,
             temp_idx: 0,
             thread_id: 0,
+            origin: arr[n - 1 - i],
+            note: None,
+            line: None,
         });
     }
 
     actions
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swaps_sort_the_keys() {
+        let values = [5, 1, 4, 1, 3];
+        let actions = bubble_sort_actions(Keys::new(&values, &values, u32::cmp));
+        let mut slots = values.to_vec();
+        for action in &actions {
+            if action.kind == ActionKind::Swap {
+                slots.swap(action.i, action.j);
+            }
+        }
+        assert_eq!(slots, [1, 1, 3, 4, 5]);
+        assert_eq!(actions.iter().filter(|a| a.kind == ActionKind::Done).count(), values.len());
+    }
+}
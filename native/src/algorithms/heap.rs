@@ -0,0 +1,245 @@
+use crate::engine::{Action, ActionKind};
+
+use super::annotate;
+
+/// Interactive priority-queue operation on the live heap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeapOp {
+    Push(u32),
+    Pop,
+    DecreaseKey { idx: usize, value: u32 },
+}
+
+/// Heap operations as shown in the pseudocode panel; actions carry indices into it
+pub const PSEUDOCODE: &[&str] = &[
+    "push(x):",
+    "    a.append(x); sift_up(n - 1)",
+    "pop():",
+    "    swap(a[0], a[n - 1]); remove the last element",
+    "    sift_down(0)",
+    "decrease_key(i, x):",
+    "    if x >= a[i]: return",
+    "    a[i] = x; sift_up(i)",
+    "sift_up(i):",
+    "    while i > 0 and a[i] < a[parent(i)]:",
+    "        swap(a[i], a[parent(i)]); i = parent(i)",
+    "sift_down(i):",
+    "    smallest = smallest of a[i] and its children",
+    "    if smallest != i: swap(a[i], a[smallest]); sift_down(smallest)",
+];
+const LINE_PUSH: usize = 1;
+const LINE_POP: usize = 3;
+const LINE_NOT_SMALLER: usize = 6;
+const LINE_DECREASE: usize = 7;
+const LINE_SIFT_UP_COMPARE: usize = 9;
+const LINE_SIFT_UP_SWAP: usize = 10;
+const LINE_SIFT_DOWN_COMPARE: usize = 12;
+const LINE_SIFT_DOWN_SWAP: usize = 13;
+
+/// Binary min-heap whose operations are recorded as actions.
+/// `values` is the logical heap after every recorded action has played.
+pub struct HeapRecorder<'a> {
+    pub values: &'a mut Vec<u32>,
+    pub actions: &'a mut Vec<Action>,
+}
+
+impl HeapRecorder<'_> {
+    /// Build a heap in place with bottom-up sift-down (Floyd's method)
+    pub fn heapify(&mut self) {
+        let n = self.values.len();
+        for i in (0..n / 2).rev() {
+            self.sift_down(i);
+        }
+    }
+
+    pub fn apply(&mut self, op: HeapOp) {
+        match op {
+            HeapOp::Push(value) => self.push(value),
+            HeapOp::Pop => self.pop(),
+            HeapOp::DecreaseKey { idx, value } => self.decrease_key(idx, value),
+        }
+    }
+
+    fn push(&mut self, value: u32) {
+        let idx = self.values.len();
+        self.values.push(value);
+        self.record(ActionKind::Append, idx, idx, value, LINE_PUSH);
+        annotate(self.actions, format!("push {value} at the end, a[{idx}], then sift it up"));
+        self.sift_up(idx);
+    }
+
+    fn pop(&mut self) {
+        let Some(&root) = self.values.first() else {
+            return;
+        };
+        let last = self.values.len() - 1;
+        self.swap(0, last, LINE_POP);
+        if last > 0 {
+            annotate(self.actions, format!("pop: swap the minimum {root} at the root with the last element a[{last}]"));
+        }
+        self.values.pop();
+        self.record(ActionKind::RemoveLast, last, last, root, LINE_POP);
+        annotate(self.actions, format!("remove {root} from the end, then sift the new root down"));
+        self.sift_down(0);
+    }
+
+    fn decrease_key(&mut self, idx: usize, value: u32) {
+        let Some(&current) = self.values.get(idx) else {
+            return;
+        };
+        // A larger or equal key is not a decrease: record the check and leave the heap alone
+        if value >= current {
+            self.record(ActionKind::Compare, idx, idx, value, LINE_NOT_SMALLER);
+            annotate(self.actions, format!("{value} is not smaller than a[{idx}]={current}; nothing to decrease"));
+            return;
+        }
+        self.values[idx] = value;
+        self.record(ActionKind::Assign, idx, idx, value, LINE_DECREASE);
+        annotate(self.actions, format!("decrease a[{idx}] from {current} to {value}, then sift it up"));
+        self.sift_up(idx);
+    }
+
+    fn sift_up(&mut self, mut idx: usize) {
+        while idx > 0 {
+            let parent = (idx - 1) / 2;
+            self.record(ActionKind::Compare, idx, parent, 0, LINE_SIFT_UP_COMPARE);
+            let (child, above) = (self.values[idx], self.values[parent]);
+            if child >= above {
+                annotate(self.actions, format!("compare a[{idx}]={child} with parent a[{parent}]={above}: heap order holds"));
+                break;
+            }
+            annotate(self.actions, format!("compare a[{idx}]={child} with parent a[{parent}]={above}: smaller, swap up"));
+            self.swap(idx, parent, LINE_SIFT_UP_SWAP);
+            idx = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut idx: usize) {
+        let n = self.values.len();
+        loop {
+            let left = 2 * idx + 1;
+            let right = left + 1;
+            let mut smallest = idx;
+            if left < n {
+                self.record(ActionKind::Compare, left, smallest, 0, LINE_SIFT_DOWN_COMPARE);
+                self.note_child(left, smallest);
+                if self.values[left] < self.values[smallest] {
+                    smallest = left;
+                }
+            }
+            if right < n {
+                self.record(ActionKind::Compare, right, smallest, 0, LINE_SIFT_DOWN_COMPARE);
+                self.note_child(right, smallest);
+                if self.values[right] < self.values[smallest] {
+                    smallest = right;
+                }
+            }
+            if smallest == idx {
+                break;
+            }
+            self.swap(idx, smallest, LINE_SIFT_DOWN_SWAP);
+            annotate(self.actions, format!("swap a[{idx}] down with its smaller child a[{smallest}]"));
+            idx = smallest;
+        }
+    }
+
+    fn swap(&mut self, i: usize, j: usize, line: usize) {
+        if i != j {
+            self.values.swap(i, j);
+            self.record(ActionKind::Swap, i, j, 0, line);
+        }
+    }
+
+    fn note_child(&mut self, child: usize, smallest: usize) {
+        let (a, b) = (self.values[child], self.values[smallest]);
+        let verdict = if a < b { "new smallest" } else { "not smaller" };
+        annotate(self.actions, format!("compare child a[{child}]={a} with a[{smallest}]={b}: {verdict}"));
+    }
+
+    fn record(&mut self, kind: ActionKind, i: usize, j: usize, value: u32, line: usize) {
+        self.actions.push(Action {
+            kind,
+            i,
+            j,
+            value,
+            memory: 0,
+            temp_idx: 0,
+            thread_id: 0,
+            origin: i,
+            note: None,
+            line: Some(line),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_heap(values: &[u32]) -> bool {
+        (1..values.len()).all(|i| values[(i - 1) / 2] <= values[i])
+    }
+
+    /// `initial` after the recorded actions, played the way the engine plays them
+    fn replay(initial: &[u32], actions: &[Action]) -> Vec<u32> {
+        let mut values = initial.to_vec();
+        for action in actions {
+            match action.kind {
+                ActionKind::Swap => values.swap(action.i, action.j),
+                ActionKind::Append => values.push(action.value),
+                ActionKind::RemoveLast => {
+                    values.pop();
+                }
+                ActionKind::Assign => values[action.i] = action.value,
+                _ => {}
+            }
+        }
+        values
+    }
+
+    #[test]
+    fn operations_keep_the_heap_order() {
+        let initial = vec![9, 4, 7, 1, 8, 2, 6, 3, 5, 0];
+        let mut values = initial.clone();
+        let mut actions = Vec::new();
+        let mut heap = HeapRecorder { values: &mut values, actions: &mut actions };
+        heap.heapify();
+        assert!(is_heap(heap.values));
+
+        for value in [5, 0, 11] {
+            heap.apply(HeapOp::Push(value));
+            assert!(is_heap(heap.values));
+        }
+        let mut popped = Vec::new();
+        for _ in 0..4 {
+            popped.push(heap.values[0]);
+            heap.apply(HeapOp::Pop);
+            assert!(is_heap(heap.values));
+        }
+        assert_eq!(popped, [0, 0, 1, 2]);
+
+        let last = heap.values.len() - 1;
+        heap.apply(HeapOp::DecreaseKey { idx: last, value: 0 });
+        assert!(is_heap(heap.values));
+        assert_eq!(heap.values[0], 0);
+
+        assert!(actions.iter().all(|a| a.line.is_some_and(|line| line < PSEUDOCODE.len())));
+        assert_eq!(replay(&initial, &actions), values);
+    }
+
+    #[test]
+    fn a_key_that_does_not_decrease_is_a_recorded_no_op() {
+        let mut values = vec![1, 3, 2];
+        let mut actions = Vec::new();
+        let mut heap = HeapRecorder { values: &mut values, actions: &mut actions };
+        heap.apply(HeapOp::DecreaseKey { idx: 1, value: 5 });
+        heap.apply(HeapOp::DecreaseKey { idx: 2, value: 2 });
+        heap.apply(HeapOp::DecreaseKey { idx: 7, value: 0 });
+        assert_eq!(values, [1, 3, 2]);
+        assert_eq!(actions.len(), 2);
+        for action in &actions {
+            assert_eq!((action.kind, action.line), (ActionKind::Compare, Some(LINE_NOT_SMALLER)));
+            assert!(action.note.as_deref().is_some_and(|note| note.contains("not smaller")));
+        }
+    }
+}
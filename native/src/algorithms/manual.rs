@@ -0,0 +1,195 @@
+use crate::engine::{Action, ActionKind};
+
+use super::{annotate, Algorithm};
+
+/// Step of a sort done by hand, from clicks on the bars or the panel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManualOp {
+    Pick(usize), // Select a bar; picking a second one compares the two
+    Swap,        // Exchange the pair compared last
+}
+
+/// The only moves a manual sort has, as shown in the pseudocode panel
+pub const PSEUDOCODE: &[&str] = &[
+    "repeat until sorted:",
+    "    pick i < j and compare a[i] with a[j]",
+    "    if a[i] > a[j]: swap(a[i], a[j])",
+];
+const LINE_COMPARE: usize = 1;
+const LINE_SWAP: usize = 2;
+
+/// Comparisons and array writes a built-in sort needs on the same input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Baseline {
+    pub algorithm: Algorithm,
+    pub comparisons: usize,
+    pub writes: usize, // See `writes`
+}
+
+impl Baseline {
+    /// Count what the engine would count playing `actions`
+    pub fn from_actions(algorithm: Algorithm, actions: &[Action]) -> Self {
+        let comparisons = actions
+            .iter()
+            .filter(|a| matches!(a.kind, ActionKind::Compare | ActionKind::CompareExchange { .. }))
+            .count();
+        Self { algorithm, comparisons, writes: writes(actions) }
+    }
+}
+
+/// Array slots `actions` write: two per swap, one per write back from a temp array.
+/// Swap-based and merge-based sorts, and a sort done by hand, compare fairly in these units.
+pub fn writes(actions: &[Action]) -> usize {
+    actions
+        .iter()
+        .map(|a| match a.kind {
+            ActionKind::Swap | ActionKind::CompareExchange { swap: true } => 2,
+            ActionKind::Write => 1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Fewest comparisons any comparison sort can guarantee on n elements: log2(n!), rounded up
+pub fn lower_bound(n: usize) -> usize {
+    (2..=n).map(|k| (k as f64).log2()).sum::<f64>().ceil() as usize
+}
+
+/// A sort done by hand: the pick between clicks, and the built-in sorts it is measured against
+#[derive(Clone, Debug, Default)]
+pub struct ManualRun {
+    pub selected: Option<usize>,          // First bar of the next comparison
+    pub compared: Option<(usize, usize)>, // Pair (i < j) compared last, offered for a swap
+    pub sorted: bool,
+    pub baselines: Vec<Baseline>, // Built-in sorts on the same initial values
+}
+
+impl ManualRun {
+    pub fn new(baselines: Vec<Baseline>) -> Self {
+        Self { baselines, ..Default::default() }
+    }
+
+    /// The built-in sort with the fewest comparisons
+    pub fn best(&self) -> Option<&Baseline> {
+        self.baselines.iter().min_by_key(|b| (b.comparisons, b.writes))
+    }
+
+    /// Record the actions `op` amounts to on `values`, the array after every recorded action has played.
+    /// A Done action follows once the array is sorted; nothing is recorded after that.
+    pub fn apply(&mut self, op: ManualOp, values: &[u32], actions: &mut Vec<Action>) {
+        if self.sorted {
+            return;
+        }
+        let mut values = values.to_vec();
+        match op {
+            ManualOp::Pick(index) if index >= values.len() => return,
+            ManualOp::Pick(index) => match self.selected.take() {
+                None => {
+                    self.selected = Some(index);
+                    return;
+                }
+                // Picking the selected bar again drops the selection
+                Some(first) if first == index => return,
+                Some(first) => {
+                    let (i, j) = (first.min(index), first.max(index));
+                    record(actions, ActionKind::Compare, i, j, LINE_COMPARE);
+                    let (a, b) = (values[i], values[j]);
+                    let verdict = if a <= b { "in order" } else { "out of order" };
+                    annotate(actions, format!("compare a[{i}]={a} with a[{j}]={b}: {verdict}"));
+                    self.compared = Some((i, j));
+                }
+            },
+            ManualOp::Swap => {
+                let Some((i, j)) = self.compared.take() else {
+                    return;
+                };
+                record(actions, ActionKind::Swap, i, j, LINE_SWAP);
+                annotate(actions, format!("swap a[{i}]={} and a[{j}]={}", values[i], values[j]));
+                values.swap(i, j);
+            }
+        }
+        if values.windows(2).all(|w| w[0] <= w[1]) {
+            self.sorted = true;
+            self.selected = None;
+            self.compared = None;
+            record(actions, ActionKind::Done, 0, 0, 0);
+            annotate(actions, "sorted".to_string());
+        }
+    }
+}
+
+fn record(actions: &mut Vec<Action>, kind: ActionKind, i: usize, j: usize, line: usize) {
+    actions.push(Action {
+        kind,
+        i,
+        j,
+        value: 0,
+        memory: 0,
+        temp_idx: 0,
+        thread_id: 0,
+        origin: i,
+        note: None,
+        line: Some(line),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lower_bound_is_log2_of_n_factorial_rounded_up() {
+        let bounds: Vec<usize> = (0..=6).map(lower_bound).collect();
+        assert_eq!(bounds, [0, 0, 1, 3, 5, 7, 10]);
+        assert_eq!(lower_bound(10), 22); // log2(3628800) = 21.79
+    }
+
+    /// Apply `op`, then play the swaps it recorded on `values` as the engine would
+    fn play(run: &mut ManualRun, op: ManualOp, values: &mut [u32], actions: &mut Vec<Action>) {
+        let from = actions.len();
+        run.apply(op, values, actions);
+        for action in &actions[from..] {
+            if action.kind == ActionKind::Swap {
+                values.swap(action.i, action.j);
+            }
+        }
+    }
+
+    #[test]
+    fn apply_records_compares_and_swaps_until_sorted() {
+        let mut run = ManualRun::default();
+        let mut values = vec![3, 1, 2];
+        let mut actions = Vec::new();
+
+        // Nothing to swap yet, and picks off the array or of the same bar twice record nothing
+        for op in [ManualOp::Swap, ManualOp::Pick(3), ManualOp::Pick(2), ManualOp::Pick(2)] {
+            run.apply(op, &values, &mut actions);
+        }
+        assert!(actions.is_empty() && run.selected.is_none());
+
+        play(&mut run, ManualOp::Pick(2), &mut values, &mut actions);
+        assert_eq!(run.selected, Some(2));
+        // Picked in either order, the pair is compared low index first
+        play(&mut run, ManualOp::Pick(0), &mut values, &mut actions);
+        assert_eq!((actions.len(), run.compared), (1, Some((0, 2))));
+        assert_eq!((actions[0].kind, actions[0].i, actions[0].j, actions[0].line), (ActionKind::Compare, 0, 2, Some(LINE_COMPARE)));
+        assert_eq!(actions[0].note.as_deref(), Some("compare a[0]=3 with a[2]=2: out of order"));
+        play(&mut run, ManualOp::Swap, &mut values, &mut actions);
+        assert_eq!((actions[1].kind, actions[1].line, run.compared), (ActionKind::Swap, Some(LINE_SWAP), None));
+        assert_eq!(values, [2, 1, 3]);
+        assert!(!run.sorted);
+
+        for op in [ManualOp::Pick(0), ManualOp::Pick(1), ManualOp::Swap] {
+            play(&mut run, op, &mut values, &mut actions);
+        }
+        assert_eq!(values, [1, 2, 3]);
+        assert!(run.sorted);
+        let kinds: Vec<ActionKind> = actions.iter().map(|a| a.kind).collect();
+        assert_eq!(kinds, [ActionKind::Compare, ActionKind::Swap, ActionKind::Compare, ActionKind::Swap, ActionKind::Done]);
+        assert_eq!(writes(&actions), 4);
+
+        // A sorted run ignores further input
+        play(&mut run, ManualOp::Pick(0), &mut values, &mut actions);
+        assert_eq!((actions.len(), run.selected), (5, None));
+    }
+}
@@ -1,18 +1,63 @@
+use std::cmp::Ordering;
+
+use crate::element::Keys;
 use crate::engine::{Action, ActionKind};
 
+use super::annotate;
+
 /// Size of u32 in bytes for memory tracking
 const ELEMENT_SIZE: usize = 4;
 
-/// Sequential merge sort - generates actions for single-threaded visualization
-pub fn merge_sort_actions(values: &[u32]) -> Vec<Action> {
-    let mut actions = Vec::new();
-    let mut arr = values.to_vec();
-    let n = arr.len();
-    let mut current_memory: usize = 0;
-    merge_sort_recursive(&mut arr, 0, n, &mut actions, &mut current_memory, 0);
-    
-    // Mark all as done
-    actions.push(Action {
+/// Merge sort as shown in the pseudocode panel; actions carry indices into it
+pub const PSEUDOCODE: &[&str] = &[
+    "merge_sort(a, lo, hi):",
+    "    if hi - lo <= 1: return",
+    "    mid = (lo + hi) / 2",
+    "    merge_sort(a, lo, mid)",
+    "    merge_sort(a, mid, hi)",
+    "    merge(a, lo, mid, hi)",
+    "",
+    "merge(a, lo, mid, hi):",
+    "    i, j, temp = lo, mid, []",
+    "    while i < mid and j < hi:",
+    "        if a[i] <= a[j]: temp.push(a[i++])",
+    "        else: temp.push(a[j++])",
+    "    while i < mid: temp.push(a[i++])",
+    "    while j < hi: temp.push(a[j++])",
+    "    a[lo..hi] = temp",
+    "    free temp",
+];
+const LINE_CALL: usize = 0;
+const LINE_SORT_LEFT: usize = 3;
+const LINE_SORT_RIGHT: usize = 4;
+const LINE_MERGE: usize = 5;
+const LINE_MERGE_LOOP: usize = 9;
+const LINE_TAKE_LEFT: usize = 10;
+const LINE_TAKE_RIGHT: usize = 11;
+const LINE_LEFT_REST: usize = 12;
+const LINE_RIGHT_REST: usize = 13;
+const LINE_COPY_BACK: usize = 14;
+const LINE_FREE: usize = 15;
+
+/// Recursion boundary for the half-open range `[left, right)` of the whole array.
+/// A call starts on the caller's line and returns once its merge is done.
+fn call_event(kind: ActionKind, left: usize, right: usize, thread_id: usize, line: usize, note: Option<String>) -> Action {
+    Action {
+        kind,
+        i: left,
+        j: right,
+        value: 0,
+        memory: 0,
+        temp_idx: 0,
+        thread_id,
+        origin: 0,
+        note,
+        line: Some(line),
+    }
+}
+
+fn done() -> Action {
+    Action {
         kind: ActionKind::Done,
         i: 0,
         j: 0,
@@ -20,172 +65,157 @@ pub fn merge_sort_actions(values: &[u32]) -> Vec<Action> {
         memory: 0,
         temp_idx: 0,
         thread_id: 0,
-    });
-    
-    actions
+        origin: 0,
+        note: None,
+        line: None,
+    }
 }
 
-fn merge_sort_recursive(
-    arr: &mut [u32], 
-    left: usize, 
-    right: usize, 
-    actions: &mut Vec<Action>, 
-    current_memory: &mut usize,
+/// Merge sort over `arr` (element ids) recorded as actions.
+/// `arr[0]` sits at index `offset` of the whole array, so a thread can sort its own chunk.
+struct MergeRecorder<'a, 'k, T, F> {
+    arr: &'a mut [usize],
+    keys: &'a mut Keys<'k, T, F>,
+    actions: Vec<Action>,
+    offset: usize,
     thread_id: usize,
-) {
-    if right - left <= 1 {
-        return;
-    }
-
-    let mid = left + (right - left) / 2;
-    merge_sort_recursive(arr, left, mid, actions, current_memory, thread_id);
-    merge_sort_recursive(arr, mid, right, actions, current_memory, thread_id);
-    merge(arr, left, mid, right, actions, current_memory, thread_id);
+    parallel: bool,        // Call captions name the thread
+    current_memory: usize, // Bytes of temp buffers live right now
 }
 
-fn merge(
-    arr: &mut [u32], 
-    left: usize, 
-    mid: usize, 
-    right: usize, 
-    actions: &mut Vec<Action>, 
-    current_memory: &mut usize,
-    thread_id: usize,
-) {
-    let temp_size = right - left;
-    let temp_bytes = temp_size * ELEMENT_SIZE;
-    
-    // Allocate temporary array - add to current memory
-    *current_memory += temp_bytes;
-    
-    // Store (value, original_index) pairs so we can track where values came from
-    let mut temp: Vec<(u32, usize)> = Vec::with_capacity(temp_size);
-    let mut i = left;
-    let mut j = mid;
-    let mut temp_idx = 0;
+impl<'a, 'k, T, F: FnMut(&T, &T) -> Ordering> MergeRecorder<'a, 'k, T, F> {
+    fn new(arr: &'a mut [usize], keys: &'a mut Keys<'k, T, F>, offset: usize, thread_id: usize, parallel: bool) -> Self {
+        Self { arr, keys, actions: Vec::new(), offset, thread_id, parallel, current_memory: 0 }
+    }
 
-    while i < mid && j < right {
-        actions.push(Action {
-            kind: ActionKind::Compare,
+    /// Action at whole-array index `i` with this recorder's thread and memory
+    fn action(&self, kind: ActionKind, i: usize, line: usize) -> Action {
+        Action {
+            kind,
             i,
-            j,
+            j: 0,
             value: 0,
-            memory: *current_memory,
+            memory: self.current_memory,
             temp_idx: 0,
-            thread_id,
-        });
-        
-        if arr[i] <= arr[j] {
-            // Push to temp array with animation
-            actions.push(Action {
-                kind: ActionKind::TempPush,
-                i,  // Source index in main array
-                j: 0,
-                value: arr[i],
-                memory: *current_memory,
-                temp_idx,
-                thread_id,
-            });
-            temp.push((arr[i], i));
-            temp_idx += 1;
-            i += 1;
-        } else {
-            // Push to temp array with animation
-            actions.push(Action {
-                kind: ActionKind::TempPush,
-                i: j,  // Source index in main array
-                j: 0,
-                value: arr[j],
-                memory: *current_memory,
-                temp_idx,
-                thread_id,
-            });
-            temp.push((arr[j], j));
-            temp_idx += 1;
-            j += 1;
+            thread_id: self.thread_id,
+            origin: 0,
+            note: None,
+            line: Some(line),
         }
     }
 
-    while i < mid {
-        actions.push(Action {
-            kind: ActionKind::TempPush,
-            i,
-            j: 0,
-            value: arr[i],
-            memory: *current_memory,
-            temp_idx,
-            thread_id,
-        });
-        temp.push((arr[i], i));
-        temp_idx += 1;
-        i += 1;
+    /// Sort `arr[left..right)`, called from pseudocode line `line`
+    fn sort(&mut self, left: usize, right: usize, line: usize) {
+        if right - left <= 1 {
+            return;
+        }
+        let mid = left + (right - left) / 2;
+        let (lo, hi) = (self.offset + left, self.offset + right);
+        let thread = if self.parallel { format!("thread {}: ", self.thread_id) } else { String::new() };
+        let note = format!("{thread}merge_sort(a, {lo}, {hi}): split at {}", self.offset + mid);
+        self.actions.push(call_event(ActionKind::CallEnter, lo, hi, self.thread_id, line, Some(note)));
+        self.sort(left, mid, LINE_SORT_LEFT);
+        self.sort(mid, right, LINE_SORT_RIGHT);
+        self.merge(left, mid, right);
+        let note = format!("{thread}merge_sort(a, {lo}, {hi}) returns, its range merged");
+        self.actions.push(call_event(ActionKind::CallExit, lo, hi, self.thread_id, LINE_MERGE, Some(note)));
     }
 
-    while j < right {
-        actions.push(Action {
-            kind: ActionKind::TempPush,
-            i: j,
-            j: 0,
-            value: arr[j],
-            memory: *current_memory,
-            temp_idx,
-            thread_id,
-        });
-        temp.push((arr[j], j));
-        temp_idx += 1;
-        j += 1;
+    /// Merge the sorted runs `arr[left..mid)` and `arr[mid..right)` through a temp buffer
+    fn merge(&mut self, left: usize, mid: usize, right: usize) {
+        let temp_bytes = (right - left) * ELEMENT_SIZE;
+        // Allocate temporary array - add to current memory
+        self.current_memory += temp_bytes;
+
+        let mut temp: Vec<usize> = Vec::with_capacity(right - left);
+        let (mut i, mut j) = (left, mid);
+        while i < mid && j < right {
+            let (global_i, global_j) = (self.offset + i, self.offset + j);
+            self.actions.push(Action { j: global_j, ..self.action(ActionKind::Compare, global_i, LINE_MERGE_LOOP) });
+            let (left_value, right_value) = (self.keys.bar_value(self.arr[i]), self.keys.bar_value(self.arr[j]));
+            if self.keys.le(self.arr[i], self.arr[j]) {
+                annotate(&mut self.actions, format!("compare a[{global_i}]={left_value} with a[{global_j}]={right_value}: take the left one (ties go left, keeping the sort stable)"));
+                self.push_temp(i, temp.len(), LINE_TAKE_LEFT, None);
+                temp.push(self.arr[i]);
+                i += 1;
+            } else {
+                annotate(&mut self.actions, format!("compare a[{global_i}]={left_value} with a[{global_j}]={right_value}: take the right one"));
+                self.push_temp(j, temp.len(), LINE_TAKE_RIGHT, None);
+                temp.push(self.arr[j]);
+                j += 1;
+            }
+        }
+        while i < mid {
+            let note = format!("right half used up: copy leftover a[{}] into temp", self.offset + i);
+            self.push_temp(i, temp.len(), LINE_LEFT_REST, Some(note));
+            temp.push(self.arr[i]);
+            i += 1;
+        }
+        while j < right {
+            let note = format!("left half used up: copy leftover a[{}] into temp", self.offset + j);
+            self.push_temp(j, temp.len(), LINE_RIGHT_REST, Some(note));
+            temp.push(self.arr[j]);
+            j += 1;
+        }
+
+        // Write back from the front of temp (FIFO)
+        for (k, &id) in temp.iter().enumerate() {
+            self.arr[left + k] = id;
+            let target = self.offset + left + k;
+            let value = self.keys.bar_value(id);
+            let note = format!("write temp[{k}]={value} back to a[{target}]");
+            self.actions.push(Action { value, origin: id, note: Some(note), ..self.action(ActionKind::Write, target, LINE_COPY_BACK) });
+        }
+
+        let (lo, hi) = (self.offset + left, self.offset + right);
+        let note = format!("a[{lo}..{hi}] merged, free the temp buffer");
+        self.actions.push(Action { note: Some(note), ..self.action(ActionKind::TempClear, 0, LINE_FREE) });
+        // Free temporary array - subtract from current memory
+        self.current_memory -= temp_bytes;
     }
 
-    for (k, &(val, _source_idx)) in temp.iter().enumerate() {
-        let target_idx = left + k;
-        arr[target_idx] = val;
-        // Write from temp array to main array
-        actions.push(Action {
-            kind: ActionKind::Write,
-            i: target_idx,  // Target in main array
-            j: 0,
-            value: val,
-            memory: *current_memory,
-            temp_idx: 0,  // Always read from front of temp (FIFO)
-            thread_id,
-        });
+    /// Copy `arr[index]` into temp slot `temp_idx`
+    fn push_temp(&mut self, index: usize, temp_idx: usize, line: usize, note: Option<String>) {
+        let id = self.arr[index];
+        let value = self.keys.bar_value(id);
+        self.actions.push(Action { value, temp_idx, origin: id, note, ..self.action(ActionKind::TempPush, self.offset + index, line) });
     }
-    
-    // Clear temp array after merge
-    actions.push(Action {
-        kind: ActionKind::TempClear,
-        i: 0,
-        j: 0,
-        value: 0,
-        memory: *current_memory,
-        temp_idx: 0,
-        thread_id,
-    });
-    
-    // Free temporary array - subtract from current memory
-    *current_memory -= temp_bytes;
+}
+
+/// Sequential merge sort - generates actions for single-threaded visualization
+pub fn merge_sort_actions<T, F>(mut keys: Keys<T, F>) -> Vec<Action>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let n = keys.len();
+    let mut arr: Vec<usize> = (0..n).collect();
+    let mut recorder = MergeRecorder::new(&mut arr, &mut keys, 0, 0, false);
+    recorder.sort(0, n, LINE_CALL);
+    let mut actions = recorder.actions;
+    actions.push(done());
+    actions
 }
 
 /// Parallel merge sort - simulates multi-threaded execution with interleaved actions
 /// Each thread processes a portion of the array, then threads merge their results
-pub fn parallel_merge_sort_actions(values: &[u32], num_threads: usize) -> Vec<Action> {
-    let n = values.len();
+pub fn parallel_merge_sort_actions<T, F>(mut keys: Keys<T, F>, num_threads: usize) -> Vec<Action>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let n = keys.len();
     if n == 0 {
-        return vec![Action {
-            kind: ActionKind::Done,
-            i: 0, j: 0, value: 0, memory: 0, temp_idx: 0, thread_id: 0,
-        }];
+        return vec![done()];
     }
     
     let num_threads = num_threads.min(n).max(1);
     
     // Phase 1: Split array into chunks, each thread sorts its chunk independently
     // We'll generate actions for each thread, then interleave them to simulate parallelism
-    let chunk_size = (n + num_threads - 1) / num_threads;
+    let chunk_size = n.div_ceil(num_threads);
     
     // Generate actions for each thread sorting its chunk
     let mut thread_actions: Vec<Vec<Action>> = Vec::with_capacity(num_threads);
-    let mut arr = values.to_vec();
+    let mut arr: Vec<usize> = (0..n).collect();
     
     for thread_id in 0..num_threads {
         let start = thread_id * chunk_size;
@@ -196,17 +226,14 @@ pub fn parallel_merge_sort_actions(values: &[u32], num_threads: usize) -> Vec<Ac
             continue;
         }
         
-        let mut actions = Vec::new();
-        let mut current_memory = 0usize;
-        
         // Sort this chunk
         let mut chunk = arr[start..end].to_vec();
-        parallel_merge_sort_chunk(&mut chunk, start, &mut actions, &mut current_memory, thread_id);
+        let mut recorder = MergeRecorder::new(&mut chunk, &mut keys, start, thread_id, true);
+        recorder.sort(0, end - start, LINE_CALL);
+        thread_actions.push(recorder.actions);
         
         // Copy sorted chunk back
         arr[start..end].copy_from_slice(&chunk);
-        
-        thread_actions.push(actions);
     }
     
     // Interleave actions from all threads to simulate parallel execution
@@ -221,12 +248,8 @@ pub fn parallel_merge_sort_actions(values: &[u32], num_threads: usize) -> Vec<Ac
         // Signal the start of a new merge phase
         interleaved.push(Action {
             kind: ActionKind::MergePhase,
-            i: 0,
-            j: 0,
             value: merge_level as u32,
-            memory: 0,
-            temp_idx: 0,
-            thread_id: 0,
+            ..done()
         });
         
         let mut merge_thread_actions: Vec<Vec<Action>> = Vec::new();
@@ -238,13 +261,10 @@ pub fn parallel_merge_sort_actions(values: &[u32], num_threads: usize) -> Vec<Ac
             let right = (left + 2 * step).min(n);
             
             if mid < right {
-                let mut actions = Vec::new();
-                let mut current_memory = 0usize;
-                
                 // Merge arr[left..mid] with arr[mid..right]
-                parallel_merge(&mut arr, left, mid, right, &mut actions, &mut current_memory, thread_id % num_threads);
-                
-                merge_thread_actions.push(actions);
+                let mut recorder = MergeRecorder::new(&mut arr, &mut keys, 0, thread_id % num_threads, true);
+                recorder.merge(left, mid, right);
+                merge_thread_actions.push(recorder.actions);
                 thread_id += 1;
             }
             
@@ -260,291 +280,11 @@ pub fn parallel_merge_sort_actions(values: &[u32], num_threads: usize) -> Vec<Ac
     }
     
     // Mark done
-    interleaved.push(Action {
-        kind: ActionKind::Done,
-        i: 0,
-        j: 0,
-        value: 0,
-        memory: 0,
-        temp_idx: 0,
-        thread_id: 0,
-    });
+    interleaved.push(done());
     
     interleaved
 }
 
-/// Sort a chunk of the array (used by each thread)
-fn parallel_merge_sort_chunk(
-    arr: &mut [u32],
-    global_offset: usize, // Offset in the main array
-    actions: &mut Vec<Action>,
-    current_memory: &mut usize,
-    thread_id: usize,
-) {
-    let n = arr.len();
-    if n <= 1 {
-        return;
-    }
-    
-    parallel_merge_sort_chunk_recursive(arr, 0, n, global_offset, actions, current_memory, thread_id);
-}
-
-fn parallel_merge_sort_chunk_recursive(
-    arr: &mut [u32],
-    left: usize,
-    right: usize,
-    global_offset: usize,
-    actions: &mut Vec<Action>,
-    current_memory: &mut usize,
-    thread_id: usize,
-) {
-    if right - left <= 1 {
-        return;
-    }
-    
-    let mid = left + (right - left) / 2;
-    parallel_merge_sort_chunk_recursive(arr, left, mid, global_offset, actions, current_memory, thread_id);
-    parallel_merge_sort_chunk_recursive(arr, mid, right, global_offset, actions, current_memory, thread_id);
-    parallel_merge_chunk(arr, left, mid, right, global_offset, actions, current_memory, thread_id);
-}
-
-fn parallel_merge_chunk(
-    arr: &mut [u32],
-    left: usize,
-    mid: usize,
-    right: usize,
-    global_offset: usize,
-    actions: &mut Vec<Action>,
-    current_memory: &mut usize,
-    thread_id: usize,
-) {
-    let temp_size = right - left;
-    let temp_bytes = temp_size * ELEMENT_SIZE;
-    *current_memory += temp_bytes;
-    
-    let mut temp: Vec<(u32, usize)> = Vec::with_capacity(temp_size);
-    let mut i = left;
-    let mut j = mid;
-    let mut temp_idx = 0;
-    
-    while i < mid && j < right {
-        let global_i = global_offset + i;
-        let global_j = global_offset + j;
-        
-        actions.push(Action {
-            kind: ActionKind::Compare,
-            i: global_i,
-            j: global_j,
-            value: 0,
-            memory: *current_memory,
-            temp_idx: 0,
-            thread_id,
-        });
-        
-        if arr[i] <= arr[j] {
-            actions.push(Action {
-                kind: ActionKind::TempPush,
-                i: global_i,
-                j: 0,
-                value: arr[i],
-                memory: *current_memory,
-                temp_idx,
-                thread_id,
-            });
-            temp.push((arr[i], i));
-            temp_idx += 1;
-            i += 1;
-        } else {
-            actions.push(Action {
-                kind: ActionKind::TempPush,
-                i: global_j,
-                j: 0,
-                value: arr[j],
-                memory: *current_memory,
-                temp_idx,
-                thread_id,
-            });
-            temp.push((arr[j], j));
-            temp_idx += 1;
-            j += 1;
-        }
-    }
-    
-    while i < mid {
-        let global_i = global_offset + i;
-        actions.push(Action {
-            kind: ActionKind::TempPush,
-            i: global_i,
-            j: 0,
-            value: arr[i],
-            memory: *current_memory,
-            temp_idx,
-            thread_id,
-        });
-        temp.push((arr[i], i));
-        temp_idx += 1;
-        i += 1;
-    }
-    
-    while j < right {
-        let global_j = global_offset + j;
-        actions.push(Action {
-            kind: ActionKind::TempPush,
-            i: global_j,
-            j: 0,
-            value: arr[j],
-            memory: *current_memory,
-            temp_idx,
-            thread_id,
-        });
-        temp.push((arr[j], j));
-        temp_idx += 1;
-        j += 1;
-    }
-    
-    for (k, &(val, _)) in temp.iter().enumerate() {
-        let target_idx = global_offset + left + k;
-        arr[left + k] = val;
-        actions.push(Action {
-            kind: ActionKind::Write,
-            i: target_idx,
-            j: 0,
-            value: val,
-            memory: *current_memory,
-            temp_idx: 0,
-            thread_id,
-        });
-    }
-    
-    actions.push(Action {
-        kind: ActionKind::TempClear,
-        i: 0,
-        j: 0,
-        value: 0,
-        memory: *current_memory,
-        temp_idx: 0,
-        thread_id,
-    });
-    
-    *current_memory -= temp_bytes;
-}
-
-/// Merge two sorted regions in the main array
-fn parallel_merge(
-    arr: &mut [u32],
-    left: usize,
-    mid: usize,
-    right: usize,
-    actions: &mut Vec<Action>,
-    current_memory: &mut usize,
-    thread_id: usize,
-) {
-    let temp_size = right - left;
-    let temp_bytes = temp_size * ELEMENT_SIZE;
-    *current_memory += temp_bytes;
-    
-    let mut temp: Vec<u32> = Vec::with_capacity(temp_size);
-    let mut i = left;
-    let mut j = mid;
-    let mut temp_idx = 0;
-    
-    while i < mid && j < right {
-        actions.push(Action {
-            kind: ActionKind::Compare,
-            i,
-            j,
-            value: 0,
-            memory: *current_memory,
-            temp_idx: 0,
-            thread_id,
-        });
-        
-        if arr[i] <= arr[j] {
-            actions.push(Action {
-                kind: ActionKind::TempPush,
-                i,
-                j: 0,
-                value: arr[i],
-                memory: *current_memory,
-                temp_idx,
-                thread_id,
-            });
-            temp.push(arr[i]);
-            temp_idx += 1;
-            i += 1;
-        } else {
-            actions.push(Action {
-                kind: ActionKind::TempPush,
-                i: j,
-                j: 0,
-                value: arr[j],
-                memory: *current_memory,
-                temp_idx,
-                thread_id,
-            });
-            temp.push(arr[j]);
-            temp_idx += 1;
-            j += 1;
-        }
-    }
-    
-    while i < mid {
-        actions.push(Action {
-            kind: ActionKind::TempPush,
-            i,
-            j: 0,
-            value: arr[i],
-            memory: *current_memory,
-            temp_idx,
-            thread_id,
-        });
-        temp.push(arr[i]);
-        temp_idx += 1;
-        i += 1;
-    }
-    
-    while j < right {
-        actions.push(Action {
-            kind: ActionKind::TempPush,
-            i: j,
-            j: 0,
-            value: arr[j],
-            memory: *current_memory,
-            temp_idx,
-            thread_id,
-        });
-        temp.push(arr[j]);
-        temp_idx += 1;
-        j += 1;
-    }
-    
-    for (k, &val) in temp.iter().enumerate() {
-        let target_idx = left + k;
-        arr[target_idx] = val;
-        actions.push(Action {
-            kind: ActionKind::Write,
-            i: target_idx,
-            j: 0,
-            value: val,
-            memory: *current_memory,
-            temp_idx: 0,
-            thread_id,
-        });
-    }
-    
-    actions.push(Action {
-        kind: ActionKind::TempClear,
-        i: 0,
-        j: 0,
-        value: 0,
-        memory: *current_memory,
-        temp_idx: 0,
-        thread_id,
-    });
-    
-    *current_memory -= temp_bytes;
-}
-
 /// Interleave actions from multiple threads to simulate parallel execution
 fn interleave_actions(thread_actions: &[Vec<Action>]) -> Vec<Action> {
     let mut result = Vec::new();
@@ -556,7 +296,7 @@ fn interleave_actions(thread_actions: &[Vec<Action>]) -> Vec<Action> {
         // Take one action from each thread that still has actions
         for (thread_idx, actions) in thread_actions.iter().enumerate() {
             if indices[thread_idx] < actions.len() {
-                result.push(actions[indices[thread_idx]]);
+                result.push(actions[indices[thread_idx]].clone());
                 indices[thread_idx] += 1;
                 any_remaining = true;
             }
@@ -569,3 +309,58 @@ fn interleave_actions(thread_actions: &[Vec<Action>]) -> Vec<Action> {
     
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Original index of the element in each slot after the writes and swaps in `actions`
+    fn replay(n: usize, actions: &[Action]) -> Vec<usize> {
+        let mut slots: Vec<usize> = (0..n).collect();
+        for action in actions {
+            match action.kind {
+                ActionKind::Write => slots[action.i] = action.origin,
+                ActionKind::Swap => slots.swap(action.i, action.j),
+                _ => {}
+            }
+        }
+        slots
+    }
+
+    fn check(keys: &[u32], actions: &[Action]) {
+        let slots = replay(keys.len(), actions);
+        for pair in slots.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert!(keys[a] < keys[b] || (keys[a] == keys[b] && a < b), "{slots:?} not sorted stably");
+        }
+        assert_eq!(actions.last().map(|a| a.kind), Some(ActionKind::Done));
+    }
+
+    #[test]
+    fn sequential_and_parallel_sort_stably() {
+        let keys = [5, 3, 9, 3, 1, 5, 8, 2, 7, 3, 6, 1, 4];
+        for n in 0..=keys.len() {
+            let keys = &keys[..n];
+            check(keys, &merge_sort_actions(Keys::new(keys, keys, u32::cmp)));
+            for threads in [1, 3, 8] {
+                check(keys, &parallel_merge_sort_actions(Keys::new(keys, keys, u32::cmp), threads));
+            }
+        }
+    }
+
+    #[test]
+    fn calls_highlight_the_recursive_lines() {
+        let keys = [5, 3, 9, 3, 1, 5, 8, 2, 7];
+        let actions = merge_sort_actions(Keys::new(&keys, &keys, u32::cmp));
+        for action in &actions {
+            match action.kind {
+                ActionKind::Done => assert_eq!(action.line, None),
+                ActionKind::CallEnter => assert!(matches!(action.line, Some(LINE_CALL | LINE_SORT_LEFT | LINE_SORT_RIGHT))),
+                ActionKind::CallExit => assert_eq!(action.line, Some(LINE_MERGE)),
+                _ => assert!(action.line.is_some_and(|line| line > LINE_MERGE && !PSEUDOCODE[line].is_empty()), "{action:?}"),
+            }
+        }
+        let root = actions.iter().filter(|a| a.kind == ActionKind::CallEnter && a.line == Some(LINE_CALL)).count();
+        assert_eq!(root, 1);
+    }
+}
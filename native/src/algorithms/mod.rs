@@ -1,2 +1,307 @@
 pub mod bubble;
+pub mod heap;
+pub mod manual;
 pub mod merge;
+pub mod network;
+pub mod search;
+pub mod select;
+
+use crate::engine::Step;
+use crate::{dp, graph, hash, text, tree};
+
+/// Attach a caption to the action or event just pushed
+pub fn annotate<S: Step>(steps: &mut [S], note: String) {
+    if let Some(step) = steps.last_mut() {
+        *step.note_mut() = Some(note);
+    }
+}
+
+/// Family of algorithms sharing one recorded stream and one view
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Subsystem {
+    Array, // Sorts, searches, selections, the priority queue and manual sorting: the shared action stream
+    Graph,
+    Dp,
+    Text,
+    Tree,
+    Hash,
+}
+
+/// Algorithm whose actions the engine plays back
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    MergeSort,
+    BitonicSort,
+    OddEvenMergeSort,
+    PairwiseSort,
+    ManualSort,
+    LinearSearch,
+    BinarySearch,
+    TernarySearch,
+    InterpolationSearch,
+    JumpSearch,
+    ExponentialSearch,
+    Quickselect,
+    Introselect,
+    MedianOfMedians,
+    PriorityQueue,
+    Bfs,
+    Dfs,
+    Dijkstra,
+    AStar,
+    Kruskal,
+    Prim,
+    Lcs,
+    EditDistance,
+    Knapsack,
+    Lis,
+    NaiveMatch,
+    Kmp,
+    ZFunction,
+    RabinKarp,
+    SuffixArray,
+    Bst,
+    AvlTree,
+    RedBlackTree,
+    LinearProbing,
+    QuadraticProbing,
+    DoubleHashing,
+    RobinHoodHashing,
+    SeparateChaining,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 38] = [
+        Algorithm::MergeSort,
+        Algorithm::BitonicSort,
+        Algorithm::OddEvenMergeSort,
+        Algorithm::PairwiseSort,
+        Algorithm::ManualSort,
+        Algorithm::LinearSearch,
+        Algorithm::BinarySearch,
+        Algorithm::TernarySearch,
+        Algorithm::InterpolationSearch,
+        Algorithm::JumpSearch,
+        Algorithm::ExponentialSearch,
+        Algorithm::Quickselect,
+        Algorithm::Introselect,
+        Algorithm::MedianOfMedians,
+        Algorithm::PriorityQueue,
+        Algorithm::Bfs,
+        Algorithm::Dfs,
+        Algorithm::Dijkstra,
+        Algorithm::AStar,
+        Algorithm::Kruskal,
+        Algorithm::Prim,
+        Algorithm::Lcs,
+        Algorithm::EditDistance,
+        Algorithm::Knapsack,
+        Algorithm::Lis,
+        Algorithm::NaiveMatch,
+        Algorithm::Kmp,
+        Algorithm::ZFunction,
+        Algorithm::RabinKarp,
+        Algorithm::SuffixArray,
+        Algorithm::Bst,
+        Algorithm::AvlTree,
+        Algorithm::RedBlackTree,
+        Algorithm::LinearProbing,
+        Algorithm::QuadraticProbing,
+        Algorithm::DoubleHashing,
+        Algorithm::RobinHoodHashing,
+        Algorithm::SeparateChaining,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Algorithm::MergeSort => "Merge Sort",
+            Algorithm::BitonicSort => "Bitonic Sorting Network",
+            Algorithm::OddEvenMergeSort => "Batcher Odd-Even Merge Network",
+            Algorithm::PairwiseSort => "Pairwise Sorting Network",
+            Algorithm::ManualSort => "Manual Sort (sort it yourself)",
+            Algorithm::LinearSearch => "Linear Search",
+            Algorithm::BinarySearch => "Binary Search",
+            Algorithm::TernarySearch => "Ternary Search",
+            Algorithm::InterpolationSearch => "Interpolation Search",
+            Algorithm::JumpSearch => "Jump Search",
+            Algorithm::ExponentialSearch => "Exponential Search",
+            Algorithm::Quickselect => "Quickselect",
+            Algorithm::Introselect => "Introselect (nth_element)",
+            Algorithm::MedianOfMedians => "Median of Medians",
+            Algorithm::PriorityQueue => "Priority Queue (min-heap)",
+            Algorithm::Bfs => "Breadth-First Search",
+            Algorithm::Dfs => "Depth-First Search",
+            Algorithm::Dijkstra => "Dijkstra",
+            Algorithm::AStar => "A*",
+            Algorithm::Kruskal => "Kruskal MST (union-find)",
+            Algorithm::Prim => "Prim MST",
+            Algorithm::Lcs => "Longest Common Subsequence",
+            Algorithm::EditDistance => "Edit Distance",
+            Algorithm::Knapsack => "0/1 Knapsack",
+            Algorithm::Lis => "Longest Increasing Subsequence",
+            Algorithm::NaiveMatch => "Naive String Match",
+            Algorithm::Kmp => "Knuth–Morris–Pratt",
+            Algorithm::ZFunction => "Z-Algorithm (Z-function)",
+            Algorithm::RabinKarp => "Rabin–Karp",
+            Algorithm::SuffixArray => "Suffix Array Search",
+            Algorithm::Bst => "Binary Search Tree",
+            Algorithm::AvlTree => "AVL Tree",
+            Algorithm::RedBlackTree => "Red-Black Tree",
+            Algorithm::LinearProbing => "Hash Table (linear probing)",
+            Algorithm::QuadraticProbing => "Hash Table (quadratic probing)",
+            Algorithm::DoubleHashing => "Hash Table (double hashing)",
+            Algorithm::RobinHoodHashing => "Hash Table (Robin Hood)",
+            Algorithm::SeparateChaining => "Hash Table (separate chaining)",
+        }
+    }
+
+    /// Sorts leave every bar in final position once done
+    pub fn is_sort(self) -> bool {
+        matches!(self, Algorithm::MergeSort) || self.is_network()
+    }
+
+    /// Sorting networks run a fixed schedule of compare-exchange layers
+    pub fn is_network(self) -> bool {
+        matches!(self, Algorithm::BitonicSort | Algorithm::OddEvenMergeSort | Algorithm::PairwiseSort)
+    }
+
+    /// Searches run on the sorted input instead of sorting it
+    pub fn is_search(self) -> bool {
+        matches!(
+            self,
+            Algorithm::LinearSearch
+                | Algorithm::BinarySearch
+                | Algorithm::TernarySearch
+                | Algorithm::InterpolationSearch
+                | Algorithm::JumpSearch
+                | Algorithm::ExponentialSearch
+        )
+    }
+
+    /// Selections stop once the k-th smallest element is in place
+    pub fn is_selection(self) -> bool {
+        matches!(self, Algorithm::Quickselect | Algorithm::Introselect | Algorithm::MedianOfMedians)
+    }
+
+    /// Graph algorithms play their own event stream over a generated graph
+    pub fn is_graph(self) -> bool {
+        matches!(
+            self,
+            Algorithm::Bfs | Algorithm::Dfs | Algorithm::Dijkstra | Algorithm::AStar | Algorithm::Kruskal | Algorithm::Prim
+        )
+    }
+
+    /// Spanning-tree algorithms cover the whole graph instead of finding a start→goal path
+    pub fn is_mst(self) -> bool {
+        matches!(self, Algorithm::Kruskal | Algorithm::Prim)
+    }
+
+    /// Dynamic programming fills a table instead of touching the array
+    pub fn is_dp(self) -> bool {
+        matches!(self, Algorithm::Lcs | Algorithm::EditDistance | Algorithm::Knapsack | Algorithm::Lis)
+    }
+
+    /// String matching slides a pattern along a text
+    pub fn is_text(self) -> bool {
+        matches!(
+            self,
+            Algorithm::NaiveMatch | Algorithm::Kmp | Algorithm::ZFunction | Algorithm::RabinKarp | Algorithm::SuffixArray
+        )
+    }
+
+    /// Search trees are driven by interactive insert / delete / search operations
+    pub fn is_tree(self) -> bool {
+        matches!(self, Algorithm::Bst | Algorithm::AvlTree | Algorithm::RedBlackTree)
+    }
+
+    /// Hash tables are driven by interactive insert / lookup / delete operations
+    pub fn is_hash(self) -> bool {
+        matches!(
+            self,
+            Algorithm::LinearProbing
+                | Algorithm::QuadraticProbing
+                | Algorithm::DoubleHashing
+                | Algorithm::RobinHoodHashing
+                | Algorithm::SeparateChaining
+        )
+    }
+
+    /// Which recorded stream the engine plays for this algorithm
+    pub fn subsystem(self) -> Subsystem {
+        match self {
+            _ if self.is_graph() => Subsystem::Graph,
+            _ if self.is_dp() => Subsystem::Dp,
+            _ if self.is_text() => Subsystem::Text,
+            _ if self.is_tree() => Subsystem::Tree,
+            _ if self.is_hash() => Subsystem::Hash,
+            _ => Subsystem::Array,
+        }
+    }
+
+    /// Array algorithms play the shared action stream; the others replay their own events
+    pub fn plays_actions(self) -> bool {
+        self.subsystem() == Subsystem::Array
+    }
+
+    /// Pseudocode listing whose lines the actions point at; empty if none is shown
+    pub fn pseudocode(self) -> &'static [&'static str] {
+        match self {
+            Algorithm::MergeSort => merge::PSEUDOCODE,
+            Algorithm::PriorityQueue => heap::PSEUDOCODE,
+            Algorithm::ManualSort => manual::PSEUDOCODE,
+            _ if self.is_network() => network::PSEUDOCODE,
+            _ if self.is_selection() => select::PSEUDOCODE,
+            _ if self.is_search() => search::pseudocode(self),
+            _ if self.is_graph() => graph::traversal::pseudocode(self),
+            _ if self.is_dp() => dp::tables::pseudocode(self),
+            _ if self.is_text() => text::matching::pseudocode(self),
+            _ if self.is_tree() => tree::model::pseudocode(self),
+            _ if self.is_hash() => hash::table::pseudocode(self),
+            _ => &[],
+        }
+    }
+
+    /// (time, space) complexity shown in the stats panel
+    pub fn complexity(self) -> (&'static str, &'static str) {
+        match self {
+            Algorithm::MergeSort => ("O(n log n)", "O(n)"),
+            Algorithm::BitonicSort => ("O(n log² n) comparators, O(log² n) depth", "O(1)"),
+            Algorithm::OddEvenMergeSort => ("O(n log² n) comparators, O(log² n) depth", "O(1)"),
+            Algorithm::PairwiseSort => ("O(n log² n) comparators, O(log² n) depth", "O(1)"),
+            Algorithm::ManualSort => ("up to you; log₂(n!) comparisons at best", "O(1)"),
+            Algorithm::LinearSearch => ("O(n)", "O(1)"),
+            Algorithm::BinarySearch => ("O(log n)", "O(1)"),
+            Algorithm::TernarySearch => ("O(log n)", "O(1)"),
+            Algorithm::InterpolationSearch => ("O(log log n) avg, O(n) worst", "O(1)"),
+            Algorithm::JumpSearch => ("O(√n)", "O(1)"),
+            Algorithm::ExponentialSearch => ("O(log i)", "O(1)"),
+            Algorithm::Quickselect => ("O(n) avg, O(n²) worst", "O(1)"),
+            Algorithm::Introselect => ("O(n) avg, O(n) worst", "O(1)"),
+            Algorithm::MedianOfMedians => ("O(n)", "O(log n)"),
+            Algorithm::PriorityQueue => ("O(n) heapify, O(log n) per op", "O(n)"),
+            Algorithm::Bfs => ("O(V + E)", "O(V)"),
+            Algorithm::Dfs => ("O(V + E)", "O(V)"),
+            Algorithm::Dijkstra => ("O((V + E) log V)", "O(V)"),
+            Algorithm::AStar => ("O((V + E) log V) worst", "O(V)"),
+            Algorithm::Kruskal => ("O(E log E) + O(E α(V)) finds", "O(V)"),
+            Algorithm::Prim => ("O((V + E) log V)", "O(V)"),
+            Algorithm::Lcs => ("O(nm)", "O(nm)"),
+            Algorithm::EditDistance => ("O(nm)", "O(nm)"),
+            Algorithm::Knapsack => ("O(nW) pseudo-polynomial", "O(nW)"),
+            Algorithm::Lis => ("O(n²)", "O(n)"),
+            Algorithm::NaiveMatch => ("O(nm)", "O(1)"),
+            Algorithm::Kmp => ("O(n + m)", "O(m)"),
+            Algorithm::ZFunction => ("O(n + m)", "O(n + m)"),
+            Algorithm::RabinKarp => ("O(n + m) avg, O(nm) worst", "O(1)"),
+            Algorithm::SuffixArray => ("O(n² log n) build here, O(m log n) query", "O(n)"),
+            Algorithm::Bst => ("O(h) per op, O(n) worst", "O(n)"),
+            Algorithm::AvlTree => ("O(log n) per op", "O(n)"),
+            Algorithm::RedBlackTree => ("O(log n) per op", "O(n)"),
+            Algorithm::LinearProbing
+            | Algorithm::QuadraticProbing
+            | Algorithm::DoubleHashing
+            | Algorithm::RobinHoodHashing
+            | Algorithm::SeparateChaining => ("O(1) avg, O(n) worst per op", "O(n)"),
+        }
+    }
+}
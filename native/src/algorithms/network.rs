@@ -0,0 +1,192 @@
+use std::cmp::Ordering;
+
+use crate::element::Keys;
+use crate::engine::{Action, ActionKind};
+
+use super::Algorithm;
+
+/// Network playback as shown in the pseudocode panel
+pub const PSEUDOCODE: &[&str] = &[
+    "for layer in network:",
+    "    for (i, j) in layer, all at once:",
+    "        if a[i] > a[j]: swap(a[i], a[j])",
+];
+
+/// Comparator: the smaller key ends up at the first index
+pub type Comparator = (usize, usize);
+
+/// Layers of a comparator network for n inputs; comparators within a layer touch disjoint wires.
+/// Networks are built for the next power of two and comparators on padding wires are dropped,
+/// which stays correct because every comparator sends the minimum to the lower wire.
+pub fn network_layers(algorithm: Algorithm, n: usize) -> Vec<Vec<Comparator>> {
+    let size = n.next_power_of_two();
+    let layers = match algorithm {
+        Algorithm::BitonicSort => bitonic(size),
+        Algorithm::OddEvenMergeSort => odd_even_merge(size),
+        Algorithm::PairwiseSort => pairwise(size),
+        _ => Vec::new(),
+    };
+    layers
+        .into_iter()
+        .map(|layer| layer.into_iter().filter(|&(_, j)| j < n).collect::<Vec<_>>())
+        .filter(|layer| !layer.is_empty())
+        .collect()
+}
+
+/// Play the network over the keys: one CompareExchange per comparator, tagged with its layer.
+/// Comparators in a layer get consecutive thread ids, since they could all run at once.
+pub fn network_actions<T, F>(algorithm: Algorithm, mut keys: Keys<T, F>, num_threads: usize) -> Vec<Action>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut actions = Vec::new();
+    let mut arr: Vec<usize> = (0..keys.len()).collect();
+    for (layer, comparators) in network_layers(algorithm, keys.len()).into_iter().enumerate() {
+        for (slot, (i, j)) in comparators.into_iter().enumerate() {
+            let (low, high) = (keys.bar_value(arr[i]), keys.bar_value(arr[j]));
+            let swap = keys.cmp(arr[i], arr[j]) == Ordering::Greater;
+            if swap {
+                arr.swap(i, j);
+            }
+            let verdict = if swap { "out of order, exchange" } else { "in order" };
+            actions.push(Action {
+                kind: ActionKind::CompareExchange { swap },
+                i,
+                j,
+                value: layer as u32,
+                memory: 0,
+                temp_idx: 0,
+                thread_id: slot % num_threads.max(1),
+                origin: 0,
+                note: Some(format!("layer {}: compare a[{i}]={low} with a[{j}]={high}: {verdict}", layer + 1)),
+                line: Some(2),
+            });
+        }
+    }
+    actions.push(Action {
+        kind: ActionKind::Done,
+        i: 0,
+        j: 0,
+        value: 0,
+        memory: 0,
+        temp_idx: 0,
+        thread_id: 0,
+        origin: 0,
+        note: None,
+        line: None,
+    });
+    actions
+}
+
+/// Bitonic sort in its all-ascending form: each merge starts by comparing mirrored
+/// wires of a block, then finishes with half-cleaners
+fn bitonic(size: usize) -> Vec<Vec<Comparator>> {
+    let mut layers = Vec::new();
+    let mut block = 2;
+    while block <= size {
+        layers.push((0..size).map(|i| (i, i ^ (block - 1))).filter(|&(i, l)| l > i).collect());
+        let mut distance = block / 4;
+        while distance >= 1 {
+            layers.push((0..size).map(|i| (i, i ^ distance)).filter(|&(i, l)| l > i).collect());
+            distance /= 2;
+        }
+        block *= 2;
+    }
+    layers
+}
+
+/// Batcher's odd-even merge sort
+fn odd_even_merge(size: usize) -> Vec<Vec<Comparator>> {
+    let mut layers = Vec::new();
+    let mut p = 1;
+    while p < size {
+        let mut k = p;
+        while k >= 1 {
+            let mut layer = Vec::new();
+            let mut j = k % p;
+            while j + k < size {
+                for i in 0..k.min(size - j - k) {
+                    // Only compare wires that belong to the same merge of two 2p-blocks
+                    if (i + j) / (2 * p) == (i + j + k) / (2 * p) {
+                        layer.push((i + j, i + j + k));
+                    }
+                }
+                j += 2 * k;
+            }
+            layers.push(layer);
+            k /= 2;
+        }
+        p *= 2;
+    }
+    layers
+}
+
+/// Parberry's pairwise sorting network
+fn pairwise(size: usize) -> Vec<Vec<Comparator>> {
+    // Walks b over the first `a` wires of every 2a-block starting at `start`
+    fn layer(size: usize, a: usize, start: usize, reach: usize) -> Vec<Comparator> {
+        let mut comparators = Vec::new();
+        let mut b = start;
+        let mut c = 0;
+        while b < size {
+            comparators.push((b - reach, b));
+            b += 1;
+            c = (c + 1) % a;
+            if c == 0 {
+                b += a;
+            }
+        }
+        comparators
+    }
+
+    let mut layers = Vec::new();
+    // Sort pairs, then pairs of pairs, ... by their first elements
+    let mut a = 1;
+    while a < size {
+        layers.push(layer(size, a, a, a));
+        a *= 2;
+    }
+    // Merge back down, fixing the remaining inversions between neighbouring groups
+    a /= 4;
+    let mut e = 1;
+    while a > 0 {
+        let mut d = e;
+        while d > 0 {
+            layers.push(layer(size, a, (d + 1) * a, d * a));
+            d /= 2;
+        }
+        a /= 2;
+        e = 2 * e + 1;
+    }
+    layers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// By the 0-1 principle a comparator network sorts every input if it sorts every input of zeros and ones
+    #[test]
+    fn networks_sort_every_zero_one_input() {
+        for algorithm in [Algorithm::BitonicSort, Algorithm::OddEvenMergeSort, Algorithm::PairwiseSort] {
+            for n in 1..=12 {
+                let layers = network_layers(algorithm, n);
+                for layer in &layers {
+                    let mut wires: Vec<usize> = layer.iter().flat_map(|&(i, j)| [i, j]).collect();
+                    wires.sort_unstable();
+                    wires.dedup();
+                    assert_eq!(wires.len(), 2 * layer.len(), "{algorithm:?} n={n}: a layer reuses a wire");
+                }
+                for bits in 0..1u32 << n {
+                    let mut input: Vec<u32> = (0..n).map(|i| bits >> i & 1).collect();
+                    for &(i, j) in layers.iter().flatten() {
+                        if input[i] > input[j] {
+                            input.swap(i, j);
+                        }
+                    }
+                    assert!(input.is_sorted(), "{algorithm:?} n={n} leaves {bits:0n$b} as {input:?}");
+                }
+            }
+        }
+    }
+}
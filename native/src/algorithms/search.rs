@@ -0,0 +1,391 @@
+use std::cmp::Ordering;
+
+use crate::engine::{Action, ActionKind};
+
+use super::{annotate, Algorithm};
+
+/// Generate actions for searching `target` in the sorted `values`
+pub fn search_actions(algorithm: Algorithm, values: &[u32], target: u32) -> Vec<Action> {
+    let mut actions = Vec::new();
+    match algorithm {
+        Algorithm::LinearSearch => linear_search(values, target, &mut actions),
+        Algorithm::BinarySearch => binary_search(values, target, 0, values.len(), 0, &mut actions),
+        Algorithm::TernarySearch => ternary_search(values, target, &mut actions),
+        Algorithm::InterpolationSearch => interpolation_search(values, target, &mut actions),
+        Algorithm::JumpSearch => jump_search(values, target, &mut actions),
+        Algorithm::ExponentialSearch => exponential_search(values, target, &mut actions),
+        _ => {}
+    }
+    actions
+}
+
+/// Each search as shown in the pseudocode panel; the functions below tag their actions with these lines
+pub fn pseudocode(algorithm: Algorithm) -> &'static [&'static str] {
+    match algorithm {
+        Algorithm::LinearSearch => &[
+            "for i in 0..n:",
+            "    if a[i] == target: return i",
+            "return not found",
+        ],
+        Algorithm::BinarySearch => &[
+            "lo, hi = 0, n",
+            "while lo < hi:",
+            "    mid = (lo + hi) / 2",
+            "    if a[mid] == target: return mid",
+            "    if a[mid] < target: lo = mid + 1",
+            "    else: hi = mid",
+            "return not found",
+        ],
+        Algorithm::TernarySearch => &[
+            "lo, hi = 0, n",
+            "while lo < hi:",
+            "    third = (hi - 1 - lo) / 3; m1, m2 = lo + third, hi - 1 - third",
+            "    if a[m1] == target: return m1",
+            "    if a[m2] == target: return m2",
+            "    if target < a[m1]: hi = m1",
+            "    elif target > a[m2]: lo = m2 + 1",
+            "    else: lo, hi = m1 + 1, m2",
+            "return not found",
+        ],
+        Algorithm::InterpolationSearch => &[
+            "lo, hi = 0, n - 1",
+            "while lo <= hi and a[lo] <= target <= a[hi]:",
+            "    if a[lo] == a[hi]: return lo if a[lo] == target",
+            "    p = lo + (target - a[lo]) * (hi - lo) / (a[hi] - a[lo])",
+            "    if a[p] == target: return p",
+            "    if a[p] < target: lo = p + 1",
+            "    else: hi = p - 1",
+            "return not found",
+        ],
+        Algorithm::JumpSearch => &[
+            "step = √n; prev, end = 0, step",
+            "while a[end - 1] < target:",
+            "    prev, end = end, min(end + step, n); if prev == n: return not found",
+            "if a[end - 1] == target: return end - 1",
+            "for i in prev..end - 1:",
+            "    if a[i] == target: return i",
+            "return not found",
+        ],
+        Algorithm::ExponentialSearch => &[
+            "if a[0] == target: return 0",
+            "bound = 1",
+            "while bound < n and a[bound] < target:",
+            "    bound *= 2",
+            "if bound < n and a[bound] == target: return bound",
+            "lo, hi = bound / 2 + 1, min(bound, n)",
+            "while lo < hi:",
+            "    mid = (lo + hi) / 2",
+            "    if a[mid] == target: return mid",
+            "    if a[mid] < target: lo = mid + 1",
+            "    else: hi = mid",
+            "return not found",
+        ],
+        _ => &[],
+    }
+}
+
+fn search_action(kind: ActionKind, i: usize, j: usize, value: u32, line: usize) -> Action {
+    Action {
+        kind,
+        i,
+        j,
+        value,
+        memory: 0,
+        temp_idx: 0,
+        thread_id: 0,
+        origin: i,
+        note: None,
+        line: Some(line),
+    }
+}
+
+/// Window is the inclusive range `[lo, hi]` still being searched
+fn narrow(lo: usize, hi: usize, line: usize, actions: &mut Vec<Action>) {
+    actions.push(search_action(ActionKind::NarrowRange, lo, hi, 0, line));
+    annotate(actions, format!("target can only be in a[{lo}..={hi}] ({} left)", hi + 1 - lo));
+}
+
+fn probe(values: &[u32], idx: usize, target: u32, line: usize, actions: &mut Vec<Action>) -> u32 {
+    let value = values[idx];
+    actions.push(search_action(ActionKind::Probe, idx, idx, value, line));
+    let verdict = match value.cmp(&target) {
+        Ordering::Equal => "match",
+        Ordering::Less => "too small",
+        Ordering::Greater => "too large",
+    };
+    annotate(actions, format!("probe a[{idx}]={value} against target {target}: {verdict}"));
+    value
+}
+
+fn found(values: &[u32], idx: usize, line: usize, actions: &mut Vec<Action>) {
+    actions.push(search_action(ActionKind::Found, idx, idx, values[idx], line));
+    annotate(actions, format!("found {} at a[{idx}]", values[idx]));
+}
+
+fn not_found(target: u32, line: usize, actions: &mut Vec<Action>) {
+    actions.push(search_action(ActionKind::NotFound, 0, 0, target, line));
+    annotate(actions, format!("{target} is not in the array"));
+}
+
+fn linear_search(values: &[u32], target: u32, actions: &mut Vec<Action>) {
+    let n = values.len();
+    for i in 0..n {
+        narrow(i, n - 1, 0, actions);
+        if probe(values, i, target, 1, actions) == target {
+            return found(values, i, 1, actions);
+        }
+    }
+    not_found(target, 2, actions);
+}
+
+/// Binary search over the half-open range `[lo, hi)`, its listing starting at line `first`
+fn binary_search(values: &[u32], target: u32, mut lo: usize, mut hi: usize, first: usize, actions: &mut Vec<Action>) {
+    if lo < hi {
+        narrow(lo, hi - 1, first, actions);
+    }
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let v = probe(values, mid, target, first + 3, actions);
+        if v == target {
+            return found(values, mid, first + 3, actions);
+        }
+        let line = if v < target {
+            lo = mid + 1;
+            first + 4
+        } else {
+            hi = mid;
+            first + 5
+        };
+        if lo < hi {
+            narrow(lo, hi - 1, line, actions);
+        }
+    }
+    not_found(target, first + 6, actions);
+}
+
+fn ternary_search(values: &[u32], target: u32, actions: &mut Vec<Action>) {
+    let mut lo = 0;
+    let mut hi = values.len();
+    if lo < hi {
+        narrow(lo, hi - 1, 0, actions);
+    }
+    while lo < hi {
+        let third = (hi - 1 - lo) / 3;
+        let m1 = lo + third;
+        let m2 = hi - 1 - third;
+
+        let v1 = probe(values, m1, target, 3, actions);
+        if v1 == target {
+            return found(values, m1, 3, actions);
+        }
+        // A window of one has m1 == m2, already probed
+        let v2 = if m2 == m1 { v1 } else { probe(values, m2, target, 4, actions) };
+        if v2 == target {
+            return found(values, m2, 4, actions);
+        }
+
+        let line = if target < v1 {
+            hi = m1;
+            5
+        } else if target > v2 {
+            lo = m2 + 1;
+            6
+        } else {
+            lo = m1 + 1;
+            hi = m2;
+            7
+        };
+        if lo < hi {
+            narrow(lo, hi - 1, line, actions);
+        }
+    }
+    not_found(target, 8, actions);
+}
+
+fn interpolation_search(values: &[u32], target: u32, actions: &mut Vec<Action>) {
+    if values.is_empty() {
+        return not_found(target, 7, actions);
+    }
+    let mut lo = 0;
+    let mut hi = values.len() - 1;
+    narrow(lo, hi, 0, actions);
+    while lo <= hi && target >= values[lo] && target <= values[hi] {
+        if values[hi] == values[lo] {
+            if probe(values, lo, target, 2, actions) == target {
+                return found(values, lo, 2, actions);
+            }
+            break;
+        }
+
+        // Estimate position assuming uniformly distributed values
+        let span = (values[hi] - values[lo]) as u64;
+        let pos = lo + ((target - values[lo]) as u64 * (hi - lo) as u64 / span) as usize;
+        let v = probe(values, pos, target, 4, actions);
+        if v == target {
+            return found(values, pos, 4, actions);
+        }
+        let line = if v < target {
+            lo = pos + 1;
+            5
+        } else if pos == 0 {
+            break;
+        } else {
+            hi = pos - 1;
+            6
+        };
+        if lo <= hi {
+            narrow(lo, hi, line, actions);
+        }
+    }
+    not_found(target, 7, actions);
+}
+
+fn jump_search(values: &[u32], target: u32, actions: &mut Vec<Action>) {
+    let n = values.len();
+    if n == 0 {
+        return not_found(target, 6, actions);
+    }
+    let step = ((n as f64).sqrt() as usize).max(1);
+
+    // Jump ahead block by block until the block end is not smaller than target
+    let mut prev = 0;
+    let mut end = step.min(n);
+    narrow(prev, n - 1, 0, actions);
+    let mut last = probe(values, end - 1, target, 1, actions);
+    while last < target {
+        prev = end;
+        if prev == n {
+            return not_found(target, 2, actions);
+        }
+        end = (end + step).min(n);
+        narrow(prev, n - 1, 2, actions);
+        last = probe(values, end - 1, target, 1, actions);
+    }
+    if last == target {
+        return found(values, end - 1, 3, actions);
+    }
+
+    // Linear scan inside the block; its last slot was just probed
+    for i in prev..end - 1 {
+        narrow(i, end - 2, 4, actions);
+        if probe(values, i, target, 5, actions) == target {
+            return found(values, i, 5, actions);
+        }
+    }
+    not_found(target, 6, actions);
+}
+
+fn exponential_search(values: &[u32], target: u32, actions: &mut Vec<Action>) {
+    let n = values.len();
+    if n == 0 {
+        return not_found(target, 11, actions);
+    }
+    narrow(0, n - 1, 0, actions);
+    if probe(values, 0, target, 0, actions) == target {
+        return found(values, 0, 0, actions);
+    }
+
+    // Double the bound until it passes the target; a[bound / 2] is already known to be smaller
+    let mut bound = 1;
+    while bound < n {
+        narrow(bound / 2 + 1, n - 1, 2, actions);
+        let v = probe(values, bound, target, 2, actions);
+        if v == target {
+            return found(values, bound, 4, actions);
+        }
+        if v > target {
+            break;
+        }
+        bound *= 2;
+    }
+    binary_search(values, target, bound / 2 + 1, bound.min(n), 5, actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEARCHES: [Algorithm; 6] = [
+        Algorithm::LinearSearch,
+        Algorithm::BinarySearch,
+        Algorithm::TernarySearch,
+        Algorithm::InterpolationSearch,
+        Algorithm::JumpSearch,
+        Algorithm::ExponentialSearch,
+    ];
+
+    /// Index reported by the run, and the indices it probed
+    fn run(algorithm: Algorithm, values: &[u32], target: u32) -> (Option<usize>, Vec<usize>) {
+        let actions = search_actions(algorithm, values, target);
+        let listing = pseudocode(algorithm);
+        for action in &actions {
+            assert!(action.line.is_some_and(|line| line < listing.len()), "{algorithm:?}: {action:?}");
+        }
+        let last = actions.last().expect("a search ends with a verdict");
+        assert!(listing[last.line.unwrap()].contains("return"), "{algorithm:?} ends on {:?}", listing[last.line.unwrap()]);
+        let probes = actions.iter().filter(|a| a.kind == ActionKind::Probe).map(|a| a.i).collect();
+        match last.kind {
+            ActionKind::Found => (Some(last.i), probes),
+            ActionKind::NotFound => (None, probes),
+            kind => panic!("{algorithm:?} ended with {kind:?}"),
+        }
+    }
+
+    /// Most probes `algorithm` may need on n elements
+    fn probe_budget(algorithm: Algorithm, n: usize) -> usize {
+        let log = (usize::BITS - n.leading_zeros()) as usize; // floor(log2 n) + 1
+        match algorithm {
+            Algorithm::LinearSearch | Algorithm::InterpolationSearch => n,
+            Algorithm::BinarySearch => log,
+            Algorithm::TernarySearch => 2 * log,
+            Algorithm::JumpSearch => 2 * (n as f64).sqrt().ceil() as usize + 1,
+            Algorithm::ExponentialSearch => 2 * log + 1,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn every_search_finds_hits_and_rejects_misses() {
+        let inputs: [&[u32]; 5] = [
+            &[],
+            &[7],
+            &[2, 3, 5, 7, 11, 13, 17, 19, 23],
+            &[1, 2, 2, 2, 3, 5, 5, 8, 8, 8, 8, 9],
+            &[4, 4, 4, 4, 4],
+        ];
+        for algorithm in SEARCHES {
+            for values in inputs {
+                let max = values.last().copied().unwrap_or(0);
+                for target in 0..=max + 1 {
+                    let (index, probes) = run(algorithm, values, target);
+                    let context = format!("{algorithm:?} for {target} in {values:?}");
+                    match index {
+                        Some(i) => assert_eq!(values[i], target, "{context}"),
+                        None => assert!(!values.contains(&target), "{context}"),
+                    }
+                    assert!(probes.len() <= probe_budget(algorithm, values.len()), "{context}: {probes:?}");
+                    let mut distinct = probes.clone();
+                    distinct.sort_unstable();
+                    distinct.dedup();
+                    assert_eq!(distinct.len(), probes.len(), "{context} probes twice: {probes:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn first_and_last_elements_take_the_expected_probes() {
+        let values: Vec<u32> = (1..=9).map(|v| v * 10).collect();
+        // Linear stops at the first match; the others meet both ends within their budget
+        assert_eq!(run(Algorithm::LinearSearch, &values, 10), (Some(0), vec![0]));
+        assert_eq!(run(Algorithm::LinearSearch, &values, 90).1.len(), 9);
+        assert_eq!(run(Algorithm::ExponentialSearch, &values, 10), (Some(0), vec![0]));
+        // Uniform values put the first estimate right on target
+        assert_eq!(run(Algorithm::InterpolationSearch, &values, 90), (Some(8), vec![8]));
+        // Blocks of 3 end at 2, 5 and 8; the last block end is the match, probed once
+        assert_eq!(run(Algorithm::JumpSearch, &values, 90), (Some(8), vec![2, 5, 8]));
+        assert_eq!(run(Algorithm::JumpSearch, &values, 40), (Some(3), vec![2, 5, 3]));
+        assert_eq!(run(Algorithm::BinarySearch, &values, 10), (Some(0), vec![4, 2, 1, 0]));
+        assert_eq!(run(Algorithm::TernarySearch, &values, 90), (Some(8), vec![2, 6, 7, 8]));
+    }
+}
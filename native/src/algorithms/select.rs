@@ -0,0 +1,338 @@
+use std::cmp::Ordering;
+
+use crate::element::Keys;
+use crate::engine::{Action, ActionKind};
+
+use super::{annotate, Algorithm};
+
+/// Generate actions that move the k-th smallest element into position `k`.
+/// Stops as soon as it is in place, leaving the rest only partially ordered.
+pub fn select_actions<T, F>(algorithm: Algorithm, mut keys: Keys<T, F>, k: usize) -> Vec<Action>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut actions = Vec::new();
+    let n = keys.len();
+    if n == 0 {
+        return actions;
+    }
+    let k = k.min(n - 1);
+    let mut arr: Vec<usize> = (0..n).collect();
+
+    match algorithm {
+        Algorithm::Quickselect => quickselect(&mut arr, &mut keys, 0, n - 1, k, None, &mut actions),
+        Algorithm::Introselect => {
+            // Fall back to median-of-medians after 2*log2(n) unbalanced partitions
+            let depth_limit = 2 * (usize::BITS - n.leading_zeros()) as usize;
+            quickselect(&mut arr, &mut keys, 0, n - 1, k, Some(depth_limit), &mut actions)
+        }
+        Algorithm::MedianOfMedians => median_of_medians_select(&mut arr, &mut keys, 0, n - 1, k, true, &mut actions),
+        _ => k,
+    };
+    actions
+}
+
+/// Selection as shown in the pseudocode panel; actions carry indices into it
+pub const PSEUDOCODE: &[&str] = &[
+    "select(a, lo, hi, k):",
+    "    loop:",
+    "        if lo == hi: return lo",
+    "        pivot = choose_pivot(a, lo, hi)",
+    "        lt, gt = partition(a, lo, hi, pivot)",
+    "        if lt <= k <= gt: return k",
+    "        if k < lt: hi = lt - 1 else: lo = gt + 1",
+    "",
+    "partition(a, lo, hi, pivot):",
+    "    swap(a[pivot], a[lo])",
+    "    lt, scan, gt = lo, lo + 1, hi",
+    "    while scan <= gt:",
+    "        if a[scan] < a[lt]: swap(a[lt], a[scan]); lt += 1; scan += 1",
+    "        elif a[scan] > a[lt]: swap(a[scan], a[gt]); gt -= 1",
+    "        else: scan += 1",
+    "    return lt, gt",
+    "",
+    "choose_pivot:",
+    "    quickselect: middle index",
+    "    introselect: middle, median of medians after 2·log2(n) splits keeping over 3/4",
+    "    median of medians: insertion-sort groups of 5, select their median",
+];
+const LINE_BASE_CASE: usize = 2;
+const LINE_CHOOSE_PIVOT: usize = 3;
+const LINE_SETTLED: usize = 5;
+const LINE_PARK_PIVOT: usize = 9;
+const LINE_SMALLER: usize = 12;
+const LINE_LARGER: usize = 13;
+
+fn action(kind: ActionKind, i: usize, j: usize, line: usize) -> Action {
+    Action {
+        kind,
+        i,
+        j,
+        value: 0,
+        memory: 0,
+        temp_idx: 0,
+        thread_id: 0,
+        origin: 0,
+        note: None,
+        line: Some(line),
+    }
+}
+
+fn compare<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &[usize],
+    keys: &mut Keys<T, F>,
+    i: usize,
+    j: usize,
+    line: usize,
+    actions: &mut Vec<Action>,
+) -> Ordering {
+    actions.push(action(ActionKind::Compare, i, j, line));
+    keys.cmp(arr[i], arr[j])
+}
+
+fn swap(arr: &mut [usize], i: usize, j: usize, line: usize, actions: &mut Vec<Action>) {
+    if i != j {
+        arr.swap(i, j);
+        actions.push(action(ActionKind::Swap, i, j, line));
+    }
+}
+
+/// Three-way partition of `[lo, hi]` around the element at `pivot_idx`, so runs of
+/// duplicates split evenly. Returns `(lt, gt)`, the range now holding values equal
+/// to the pivot. When `settle` is set that range is marked final; a pivot search
+/// inside a sub-range leaves it unmarked, as positions there are not final.
+fn partition<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [usize],
+    keys: &mut Keys<T, F>,
+    lo: usize,
+    hi: usize,
+    pivot_idx: usize,
+    settle: bool,
+    actions: &mut Vec<Action>,
+) -> (usize, usize) {
+    // Park the pivot at the start of the range
+    swap(arr, pivot_idx, lo, LINE_PARK_PIVOT, actions);
+    let pivot = keys.bar_value(arr[lo]);
+    if settle {
+        actions.push(action(ActionKind::Pivot, lo, lo, LINE_PARK_PIVOT));
+    }
+    annotate(actions, format!("partition a[{lo}..={hi}] around pivot {pivot}, parked at a[{lo}]"));
+
+    // a[lo..lt] < pivot, a[lt..scan] == pivot, a[gt + 1..=hi] > pivot
+    let (mut lt, mut scan, mut gt) = (lo, lo + 1, hi);
+    while scan <= gt {
+        let value = keys.bar_value(arr[scan]);
+        match compare(arr, keys, scan, lt, LINE_SMALLER, actions) {
+            Ordering::Less => {
+                annotate(actions, format!("a[{scan}]={value} < pivot {pivot}: swap into a[{lt}]"));
+                swap(arr, lt, scan, LINE_SMALLER, actions);
+                lt += 1;
+                scan += 1;
+            }
+            Ordering::Greater => {
+                annotate(actions, format!("a[{scan}]={value} > pivot {pivot}: swap out to a[{gt}]"));
+                swap(arr, scan, gt, LINE_LARGER, actions);
+                gt -= 1;
+            }
+            Ordering::Equal => {
+                annotate(actions, format!("a[{scan}]={value} equals pivot {pivot}: leave it"));
+                scan += 1;
+            }
+        }
+    }
+
+    if settle {
+        for p in lt..=gt {
+            actions.push(action(ActionKind::Settled, p, p, LINE_SETTLED));
+        }
+    }
+    (lt, gt)
+}
+
+fn quickselect<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [usize],
+    keys: &mut Keys<T, F>,
+    mut lo: usize,
+    mut hi: usize,
+    k: usize,
+    mut depth_limit: Option<usize>,
+    actions: &mut Vec<Action>,
+) -> usize {
+    loop {
+        if lo == hi {
+            actions.push(action(ActionKind::Settled, lo, lo, LINE_BASE_CASE));
+            return lo;
+        }
+
+        let pivot_idx = match depth_limit {
+            Some(0) => median_of_medians_pivot(arr, keys, lo, hi, actions),
+            _ => lo + (hi - lo) / 2,
+        };
+
+        // Each partition puts its pivot's run of equal values in final sorted position
+        let size = hi - lo + 1;
+        let (lt, gt) = partition(arr, keys, lo, hi, pivot_idx, true, actions);
+        annotate(actions, settled_note(lt, gt, k));
+        if (lt..=gt).contains(&k) {
+            return k;
+        }
+        if k < lt {
+            hi = lt - 1;
+        } else {
+            lo = gt + 1;
+        }
+        // Only splits that keep more than 3/4 of the range count against the limit
+        if let Some(depth) = depth_limit.as_mut() {
+            if 4 * (hi - lo + 1) > 3 * size {
+                *depth = depth.saturating_sub(1);
+            }
+        }
+    }
+}
+
+fn settled_note(lt: usize, gt: usize, k: usize) -> String {
+    let next = if (lt..=gt).contains(&k) {
+        "found the k-th smallest"
+    } else if k < lt {
+        "continue in the left part"
+    } else {
+        "continue in the right part"
+    };
+    let at = if lt == gt { format!("a[{lt}]") } else { format!("a[{lt}..={gt}]") };
+    format!("pivot is final at {at}; k = {k}, so {next}")
+}
+
+/// `settle` is false while selecting a pivot inside a sub-range,
+/// where positions are not final for the whole array.
+fn median_of_medians_select<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [usize],
+    keys: &mut Keys<T, F>,
+    mut lo: usize,
+    mut hi: usize,
+    k: usize,
+    settle: bool,
+    actions: &mut Vec<Action>,
+) -> usize {
+    loop {
+        if lo == hi {
+            if settle {
+                actions.push(action(ActionKind::Settled, lo, lo, LINE_BASE_CASE));
+            }
+            return lo;
+        }
+
+        let pivot_idx = median_of_medians_pivot(arr, keys, lo, hi, actions);
+        let (lt, gt) = partition(arr, keys, lo, hi, pivot_idx, settle, actions);
+        if settle {
+            annotate(actions, settled_note(lt, gt, k));
+        }
+        if (lt..=gt).contains(&k) {
+            return k;
+        }
+        if k < lt {
+            hi = lt - 1;
+        } else {
+            lo = gt + 1;
+        }
+    }
+}
+
+/// Pick a pivot guaranteed to split `[lo, hi]` at least 30/70:
+/// the median of the medians of groups of five.
+fn median_of_medians_pivot<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [usize],
+    keys: &mut Keys<T, F>,
+    lo: usize,
+    hi: usize,
+    actions: &mut Vec<Action>,
+) -> usize {
+    if hi - lo < 5 {
+        insertion_sort(arr, keys, lo, hi, actions);
+        return lo + (hi - lo) / 2;
+    }
+
+    // Move each group's median to the front of the range
+    let mut groups = 0;
+    let mut start = lo;
+    while start <= hi {
+        let end = (start + 4).min(hi);
+        insertion_sort(arr, keys, start, end, actions);
+        swap(arr, lo + groups, start + (end - start) / 2, LINE_CHOOSE_PIVOT, actions);
+        groups += 1;
+        start += 5;
+    }
+
+    // Recursively select the median of the medians
+    let mid = lo + (groups - 1) / 2;
+    median_of_medians_select(arr, keys, lo, lo + groups - 1, mid, false, actions)
+}
+
+fn insertion_sort<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [usize],
+    keys: &mut Keys<T, F>,
+    lo: usize,
+    hi: usize,
+    actions: &mut Vec<Action>,
+) {
+    for i in lo + 1..=hi {
+        let mut j = i;
+        while j > lo {
+            let (left, right) = (keys.bar_value(arr[j - 1]), keys.bar_value(arr[j]));
+            if compare(arr, keys, j - 1, j, LINE_CHOOSE_PIVOT, actions) != Ordering::Greater {
+                annotate(actions, format!("insertion sort: a[{}]={left} ≤ a[{j}]={right}, in place", j - 1));
+                break;
+            }
+            annotate(actions, format!("insertion sort: a[{}]={left} > a[{j}]={right}, swap", j - 1));
+            swap(arr, j - 1, j, LINE_CHOOSE_PIVOT, actions);
+            j -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_leaves_kth_smallest_at_k() {
+        let inputs: [&[u32]; 4] = [
+            &[7, 2, 9, 4, 4, 1, 8, 3, 6, 5, 0, 4],
+            &[3; 17],
+            &[1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1],
+            &[30, 29, 28, 27, 26, 25, 24, 23, 22, 21, 20, 19, 18, 17, 16, 15, 14, 13, 12, 11, 10, 9],
+        ];
+        for values in inputs {
+            let mut sorted = values.to_vec();
+            sorted.sort_unstable();
+            for algorithm in [Algorithm::Quickselect, Algorithm::Introselect, Algorithm::MedianOfMedians] {
+                for k in 0..values.len() {
+                    let actions = select_actions(algorithm, Keys::new(values, values, u32::cmp), k);
+                    let mut arr = values.to_vec();
+                    // The marked pivot, followed through swaps, must be settled before the next one
+                    let mut pivot = None;
+                    for action in &actions {
+                        match action.kind {
+                            ActionKind::Swap => {
+                                arr.swap(action.i, action.j);
+                                if pivot == Some(action.i) {
+                                    pivot = Some(action.j);
+                                } else if pivot == Some(action.j) {
+                                    pivot = Some(action.i);
+                                }
+                            }
+                            ActionKind::Pivot => {
+                                assert_eq!(pivot, None, "{algorithm:?} k={k}: pivot left unsettled");
+                                pivot = Some(action.i);
+                            }
+                            ActionKind::Settled if pivot == Some(action.i) => pivot = None,
+                            _ => {}
+                        }
+                    }
+                    assert_eq!(pivot, None, "{algorithm:?} k={k}: pivot left unsettled");
+                    assert_eq!(arr[k], sorted[k], "{algorithm:?} k={k} on {values:?}");
+                    assert!(arr[..k].iter().all(|&v| v <= arr[k]) && arr[k..].iter().all(|&v| v >= arr[k]));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use anyhow::{bail, Context, Result};
+
+use crate::engine::{Action, ActionKind, Engine};
+
+pub const SAMPLE_RATE: u32 = 44_100;
+const LOW_HZ: f32 = 120.0; // Pitch of the smallest value
+const HIGH_HZ: f32 = 1_200.0; // Pitch of the largest value
+const TONE_SECS: f32 = 0.08;
+const ATTACK_SECS: f32 = 0.005;
+const MAX_VOICES: usize = 16; // Oldest tones are cut when fast playback piles them up
+const BLOCK: usize = 512; // Samples handed to the audio player at a time (~12 ms)
+
+/// Mute toggle and volume from the egui panel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SoundSettings {
+    pub muted: bool,
+    pub volume: f32, // 0.0..=1.0
+}
+
+impl Default for SoundSettings {
+    /// The app starts muted; sound is opt-in from the panel
+    fn default() -> Self {
+        Self { muted: true, volume: 0.5 }
+    }
+}
+
+/// Values are spread exponentially over the range, so equal value steps sound like equal intervals
+fn pitch(value: u32, max_value: u32) -> f32 {
+    let t = value as f32 / max_value.max(1) as f32;
+    LOW_HZ * (HIGH_HZ / LOW_HZ).powf(t.clamp(0.0, 1.0))
+}
+
+/// Pitches for `action` played on `values`: both values of a compare or swap, the value a write stores
+fn tones(action: &Action, values: &[u32], max_value: u32) -> Vec<f32> {
+    let value_at = |i: usize| values.get(i).copied();
+    let touched = match action.kind {
+        ActionKind::Compare | ActionKind::Swap | ActionKind::CompareExchange { .. } => vec![value_at(action.i), value_at(action.j)],
+        ActionKind::Write => vec![Some(action.value)],
+        _ => Vec::new(),
+    };
+    touched.into_iter().flatten().map(|value| pitch(value, max_value)).collect()
+}
+
+/// Play `action` on `values`, as far as the array itself changes
+fn apply(action: &Action, values: &mut Vec<u32>) {
+    match action.kind {
+        ActionKind::Swap | ActionKind::CompareExchange { swap: true } if action.i.max(action.j) < values.len() => values.swap(action.i, action.j),
+        ActionKind::Write | ActionKind::Assign if action.i < values.len() => values[action.i] = action.value,
+        ActionKind::Append => values.push(action.value),
+        ActionKind::RemoveLast => {
+            values.pop();
+        }
+        _ => {}
+    }
+}
+
+struct Voice {
+    frequency: f32,
+    phase: f32, // 0.0..1.0 through the current period
+    age: usize, // Samples played so far
+}
+
+/// Mixes short triangle-wave tones, one per value touched by the actions the engine plays
+pub struct Synth {
+    voices: Vec<Voice>,
+    played: usize,    // Engine::played at the last `follow`
+    values: Vec<u32>, // Bar values at the last `follow`
+    pub settings: SoundSettings,
+}
+
+impl Synth {
+    pub fn new(settings: SoundSettings) -> Self {
+        Self { voices: Vec::new(), played: 0, values: Vec::new(), settings }
+    }
+
+    /// Start the tones of every action played since the last call, each on the values it saw;
+    /// a reset only rewinds
+    pub fn follow(&mut self, engine: &Engine) {
+        let played = engine.played();
+        let (bars, max_value) = engine.bars();
+        if played > self.played {
+            for action in &engine.history()[self.played..played] {
+                for frequency in tones(action, &self.values, max_value) {
+                    if self.voices.len() == MAX_VOICES {
+                        self.voices.remove(0);
+                    }
+                    self.voices.push(Voice { frequency, phase: 0.0, age: 0 });
+                }
+                apply(action, &mut self.values);
+            }
+        }
+        self.played = played;
+        self.values = bars.iter().map(|bar| bar.value).collect();
+    }
+
+    /// Fill `out` with the next samples in -1.0..=1.0; muted playback still advances the tones
+    pub fn render(&mut self, out: &mut [f32]) {
+        let length = (TONE_SECS * SAMPLE_RATE as f32) as usize;
+        let attack = ATTACK_SECS * SAMPLE_RATE as f32;
+        let gain = if self.settings.muted { 0.0 } else { self.settings.volume.clamp(0.0, 1.0) };
+        for sample in out.iter_mut() {
+            let mut mix = 0.0;
+            for voice in &mut self.voices {
+                // Short linear attack against clicks, then a linear fade to the end of the tone
+                let envelope = (voice.age as f32 / attack).min(1.0) * (1.0 - voice.age as f32 / length as f32);
+                let triangle = 1.0 - 4.0 * (voice.phase - 0.5).abs();
+                mix += triangle * envelope * 0.3;
+                voice.phase = (voice.phase + voice.frequency / SAMPLE_RATE as f32).fract();
+                voice.age += 1;
+            }
+            self.voices.retain(|voice| voice.age < length);
+            // Soft clip, so chords of many voices saturate instead of wrapping
+            *sample = mix.tanh() * gain;
+        }
+    }
+}
+
+/// Live playback: a background thread streams the synth into an external audio player
+/// (PulseAudio's `pacat` or ALSA's `aplay`) until the output is dropped
+pub struct AudioOutput {
+    synth: Arc<Mutex<Synth>>,
+    player: Child,
+}
+
+impl AudioOutput {
+    pub fn start(settings: SoundSettings) -> Result<Self> {
+        let rate = SAMPLE_RATE.to_string();
+        let players: [(&str, &[&str]); 2] = [
+            ("pacat", &["--raw", "--format=s16le", "--channels=1", "--latency-msec=60", "--rate", &rate]),
+            ("aplay", &["-q", "-t", "raw", "-f", "S16_LE", "-c", "1", "--buffer-time=60000", "-r", &rate]),
+        ];
+        let Some(mut player) = players.iter().find_map(|(program, args)| {
+            Command::new(program).args(*args).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn().ok()
+        }) else {
+            bail!("no audio player found (tried pacat and aplay); --wav still renders sound to a file");
+        };
+        let mut stdin = player.stdin.take().context("audio player has no stdin")?;
+
+        let synth = Arc::new(Mutex::new(Synth::new(settings)));
+        let shared = Arc::clone(&synth);
+        // Blocking writes into the player's small buffer pace the thread in real time
+        thread::spawn(move || {
+            let mut block = [0.0; BLOCK];
+            loop {
+                match shared.lock() {
+                    Ok(mut synth) => synth.render(&mut block),
+                    Err(_) => return,
+                }
+                if stdin.write_all(&pcm16(&block)).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Self { synth, player })
+    }
+
+    pub fn follow(&self, engine: &Engine) {
+        if let Ok(mut synth) = self.synth.lock() {
+            synth.follow(engine);
+        }
+    }
+
+    pub fn set_settings(&self, settings: SoundSettings) {
+        if let Ok(mut synth) = self.synth.lock() {
+            synth.settings = settings;
+        }
+    }
+}
+
+impl Drop for AudioOutput {
+    fn drop(&mut self) {
+        // The streaming thread ends on its next failed write
+        let _ = self.player.kill();
+        let _ = self.player.wait();
+    }
+}
+
+/// Signed 16-bit little-endian samples, as both WAV and the audio players expect
+fn pcm16(samples: &[f32]) -> Vec<u8> {
+    samples.iter().flat_map(|s| ((s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes()).collect()
+}
+
+/// Write mono 16-bit PCM at SAMPLE_RATE as a RIFF WAVE file
+pub fn write_wav(path: &Path, samples: &[f32]) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("cannot create {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+    let data = pcm16(samples);
+    out.write_all(b"RIFF")?;
+    out.write_all(&(36 + data.len() as u32).to_le_bytes())?;
+    out.write_all(b"WAVEfmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?; // PCM
+    out.write_all(&1u16.to_le_bytes())?; // Mono
+    out.write_all(&SAMPLE_RATE.to_le_bytes())?;
+    out.write_all(&(SAMPLE_RATE * 2).to_le_bytes())?; // Bytes per second
+    out.write_all(&2u16.to_le_bytes())?; // Bytes per sample frame
+    out.write_all(&16u16.to_le_bytes())?; // Bits per sample
+    out.write_all(b"data")?;
+    out.write_all(&(data.len() as u32).to_le_bytes())?;
+    out.write_all(&data)?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Algorithm;
+
+    #[test]
+    fn follow_voices_every_action_of_a_jump() {
+        let mut engine = Engine::new(12);
+        engine.set_algorithm(Algorithm::BitonicSort);
+        let mut stepped = Synth::new(SoundSettings::default());
+        let mut jumped = Synth::new(SoundSettings::default());
+        stepped.follow(&engine);
+        jumped.follow(&engine);
+        // Compare-exchanges, two tones each, but few enough that no voice is cut
+        for played in 1..=MAX_VOICES / 2 {
+            engine.seek(played);
+            stepped.follow(&engine);
+        }
+        jumped.follow(&engine);
+        let frequencies = |synth: &Synth| synth.voices.iter().map(|voice| voice.frequency).collect::<Vec<_>>();
+        assert!(!jumped.voices.is_empty());
+        assert_eq!(frequencies(&jumped), frequencies(&stepped));
+    }
+
+    #[test]
+    fn wav_header_describes_the_samples() {
+        let path = std::env::temp_dir().join(format!("algo-native-{}.wav", std::process::id()));
+        let samples = [0.0, 0.5, -0.5, 1.0, -2.0];
+        write_wav(&path, &samples).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let u16_at = |at: usize| u16::from_le_bytes(bytes[at..at + 2].try_into().unwrap());
+        let data_len = 2 * samples.len() as u32;
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(4), 36 + data_len);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!((u16_at(20), u16_at(22)), (1, 1)); // PCM, mono
+        assert_eq!(u32_at(24), SAMPLE_RATE);
+        assert_eq!(u32_at(28), 2 * SAMPLE_RATE);
+        assert_eq!((u16_at(32), u16_at(34)), (2, 16));
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32_at(40), data_len);
+        assert_eq!(bytes.len(), 44 + data_len as usize);
+        // Out-of-range samples are clipped to full scale
+        assert_eq!(i16::from_le_bytes([bytes[50], bytes[51]]), i16::MAX);
+        assert_eq!(i16::from_le_bytes([bytes[52], bytes[53]]), -i16::MAX);
+    }
+}
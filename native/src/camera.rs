@@ -0,0 +1,185 @@
+use std::f32::consts::{FRAC_PI_4, PI};
+
+use glam::{Mat4, Vec2, Vec3};
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+
+const FOV_Y_DEGREES: f32 = 50.0;
+const NEAR: f32 = 0.1;
+const FAR: f32 = 10.0;
+const MIN_DISTANCE: f32 = 0.6;
+const MAX_DISTANCE: f32 = 6.0;
+const MAX_PITCH: f32 = 1.5; // Just short of straight down, where the Y-up basis degenerates
+const ORBIT_SPEED: f32 = 0.008; // Radians per pixel dragged
+const PAN_SPEED: f32 = 0.0015; // World units per pixel, per unit of distance
+const EASE_RATE: f32 = 8.0; // Higher = faster transitions
+const CLICK_SLOP: f32 = 4.0; // Pixels a left press may move and still count as a click
+
+/// Camera presets, bound to keys 1-4
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraPreset {
+    Front,
+    TopDown,
+    Isometric,
+    FollowActive, // Tracks the bars the last action touched
+}
+
+/// Orbit camera looking at `target` from `distance` away; yaw 0 looks down -Z, positive pitch from above
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pose {
+    target: Vec3,
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+}
+
+impl Pose {
+    fn eye(&self) -> Vec3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        self.target + self.distance * Vec3::new(cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw)
+    }
+
+    fn lerp(&self, goal: &Pose, t: f32) -> Pose {
+        Pose {
+            target: self.target.lerp(goal.target, t),
+            yaw: self.yaw + (goal.yaw - self.yaw) * t,
+            pitch: self.pitch + (goal.pitch - self.pitch) * t,
+            distance: self.distance + (goal.distance - self.distance) * t,
+        }
+    }
+}
+
+impl CameraPreset {
+    fn pose(self) -> Pose {
+        match self {
+            // The original fixed view: eye (0, 1.2, 2.6) looking at (0, 0.6, 0)
+            CameraPreset::Front => Pose { target: Vec3::new(0.0, 0.6, 0.0), yaw: 0.0, pitch: 0.6f32.atan2(2.6), distance: 0.6f32.hypot(2.6) },
+            CameraPreset::TopDown => Pose { target: Vec3::new(0.0, 0.0, 0.0), yaw: 0.0, pitch: MAX_PITCH, distance: 3.4 },
+            // True isometric elevation, atan(1/sqrt(2))
+            CameraPreset::Isometric => Pose { target: Vec3::new(0.0, 0.3, 0.0), yaw: FRAC_PI_4, pitch: 0.6155, distance: 3.6 },
+            CameraPreset::FollowActive => Pose { target: Vec3::new(0.0, 0.4, 0.0), yaw: 0.3, pitch: 0.35, distance: 1.6 },
+        }
+    }
+}
+
+/// What the current mouse drag controls; a drag that starts over an egui panel stays with egui
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Drag {
+    None,
+    Orbit, // Left button
+    Pan,   // Right or middle button
+    Ui,
+}
+
+/// Mouse-driven orbit/pan/zoom camera that eases towards its goal pose every frame
+pub struct Camera {
+    pose: Pose, // Pose drawn this frame
+    goal: Pose, // Pose being eased towards
+    follow: bool,
+    drag: Drag,
+    cursor: Option<Vec2>,
+    press: Option<Vec2>, // Where the current left-button orbit drag began
+    click: Option<Vec2>, // Scene click not yet taken by the renderer
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        let pose = CameraPreset::Front.pose();
+        Self { pose, goal: pose, follow: false, drag: Drag::None, cursor: None, press: None, click: None }
+    }
+
+    pub fn set_preset(&mut self, preset: CameraPreset) {
+        let mut goal = preset.pose();
+        // Turn the short way round, however far the user has orbited
+        goal.yaw = self.pose.yaw + (goal.yaw - self.pose.yaw + PI).rem_euclid(2.0 * PI) - PI;
+        self.goal = goal;
+        self.follow = preset == CameraPreset::FollowActive;
+    }
+
+    /// Mouse input for the 3D view. `over_ui` is true while egui wants the pointer:
+    /// presses and wheel turns over a panel are left to egui, but a drag begun on the scene keeps the camera.
+    pub fn handle_event(&mut self, event: &WindowEvent, over_ui: bool) {
+        match event {
+            WindowEvent::MouseInput { state: ElementState::Pressed, button, .. } if self.drag == Drag::None => {
+                self.drag = match button {
+                    _ if over_ui => Drag::Ui,
+                    MouseButton::Left => Drag::Orbit,
+                    MouseButton::Right | MouseButton::Middle => Drag::Pan,
+                    _ => Drag::None,
+                };
+                self.press = if self.drag == Drag::Orbit { self.cursor } else { None };
+            }
+            WindowEvent::MouseInput { state: ElementState::Released, .. } => {
+                // A left press released where it began is a click on the scene, not an orbit
+                if let (Some(press), Some(cursor)) = (self.press.take(), self.cursor) {
+                    if press.distance(cursor) <= CLICK_SLOP {
+                        self.click = Some(cursor);
+                    }
+                }
+                self.drag = Drag::None;
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = Vec2::new(position.x as f32, position.y as f32);
+                let delta = self.cursor.map_or(Vec2::ZERO, |last| position - last);
+                self.cursor = Some(position);
+                match self.drag {
+                    Drag::Orbit => {
+                        self.goal.yaw -= delta.x * ORBIT_SPEED;
+                        self.goal.pitch = (self.goal.pitch + delta.y * ORBIT_SPEED).clamp(-0.1, MAX_PITCH);
+                    }
+                    Drag::Pan => {
+                        let forward = (self.goal.target - self.goal.eye()).normalize();
+                        let right = forward.cross(Vec3::Y).normalize();
+                        let up = right.cross(forward);
+                        let scale = PAN_SPEED * self.goal.distance;
+                        self.goal.target += (up * delta.y - right * delta.x) * scale;
+                        // Panning away from the active bars ends following them
+                        self.follow = false;
+                    }
+                    Drag::None | Drag::Ui => {}
+                }
+            }
+            WindowEvent::CursorLeft { .. } => self.cursor = None,
+            WindowEvent::MouseWheel { delta, .. } if !over_ui => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(p) => p.y as f32 / 40.0,
+                };
+                self.goal.distance = (self.goal.distance * 0.9f32.powf(lines)).clamp(MIN_DISTANCE, MAX_DISTANCE);
+            }
+            _ => {}
+        }
+    }
+
+    /// Ease towards the goal; while following, `active` (the touched bars' centre) becomes the target
+    pub fn update(&mut self, dt: f32, active: Option<Vec3>) {
+        if let (true, Some(active)) = (self.follow, active) {
+            self.goal.target = active;
+        }
+        self.pose = self.pose.lerp(&self.goal, 1.0 - (-EASE_RATE * dt).exp());
+    }
+
+    /// Pointer position over the scene while no drag is in progress
+    pub fn hover(&self) -> Option<Vec2> {
+        self.cursor.filter(|_| self.drag == Drag::None)
+    }
+
+    pub fn take_click(&mut self) -> Option<Vec2> {
+        self.click.take()
+    }
+
+    /// World-space ray through pixel `cursor` of a `size` viewport, as (origin, unit direction)
+    pub fn ray(&self, cursor: Vec2, size: Vec2) -> (Vec3, Vec3) {
+        let ndc = Vec2::new(2.0 * cursor.x / size.x - 1.0, 1.0 - 2.0 * cursor.y / size.y);
+        let inverse = self.view_proj(size.x / size.y).inverse();
+        let near = inverse.project_point3(ndc.extend(0.0));
+        let far = inverse.project_point3(ndc.extend(1.0));
+        (near, (far - near).normalize())
+    }
+
+    pub fn view_proj(&self, aspect: f32) -> Mat4 {
+        let view = Mat4::look_at_rh(self.pose.eye(), self.pose.target, Vec3::Y);
+        let proj = Mat4::perspective_rh(FOV_Y_DEGREES.to_radians(), aspect, NEAR, FAR);
+        proj * view
+    }
+}
@@ -0,0 +1,123 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+/// Animated clip container, chosen from the output file extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipFormat {
+    Gif,
+    Apng,
+}
+
+impl ClipFormat {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+        match extension.as_str() {
+            "gif" => Ok(ClipFormat::Gif),
+            "png" | "apng" => Ok(ClipFormat::Apng),
+            _ => bail!("cannot tell the clip format of {}; use .gif, .png or .apng", path.display()),
+        }
+    }
+}
+
+/// Streams frames of one run into an animated GIF or APNG that loops forever
+pub enum ClipWriter {
+    Gif(GifEncoder<BufWriter<File>>, Delay),
+    Apng(png::Writer<BufWriter<File>>),
+}
+
+impl ClipWriter {
+    /// APNG declares its frame count up front, so `frame_count` must match the frames added; GIF ignores it
+    pub fn create(path: &Path, format: ClipFormat, width: u32, height: u32, fps: u32, frame_count: u32) -> Result<Self> {
+        let file = File::create(path).with_context(|| format!("cannot create {}", path.display()))?;
+        let out = BufWriter::new(file);
+        match format {
+            ClipFormat::Gif => {
+                // Speed 10 of 30 quantizes a 720p frame quickly without visible banding in the flat UI colors
+                let mut encoder = GifEncoder::new_with_speed(out, 10);
+                encoder.set_repeat(Repeat::Infinite)?;
+                // GIF delays are in centiseconds; browsers clamp very short delays, so keep at least 2
+                let delay = Delay::from_numer_denom_ms((100 / fps.max(1)).max(2) * 10, 1);
+                Ok(ClipWriter::Gif(encoder, delay))
+            }
+            ClipFormat::Apng => {
+                let mut encoder = png::Encoder::new(out, width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+                encoder.set_animated(frame_count, 0)?;
+                encoder.set_frame_delay(1, fps.min(u16::MAX as u32) as u16)?;
+                Ok(ClipWriter::Apng(encoder.write_header()?))
+            }
+        }
+    }
+
+    pub fn add_frame(&mut self, frame: &RgbaImage) -> Result<()> {
+        match self {
+            ClipWriter::Gif(encoder, delay) => Ok(encoder.encode_frame(Frame::from_parts(frame.clone(), 0, 0, *delay))?),
+            ClipWriter::Apng(writer) => Ok(writer.write_image_data(frame.as_raw())?),
+        }
+    }
+
+    pub fn finish(self) -> Result<()> {
+        match self {
+            // The encoder writes the GIF trailer when dropped
+            ClipWriter::Gif(..) => Ok(()),
+            ClipWriter::Apng(writer) => Ok(writer.finish()?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Cursor};
+
+    use image::codecs::gif::GifDecoder;
+    use image::{AnimationDecoder, Rgba};
+
+    use super::*;
+
+    /// Write `count` solid frames, each a different shade, and return the file's bytes
+    fn clip(format: ClipFormat, count: u32) -> Vec<u8> {
+        let extension = if format == ClipFormat::Gif { "gif" } else { "png" };
+        let path = std::env::temp_dir().join(format!("algo-native-clip-{}-{count}.{extension}", std::process::id()));
+        let mut writer = ClipWriter::create(&path, format, 6, 4, 30, count).unwrap();
+        for shade in 0..count {
+            writer.add_frame(&RgbaImage::from_pixel(6, 4, Rgba([(shade * 40) as u8, 0, 0, 255]))).unwrap();
+        }
+        writer.finish().unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn format_follows_the_extension() {
+        assert_eq!(ClipFormat::from_path(Path::new("run.GIF")).unwrap(), ClipFormat::Gif);
+        assert_eq!(ClipFormat::from_path(Path::new("run.apng")).unwrap(), ClipFormat::Apng);
+        assert_eq!(ClipFormat::from_path(Path::new("run.png")).unwrap(), ClipFormat::Apng);
+        assert!(ClipFormat::from_path(Path::new("run.mp4")).is_err());
+    }
+
+    #[test]
+    fn clips_hold_every_frame() {
+        let gif = GifDecoder::new(BufReader::new(Cursor::new(clip(ClipFormat::Gif, 5)))).unwrap();
+        let frames = gif.into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[4].buffer().get_pixel(0, 0)[0], 160);
+
+        let mut apng = png::Decoder::new(Cursor::new(clip(ClipFormat::Apng, 5))).read_info().unwrap();
+        assert_eq!(apng.info().animation_control().map(|control| control.num_frames), Some(5));
+        let mut buf = vec![0; apng.output_buffer_size().unwrap()];
+        let mut decoded = 0;
+        while apng.next_frame(&mut buf).is_ok() {
+            decoded += 1;
+        }
+        assert_eq!(decoded, 5);
+        assert_eq!(buf[0], 160);
+    }
+}
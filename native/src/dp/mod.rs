@@ -0,0 +1,159 @@
+pub mod tables;
+
+use rand::Rng;
+
+use crate::algorithms::Algorithm;
+
+/// Inputs for one dynamic-programming problem
+#[derive(Clone, Debug)]
+pub enum DpProblem {
+    Lcs { a: Vec<u8>, b: Vec<u8> },
+    EditDistance { a: Vec<u8>, b: Vec<u8> },
+    Knapsack { weights: Vec<u32>, values: Vec<u32>, capacity: usize },
+    Lis { values: Vec<u32> },
+}
+
+impl Default for DpProblem {
+    fn default() -> Self {
+        DpProblem::Lis { values: Vec::new() }
+    }
+}
+
+impl DpProblem {
+    /// Random small instance that keeps the table readable on screen
+    pub fn random<R: Rng>(algorithm: Algorithm, rng: &mut R) -> Self {
+        let word = |rng: &mut R, len: usize| -> Vec<u8> { (0..len).map(|_| b"ACGT"[rng.gen_range(0..4)]).collect() };
+        match algorithm {
+            Algorithm::Lcs => DpProblem::Lcs { a: word(rng, 8), b: word(rng, 10) },
+            Algorithm::EditDistance => DpProblem::EditDistance { a: word(rng, 7), b: word(rng, 9) },
+            Algorithm::Knapsack => {
+                let items = 6;
+                DpProblem::Knapsack {
+                    weights: (0..items).map(|_| rng.gen_range(1..=5)).collect(),
+                    values: (0..items).map(|_| rng.gen_range(1..=9)).collect(),
+                    capacity: 12,
+                }
+            }
+            _ => DpProblem::Lis { values: (0..14).map(|_| rng.gen_range(1..=30)).collect() },
+        }
+    }
+
+    /// One-line description of the inputs for the stats panel
+    pub fn describe(&self) -> String {
+        match self {
+            DpProblem::Lcs { a, b } | DpProblem::EditDistance { a, b } => {
+                format!("a = {}, b = {}", String::from_utf8_lossy(a), String::from_utf8_lossy(b))
+            }
+            DpProblem::Knapsack { weights, values, capacity } => {
+                let items: Vec<String> = weights.iter().zip(values).map(|(w, v)| format!("{}/{}", w, v)).collect();
+                format!("capacity {}, items (weight/value): {}", capacity, items.join(" "))
+            }
+            DpProblem::Lis { values } => {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                format!("sequence: {}", values.join(" "))
+            }
+        }
+    }
+}
+
+/// Table shape and current cell contents (None until first written)
+#[derive(Clone, Debug, Default)]
+pub struct DpTable {
+    pub rows: usize,
+    pub cols: usize,
+    pub cells: Vec<Option<u32>>,
+}
+
+impl DpTable {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            cells: vec![None; rows * cols],
+        }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<u32> {
+        self.cells[row * self.cols + col]
+    }
+}
+
+/// DP problem plus the playback of its table-filling events
+#[derive(Clone, Debug, Default)]
+pub struct DpRun {
+    pub problem: DpProblem,
+    pub table: DpTable,
+    events: Vec<tables::DpEvent>,
+    cursor: usize,
+    pub reads: Vec<(usize, usize)>, // Cells read since the last write
+    pub written: Option<(usize, usize)>,
+    pub trace: Vec<bool>, // Cells on the reconstructed optimal solution
+    pub answer: Option<u32>,
+}
+
+impl DpRun {
+    pub fn new(problem: DpProblem) -> Self {
+        let (rows, cols, events) = tables::dp_events(&problem);
+        Self {
+            problem,
+            table: DpTable::new(rows, cols),
+            events,
+            cursor: 0,
+            reads: Vec::new(),
+            written: None,
+            trace: vec![false; rows * cols],
+            answer: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.events.len()
+    }
+
+    pub fn table_bytes(&self) -> usize {
+        self.table.cells.len() * 4
+    }
+
+    pub fn events(&self) -> &Vec<tables::DpEvent> {
+        &self.events
+    }
+
+    /// Number of events played so far
+    pub fn played(&self) -> usize {
+        self.cursor
+    }
+
+    /// Apply the next event, returning it so the engine can count its cost
+    pub fn advance(&mut self) -> Option<tables::DpEvent> {
+        use tables::DpEventKind;
+
+        let event = self.events.get(self.cursor)?.clone();
+        self.cursor += 1;
+        let idx = event.row * self.table.cols + event.col;
+
+        match event.kind {
+            DpEventKind::Allocate | DpEventKind::Compare => {}
+            DpEventKind::Read => {
+                if self.written.take().is_some() {
+                    self.reads.clear();
+                }
+                self.reads.push((event.row, event.col));
+            }
+            DpEventKind::Write => {
+                self.table.cells[idx] = Some(event.value);
+                self.written = Some((event.row, event.col));
+            }
+            DpEventKind::Trace => {
+                self.reads.clear();
+                self.written = None;
+                self.trace[idx] = true;
+            }
+            DpEventKind::Done => {
+                self.reads.clear();
+                self.written = None;
+                self.answer = Some(event.value);
+            }
+        }
+        Some(event)
+    }
+}
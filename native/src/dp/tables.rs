@@ -0,0 +1,526 @@
+use super::DpProblem;
+use crate::algorithms::{annotate, Algorithm};
+use crate::engine::Step;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DpEventKind {
+    Allocate, // Table of rows × cols cells is allocated
+    Compare,  // A test is evaluated while filling cell (row, col); value is 1 if it held
+    Read,     // Cell value is read to compute another cell
+    Write,    // Cell receives its final value
+    Trace,    // Cell lies on the reconstructed optimal solution
+    Done,     // value holds the answer
+}
+
+#[derive(Clone, Debug)]
+pub struct DpEvent {
+    pub kind: DpEventKind,
+    pub row: usize,
+    pub col: usize,
+    pub value: u32,
+    pub note: Option<String>,
+    pub line: Option<usize>,
+}
+
+impl Step for DpEvent {
+    fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    fn note_mut(&mut self) -> &mut Option<String> {
+        &mut self.note
+    }
+
+    fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    fn columns(&self) -> String {
+        format!("{:?}\t{}\t{}\t{}", self.kind, self.row, self.col, self.value)
+    }
+}
+
+// All four listings share one shape: base cases, a test per cell, its two outcomes, the fill, the traceback
+const LINE_BASE: usize = 0;
+const LINE_TEST: usize = 2;
+const LINE_THEN: usize = 3;
+const LINE_ELSE: usize = 4;
+const LINE_FILL: usize = 5;
+const LINE_TRACE: usize = 6;
+const LINE_ANSWER: usize = 7;
+
+/// Listing the events' lines index into
+pub fn pseudocode(algorithm: Algorithm) -> &'static [&'static str] {
+    match algorithm {
+        Algorithm::Lcs => &[
+            "dp[i][0] = dp[0][j] = 0",
+            "for i in 1..=n, j in 1..=m:",
+            "    if a[i-1] == b[j-1]:",
+            "        best = dp[i-1][j-1] + 1",
+            "    else: best = max(dp[i-1][j], dp[i][j-1])",
+            "    dp[i][j] = best",
+            "walk back from dp[n][m] along matches and the larger neighbour",
+            "return dp[n][m]",
+        ],
+        Algorithm::EditDistance => &[
+            "dp[i][0] = i; dp[0][j] = j",
+            "for i in 1..=n, j in 1..=m:",
+            "    if a[i-1] == b[j-1]:",
+            "        best = dp[i-1][j-1]",
+            "    else: best = 1 + min(dp[i-1][j], dp[i][j-1], dp[i-1][j-1])",
+            "    dp[i][j] = best",
+            "walk back from dp[n][m] through the neighbour each cell came from",
+            "return dp[n][m]",
+        ],
+        Algorithm::Knapsack => &[
+            "dp[0][w] = 0",
+            "for i in 1..=n, w in 0..=W:",
+            "    if weight[i] <= w:",
+            "        best = max(dp[i-1][w], dp[i-1][w - weight[i]] + value[i])",
+            "    else: best = dp[i-1][w]",
+            "    dp[i][w] = best",
+            "walk back from dp[n][W]: item i was taken where dp[i][w] != dp[i-1][w]",
+            "return dp[n][W]",
+        ],
+        Algorithm::Lis => &[
+            "for i in 0..n: best = 1",
+            "    for j in 0..i:",
+            "        if a[j] < a[i]:",
+            "            best = max(best, dp[j] + 1)",
+            "        # else a[i] cannot extend the run ending at a[j]",
+            "    dp[i] = best",
+            "walk back from the largest dp[i] through predecessors",
+            "return max(dp)",
+        ],
+        _ => &[],
+    }
+}
+
+/// Table being filled, recording every access
+struct Recorder {
+    cols: usize,
+    cells: Vec<u32>,
+    events: Vec<DpEvent>,
+    line: Option<usize>, // Pseudocode line the next events execute
+}
+
+impl Recorder {
+    fn new(rows: usize, cols: usize) -> Self {
+        let mut recorder = Self {
+            cols,
+            cells: vec![0; rows * cols],
+            events: Vec::new(),
+            line: None,
+        };
+        recorder.push(DpEventKind::Allocate, 0, 0, (rows * cols) as u32);
+        recorder
+    }
+
+    fn push(&mut self, kind: DpEventKind, row: usize, col: usize, value: u32) {
+        self.events.push(DpEvent { kind, row, col, value, note: None, line: self.line });
+    }
+
+    /// Attribute the following events to pseudocode line `line`
+    fn at(&mut self, line: usize) {
+        self.line = Some(line);
+    }
+
+    /// Caption the event just recorded
+    fn note(&mut self, note: String) {
+        annotate(&mut self.events, note);
+    }
+
+    fn read(&mut self, row: usize, col: usize) -> u32 {
+        let value = self.cells[row * self.cols + col];
+        self.push(DpEventKind::Read, row, col, value);
+        value
+    }
+
+    /// Record a comparison of input elements made while filling (row, col), passing its outcome through
+    fn compare(&mut self, row: usize, col: usize, holds: bool) -> bool {
+        self.push(DpEventKind::Compare, row, col, u32::from(holds));
+        holds
+    }
+
+    fn write(&mut self, row: usize, col: usize, value: u32) {
+        self.cells[row * self.cols + col] = value;
+        self.push(DpEventKind::Write, row, col, value);
+    }
+
+    /// Peek without recording, for traceback decisions
+    fn get(&self, row: usize, col: usize) -> u32 {
+        self.cells[row * self.cols + col]
+    }
+
+    fn trace(&mut self, row: usize, col: usize) {
+        let value = self.get(row, col);
+        self.push(DpEventKind::Trace, row, col, value);
+    }
+}
+
+/// Generate the table shape (rows, cols) and fill events for a problem
+pub fn dp_events(problem: &DpProblem) -> (usize, usize, Vec<DpEvent>) {
+    let (rows, cols, mut recorder, answer) = match problem {
+        DpProblem::Lcs { a, b } => {
+            let (rows, cols) = (a.len() + 1, b.len() + 1);
+            let mut recorder = Recorder::new(rows, cols);
+            let answer = lcs(a, b, &mut recorder);
+            (rows, cols, recorder, answer)
+        }
+        DpProblem::EditDistance { a, b } => {
+            let (rows, cols) = (a.len() + 1, b.len() + 1);
+            let mut recorder = Recorder::new(rows, cols);
+            let answer = edit_distance(a, b, &mut recorder);
+            (rows, cols, recorder, answer)
+        }
+        DpProblem::Knapsack { weights, values, capacity } => {
+            let (rows, cols) = (weights.len() + 1, capacity + 1);
+            let mut recorder = Recorder::new(rows, cols);
+            let answer = knapsack(weights, values, *capacity, &mut recorder);
+            (rows, cols, recorder, answer)
+        }
+        DpProblem::Lis { values } => {
+            let (rows, cols) = (1, values.len().max(1));
+            let mut recorder = Recorder::new(rows, cols);
+            let answer = lis(values, &mut recorder);
+            (rows, cols, recorder, answer)
+        }
+    };
+    recorder.at(LINE_ANSWER);
+    recorder.push(DpEventKind::Done, rows - 1, cols - 1, answer);
+    let answer = match problem {
+        DpProblem::Lcs { .. } => format!("longest common subsequence has length {answer}"),
+        DpProblem::EditDistance { .. } => format!("edit distance is {answer}"),
+        DpProblem::Knapsack { .. } => format!("best total value is {answer}"),
+        DpProblem::Lis { .. } => format!("longest increasing subsequence has length {answer}"),
+    };
+    recorder.note(answer);
+    (rows, cols, recorder.events)
+}
+
+/// dp[i][j] = LCS length of a[..i] and b[..j]
+fn lcs(a: &[u8], b: &[u8], rec: &mut Recorder) -> u32 {
+    let (n, m) = (a.len(), b.len());
+    rec.at(LINE_BASE);
+    for i in 0..=n {
+        rec.write(i, 0, 0);
+        rec.note(format!("dp[{i}][0] = 0: nothing in common with an empty b"));
+    }
+    for j in 1..=m {
+        rec.write(0, j, 0);
+        rec.note(format!("dp[0][{j}] = 0: nothing in common with an empty a"));
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let (x, y) = (a[i - 1] as char, b[j - 1] as char);
+            rec.at(LINE_TEST);
+            let matched = rec.compare(i, j, x == y);
+            let verdict = if matched { "match" } else { "differ" };
+            rec.note(format!("compare a[{}]={x} with b[{}]={y}: {verdict}", i - 1, j - 1));
+            let value = if matched {
+                rec.at(LINE_THEN);
+                rec.read(i - 1, j - 1) + 1
+            } else {
+                rec.at(LINE_ELSE);
+                rec.read(i - 1, j).max(rec.read(i, j - 1))
+            };
+            rec.at(LINE_FILL);
+            rec.write(i, j, value);
+            if matched {
+                rec.note(format!("dp[{i}][{j}] = dp[{}][{}] + 1 = {value}", i - 1, j - 1));
+            } else {
+                rec.note(format!("dp[{i}][{j}] = max(dp[{}][{j}], dp[{i}][{}]) = {value}", i - 1, j - 1));
+            }
+        }
+    }
+
+    // Walk back along matches and the larger neighbour
+    rec.at(LINE_TRACE);
+    let (mut i, mut j) = (n, m);
+    while i > 0 && j > 0 {
+        rec.trace(i, j);
+        if a[i - 1] == b[j - 1] {
+            rec.note(format!("trace back: a[{}] = b[{}] = {} is in the subsequence", i - 1, j - 1, a[i - 1] as char));
+            i -= 1;
+            j -= 1;
+        } else if rec.get(i - 1, j) >= rec.get(i, j - 1) {
+            rec.note(format!("trace back: dp[{i}][{j}] came from above"));
+            i -= 1;
+        } else {
+            rec.note(format!("trace back: dp[{i}][{j}] came from the left"));
+            j -= 1;
+        }
+    }
+    rec.get(n, m)
+}
+
+/// dp[i][j] = edits turning a[..i] into b[..j]
+fn edit_distance(a: &[u8], b: &[u8], rec: &mut Recorder) -> u32 {
+    let (n, m) = (a.len(), b.len());
+    rec.at(LINE_BASE);
+    for i in 0..=n {
+        rec.write(i, 0, i as u32);
+        rec.note(format!("dp[{i}][0] = {i}: delete all {i} characters"));
+    }
+    for j in 1..=m {
+        rec.write(0, j, j as u32);
+        rec.note(format!("dp[0][{j}] = {j}: insert all {j} characters"));
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let (x, y) = (a[i - 1] as char, b[j - 1] as char);
+            rec.at(LINE_TEST);
+            let matched = rec.compare(i, j, x == y);
+            let verdict = if matched { "match, no edit needed" } else { "differ" };
+            rec.note(format!("compare a[{}]={x} with b[{}]={y}: {verdict}", i - 1, j - 1));
+            if matched {
+                rec.at(LINE_THEN);
+                let value = rec.read(i - 1, j - 1);
+                rec.at(LINE_FILL);
+                rec.write(i, j, value);
+                rec.note(format!("dp[{i}][{j}] = dp[{}][{}] = {value}", i - 1, j - 1));
+            } else {
+                rec.at(LINE_ELSE);
+                let delete = rec.read(i - 1, j);
+                let insert = rec.read(i, j - 1);
+                let replace = rec.read(i - 1, j - 1);
+                let value = 1 + delete.min(insert).min(replace);
+                rec.at(LINE_FILL);
+                rec.write(i, j, value);
+                rec.note(format!("dp[{i}][{j}] = 1 + min(delete {delete}, insert {insert}, replace {replace}) = {value}"));
+            }
+        }
+    }
+
+    // Walk back through whichever neighbour produced each cell
+    rec.at(LINE_TRACE);
+    let (mut i, mut j) = (n, m);
+    loop {
+        rec.trace(i, j);
+        if i == 0 && j == 0 {
+            rec.note("trace back: reached the empty prefixes".to_string());
+            break;
+        }
+        let here = rec.get(i, j);
+        let diagonal = (i > 0 && j > 0).then(|| rec.get(i - 1, j - 1) + u32::from(a[i - 1] != b[j - 1]));
+        if diagonal == Some(here) {
+            let step = if a[i - 1] == b[j - 1] { "keep" } else { "replace" };
+            rec.note(format!("trace back: {step} a[{}]={}", i - 1, a[i - 1] as char));
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && here == rec.get(i - 1, j) + 1 {
+            rec.note(format!("trace back: delete a[{}]={}", i - 1, a[i - 1] as char));
+            i -= 1;
+        } else {
+            rec.note(format!("trace back: insert b[{}]={}", j - 1, b[j - 1] as char));
+            j -= 1;
+        }
+    }
+    rec.get(n, m)
+}
+
+/// dp[i][w] = best value using the first i items within weight w
+fn knapsack(weights: &[u32], values: &[u32], capacity: usize, rec: &mut Recorder) -> u32 {
+    let n = weights.len();
+    rec.at(LINE_BASE);
+    for w in 0..=capacity {
+        rec.write(0, w, 0);
+        rec.note(format!("dp[0][{w}] = 0: no items, no value"));
+    }
+    for i in 1..=n {
+        let (weight, item_value) = (weights[i - 1] as usize, values[i - 1]);
+        for w in 0..=capacity {
+            rec.at(LINE_TEST);
+            let fits = rec.compare(i, w, weight <= w);
+            let verdict = if fits { "fits" } else { "is too heavy" };
+            rec.note(format!("item {i} (weight {weight}) within capacity {w}: {verdict}"));
+            if fits {
+                rec.at(LINE_THEN);
+                let skip = rec.read(i - 1, w);
+                let take = rec.read(i - 1, w - weight) + item_value;
+                let value = if rec.compare(i, w, take > skip) { take } else { skip };
+                rec.at(LINE_FILL);
+                rec.write(i, w, value);
+                rec.note(format!("item {i} (weight {weight}, value {item_value}) within {w}: max(skip {skip}, take {take}) = {value}"));
+            } else {
+                rec.at(LINE_ELSE);
+                let skip = rec.read(i - 1, w);
+                rec.at(LINE_FILL);
+                rec.write(i, w, skip);
+                rec.note(format!("item {i} (weight {weight}) does not fit in {w}: dp[{i}][{w}] = {skip}"));
+            }
+        }
+    }
+
+    // An item was taken wherever the value differs from the row above
+    rec.at(LINE_TRACE);
+    let mut w = capacity;
+    for i in (1..=n).rev() {
+        rec.trace(i, w);
+        if rec.get(i, w) != rec.get(i - 1, w) {
+            rec.note(format!("trace back: item {i} was taken, {} capacity left", w - weights[i - 1] as usize));
+            w -= weights[i - 1] as usize;
+        } else {
+            rec.note(format!("trace back: item {i} was skipped"));
+        }
+    }
+    rec.trace(0, w);
+    rec.note("trace back: no items left".to_string());
+    rec.get(n, capacity)
+}
+
+/// dp[i] = length of the longest increasing subsequence ending at i (O(n²))
+fn lis(values: &[u32], rec: &mut Recorder) -> u32 {
+    let n = values.len();
+    let mut prev = vec![None; n];
+    for i in 0..n {
+        let mut best = 1;
+        for j in 0..i {
+            let (before, here) = (values[j], values[i]);
+            rec.at(LINE_TEST);
+            if rec.compare(0, i, before < here) {
+                rec.note(format!("a[{j}]={before} < a[{i}]={here}: a[{i}] can extend the run ending at a[{j}]"));
+                rec.at(LINE_THEN);
+                let candidate = rec.read(0, j) + 1;
+                if candidate > best {
+                    best = candidate;
+                    prev[i] = Some(j);
+                }
+            } else {
+                rec.note(format!("a[{j}]={before} ≥ a[{i}]={here}: cannot extend"));
+            }
+        }
+        rec.at(LINE_FILL);
+        rec.write(0, i, best);
+        rec.note(format!("dp[{i}] = {best}: longest increasing run ending at a[{i}]"));
+    }
+
+    // Follow predecessors back from the longest ending
+    rec.at(LINE_TRACE);
+    let end = (0..n).max_by_key(|&i| (rec.get(0, i), std::cmp::Reverse(i)));
+    let mut node = end;
+    while let Some(i) = node {
+        rec.trace(0, i);
+        rec.note(format!("trace back: a[{i}]={} is in the subsequence", values[i]));
+        node = prev[i];
+    }
+    end.map_or(0, |i| rec.get(0, i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Final table contents as rows, plus the answer the Done event reports
+    fn solve(problem: DpProblem) -> (Vec<Vec<u32>>, u32, Vec<DpEvent>) {
+        let (rows, cols, events) = dp_events(&problem);
+        let mut table = vec![vec![0; cols]; rows];
+        for e in events.iter().filter(|e| e.kind == DpEventKind::Write) {
+            table[e.row][e.col] = e.value;
+        }
+        let done = events.last().expect("a Done event");
+        assert_eq!(done.kind, DpEventKind::Done);
+        (table, done.value, events)
+    }
+
+    /// Trace events in the order they were recorded, with their captions
+    fn trace(events: &[DpEvent]) -> Vec<(usize, usize, &str)> {
+        events
+            .iter()
+            .filter(|e| e.kind == DpEventKind::Trace)
+            .map(|e| (e.row, e.col, e.note.as_deref().unwrap_or("")))
+            .collect()
+    }
+
+    #[test]
+    fn lcs_fills_the_table_and_traces_a_common_subsequence() {
+        let (table, answer, _) = solve(DpProblem::Lcs { a: b"ACB".to_vec(), b: b"ABC".to_vec() });
+        assert_eq!(table, [[0, 0, 0, 0], [0, 1, 1, 1], [0, 1, 1, 2], [0, 1, 2, 2]]);
+        assert_eq!(answer, 2);
+
+        for (a, b, expected) in [(&b"ACB"[..], &b"ABC"[..], 2), (b"AGGTABQ", b"GXTXAYB", 4), (b"AC", b"GT", 0)] {
+            let (_, answer, events) = solve(DpProblem::Lcs { a: a.to_vec(), b: b.to_vec() });
+            assert_eq!(answer, expected);
+            // Matches are traced back to front, so both index sequences fall
+            let matches: Vec<(usize, usize)> = trace(&events)
+                .into_iter()
+                .filter(|(_, _, note)| note.ends_with("is in the subsequence"))
+                .map(|(i, j, _)| (i - 1, j - 1))
+                .collect();
+            assert_eq!(matches.len(), expected as usize);
+            assert!(matches.iter().all(|&(i, j)| a[i] == b[j]));
+            assert!(matches.windows(2).all(|w| w[1].0 < w[0].0 && w[1].1 < w[0].1));
+        }
+    }
+
+    #[test]
+    fn edit_distance_fills_the_table_and_traces_an_alignment() {
+        let (table, answer, _) = solve(DpProblem::EditDistance { a: b"AB".to_vec(), b: b"BB".to_vec() });
+        assert_eq!(table, [[0, 1, 2], [1, 1, 2], [2, 1, 1]]);
+        assert_eq!(answer, 1);
+
+        for (a, b, expected) in [(&b"KITTEN"[..], &b"SITTING"[..], 3), (b"AB", b"BB", 1), (b"", b"AC", 2)] {
+            let (_, answer, events) = solve(DpProblem::EditDistance { a: a.to_vec(), b: b.to_vec() });
+            assert_eq!(answer, expected);
+            // Replay the traced edits front to back; they must consume a and produce b exactly
+            let (mut i, mut j, mut edits) = (0, 0, 0);
+            for &(row, col, note) in trace(&events).iter().rev() {
+                if note.starts_with("trace back: keep") {
+                    assert_eq!((row - 1, col - 1), (i, j));
+                    assert_eq!(a[i], b[j]);
+                    (i, j) = (i + 1, j + 1);
+                } else if note.starts_with("trace back: replace") {
+                    assert_eq!((row - 1, col - 1), (i, j));
+                    (i, j, edits) = (i + 1, j + 1, edits + 1);
+                } else if note.starts_with("trace back: delete") {
+                    assert_eq!(row - 1, i);
+                    (i, edits) = (i + 1, edits + 1);
+                } else if note.starts_with("trace back: insert") {
+                    assert_eq!(col - 1, j);
+                    (j, edits) = (j + 1, edits + 1);
+                }
+            }
+            assert_eq!((i, j, edits), (a.len(), b.len(), expected));
+        }
+    }
+
+    #[test]
+    fn knapsack_fills_the_table_and_traces_the_items_taken() {
+        let problem = DpProblem::Knapsack { weights: vec![1, 3, 4], values: vec![15, 20, 30], capacity: 4 };
+        let (table, answer, events) = solve(problem);
+        assert_eq!(table, [[0, 0, 0, 0, 0], [0, 15, 15, 15, 15], [0, 15, 15, 20, 35], [0, 15, 15, 20, 35]]);
+        assert_eq!(answer, 35);
+
+        let taken: Vec<usize> = trace(&events)
+            .into_iter()
+            .filter(|(_, _, note)| note.contains("was taken"))
+            .map(|(i, _, _)| i)
+            .collect();
+        assert_eq!(taken, [2, 1]);
+
+        // Every cell past the base row tests the fit, and a fitting item also weighs skip against take
+        let compares = events.iter().filter(|e| e.kind == DpEventKind::Compare).count();
+        let cells = 3 * 5;
+        let fitting = [1, 3, 4].iter().map(|&weight| 5 - weight).sum::<usize>();
+        assert_eq!(compares, cells + fitting);
+        let lines = events.iter().filter(|e| e.kind == DpEventKind::Compare).map(|e| e.line);
+        assert!(lines.into_iter().all(|line| line == Some(LINE_TEST) || line == Some(LINE_THEN)));
+    }
+
+    #[test]
+    fn lis_fills_the_table_and_traces_an_increasing_subsequence() {
+        let values = vec![3, 1, 4, 1, 5, 9, 2, 6];
+        let (table, answer, events) = solve(DpProblem::Lis { values: values.clone() });
+        assert_eq!(table, [[1, 1, 2, 1, 3, 4, 2, 4]]);
+        assert_eq!(answer, 4);
+
+        // Traced back to front: indices and values both fall
+        let picked: Vec<usize> = trace(&events).into_iter().map(|(_, i, _)| i).collect();
+        assert_eq!(picked.len(), 4);
+        assert!(picked.windows(2).all(|w| w[1] < w[0] && values[w[1]] < values[w[0]]));
+
+        let (_, answer, events) = solve(DpProblem::Lis { values: Vec::new() });
+        assert_eq!(answer, 0);
+        assert!(trace(&events).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use std::cmp::Ordering;
+
+use rand::Rng;
+
+/// Kind of elements being sorted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElementKind {
+    Integers,
+    Floats,
+    Strings,
+    Tuples,
+}
+
+impl ElementKind {
+    pub const ALL: [ElementKind; 4] = [
+        ElementKind::Integers,
+        ElementKind::Floats,
+        ElementKind::Strings,
+        ElementKind::Tuples,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ElementKind::Integers => "Integers (u32)",
+            ElementKind::Floats => "Floats (f64, NaN last)",
+            ElementKind::Strings => "Strings (lexicographic)",
+            ElementKind::Tuples => "Tuples (key, payload)",
+        }
+    }
+}
+
+/// Typed input values for a run, kept so actions can be regenerated on mode switch
+#[derive(Clone, Debug)]
+pub enum Dataset {
+    Integers(Vec<u32>),
+    Floats(Vec<f64>),
+    Strings(Vec<String>),
+    Tuples(Vec<(u32, char)>), // Sorted by key only, payload shows stability
+}
+
+impl Dataset {
+    pub fn random<R: Rng>(kind: ElementKind, size: usize, rng: &mut R) -> Self {
+        match kind {
+            ElementKind::Integers => Dataset::Integers((0..size).map(|_| rng.gen_range(1..=1000)).collect()),
+            ElementKind::Floats => Dataset::Floats(
+                (0..size)
+                    .map(|_| if rng.gen_bool(0.1) { f64::NAN } else { rng.gen_range(-100.0..100.0) })
+                    .collect(),
+            ),
+            ElementKind::Strings => {
+                const WORDS: [&str; 16] = [
+                    "apple", "banana", "cherry", "date", "elder", "fig", "grape", "kiwi",
+                    "lemon", "mango", "nut", "olive", "pear", "quince", "rasp", "sloe",
+                ];
+                Dataset::Strings((0..size).map(|_| WORDS[rng.gen_range(0..WORDS.len())].to_string()).collect())
+            }
+            ElementKind::Tuples => Dataset::Tuples(
+                // Small key range so duplicates are common
+                (0..size).map(|i| (rng.gen_range(1..=5), (b'a' + (i % 26) as u8) as char)).collect(),
+            ),
+        }
+    }
+
+    pub fn kind(&self) -> ElementKind {
+        match self {
+            Dataset::Integers(_) => ElementKind::Integers,
+            Dataset::Floats(_) => ElementKind::Floats,
+            Dataset::Strings(_) => ElementKind::Strings,
+            Dataset::Tuples(_) => ElementKind::Tuples,
+        }
+    }
+
+    /// Bar heights for the elements: integers keep their value, other kinds use their rank
+    pub fn bar_values(&self) -> Vec<u32> {
+        match self {
+            Dataset::Integers(v) => v.clone(),
+            Dataset::Floats(v) => rank_values(v, nan_last),
+            Dataset::Strings(v) => rank_values(v, String::cmp),
+            Dataset::Tuples(v) => rank_values(v, by_key),
+        }
+    }
+}
+
+/// Float ordering that places NaN after every number (NaNs compare equal to each other)
+pub fn nan_last(a: &f64, b: &f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// Compare records by key only, ignoring the payload
+pub fn by_key<K: Ord, P>(a: &(K, P), b: &(K, P)) -> Ordering {
+    a.0.cmp(&b.0)
+}
+
+/// Dense rank (1-based) of each value under `cmp`; equal keys share a rank
+pub fn rank_values<T, F>(values: &[T], mut cmp: F) -> Vec<u32>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| cmp(&values[a], &values[b]));
+
+    let mut ranks = vec![0u32; values.len()];
+    let mut rank = 0u32;
+    for (k, &idx) in order.iter().enumerate() {
+        if k == 0 || cmp(&values[order[k - 1]], &values[idx]) != Ordering::Equal {
+            rank += 1;
+        }
+        ranks[idx] = rank;
+    }
+    ranks
+}
+
+/// Elements being sorted, addressed by their original index.
+/// Generators sort these ids so they never need to clone `T`.
+pub struct Keys<'a, T, F> {
+    values: &'a [T],
+    bar_values: &'a [u32],
+    cmp: F,
+}
+
+impl<'a, T, F> Keys<'a, T, F>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    pub fn new(values: &'a [T], bar_values: &'a [u32], cmp: F) -> Self {
+        Self { values, bar_values, cmp }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn cmp(&mut self, a: usize, b: usize) -> Ordering {
+        (self.cmp)(&self.values[a], &self.values[b])
+    }
+
+    /// True if element `a` may stay before element `b` (stable `<=`)
+    pub fn le(&mut self, a: usize, b: usize) -> bool {
+        self.cmp(a, b) != Ordering::Greater
+    }
+
+    /// Bar value carried by actions that move element `id`
+    pub fn bar_value(&self, id: usize) -> u32 {
+        self.bar_values[id]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nan_sorts_after_every_number() {
+        let mut values = [f64::NAN, 2.5, f64::NEG_INFINITY, f64::NAN, -1.0, f64::INFINITY];
+        values.sort_by(nan_last);
+        assert_eq!(values[..4], [f64::NEG_INFINITY, -1.0, 2.5, f64::INFINITY]);
+        assert!(values[4..].iter().all(|v| v.is_nan()));
+        assert_eq!(nan_last(&f64::NAN, &f64::NAN), Ordering::Equal);
+        assert_eq!(nan_last(&f64::NAN, &f64::INFINITY), Ordering::Greater);
+        assert_eq!(nan_last(&0.0, &f64::NAN), Ordering::Less);
+    }
+
+    #[test]
+    fn ranks_are_dense_and_shared_by_ties() {
+        assert_eq!(rank_values(&[30, 10, 20, 10, 30], u32::cmp), [3, 1, 2, 1, 3]);
+        assert_eq!(rank_values(&[1.5, f64::NAN, -2.0, f64::NAN, 1.5], nan_last), [2, 3, 1, 3, 2]);
+        assert_eq!(rank_values(&[(2, 'a'), (1, 'b'), (2, 'c')], by_key), [2, 1, 2]);
+        assert!(rank_values::<u32, _>(&[], u32::cmp).is_empty());
+    }
+}
@@ -1,7 +1,36 @@
+use std::cmp::Ordering;
 use std::time::Duration;
 
+use crate::algorithms::heap::{HeapOp, HeapRecorder};
+use crate::algorithms::manual::{Baseline, ManualOp, ManualRun};
 use crate::algorithms::merge::{merge_sort_actions, parallel_merge_sort_actions};
+use crate::algorithms::network::{network_actions, network_layers, Comparator};
+use crate::algorithms::search::search_actions;
+use crate::algorithms::select::select_actions;
+use crate::algorithms::{Algorithm, Subsystem};
+use crate::dp::tables::{DpEvent, DpEventKind};
+use crate::dp::{DpProblem, DpRun};
+use crate::element::{by_key, nan_last, Dataset, ElementKind, Keys};
+use crate::graph::traversal::{traversal_events, GraphEvent, GraphEventKind};
+use crate::graph::{Graph, GraphKind, GraphRun};
+use crate::hash::table::{HashEventKind, HashOp, Probing};
+use crate::hash::HashRun;
+use crate::text::matching::{TextEvent, TextEventKind};
+use crate::text::TextRun;
+use crate::tree::model::{TreeEventKind, TreeKind, TreeOp};
+use crate::tree::TreeRun;
 use rand::{rngs::SmallRng, Rng, SeedableRng};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+// Estimated CPU time costs (in nanoseconds)
+// Based on typical modern CPU performance
+const COMPARE_NS: u64 = 3;       // Simple integer comparison
+const MEMORY_READ_NS: u64 = 5;   // L1 cache hit
+const MEMORY_WRITE_NS: u64 = 5;  // L1 cache hit
+const ALLOC_NS: u64 = 200;       // Small allocation (malloc overhead)
+const FREE_NS: u64 = 100;        // Deallocation
 
 /// Execution mode for sorting visualization
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -18,10 +47,22 @@ pub enum ActionKind {
     TempPush,    // Push element to temp array
     TempClear,   // Clear temp array (merge complete)
     MergePhase,  // Signal new merge phase (merge_level stored in value)
+    Probe,       // Search reads a[i] and compares it with the target
+    NarrowRange, // Search window shrinks to [i, j]
+    Found,       // Target found at i
+    NotFound,    // Target absent
+    Pivot,       // Element at i is the current partition pivot
+    Settled,     // Element at i is in its final sorted position
+    Append,      // Grow the array with value at i
+    RemoveLast,  // Shrink the array, dropping value at i
+    Assign,      // Overwrite a[i] with value in place
+    CompareExchange { swap: bool }, // Network comparator on (i, j) in layer `value`; swap if out of order
+    CallEnter,   // Recursive call on [i, j) starts
+    CallExit,    // Recursive call on [i, j) returns
     Done,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct Action {
     pub kind: ActionKind,
     pub i: usize,        // Main array index (target for Write, source for TempPush)
@@ -30,6 +71,94 @@ pub struct Action {
     pub memory: usize,
     pub temp_idx: usize, // Index in temp array (for TempPush/Write)
     pub thread_id: usize, // Thread ID for parallel visualization (0-7)
+    pub origin: usize,    // Original index of the moved element (TempPush/Write)
+    pub note: Option<String>, // Human-readable caption shown while the action plays
+    pub line: Option<usize>,  // Pseudocode line this action executes (index into the algorithm's listing)
+}
+
+/// A recorded step of any run: a sort action, or a graph, table, text, tree or hash event.
+/// Narration and trace export read every stream through this.
+pub trait Step {
+    fn note(&self) -> Option<&str>;
+    fn note_mut(&mut self) -> &mut Option<String>;
+    /// Pseudocode line the step executes, as an index into the algorithm's listing
+    fn line(&self) -> Option<usize>;
+    /// Kind, two operands and a value, as the tab-separated columns of a trace line
+    fn columns(&self) -> String;
+}
+
+impl Step for Action {
+    fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    fn note_mut(&mut self) -> &mut Option<String> {
+        &mut self.note
+    }
+
+    fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    fn columns(&self) -> String {
+        format!("{:?}\t{}\t{}\t{}", self.kind, self.i, self.j, self.value)
+    }
+}
+
+/// Recorded steps of one run, indexable without knowing their type
+pub trait Steps {
+    fn len(&self) -> usize;
+    fn step(&self, index: usize) -> &dyn Step;
+}
+
+impl<T: Step> Steps for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn step(&self, index: usize) -> &dyn Step {
+        &self[index]
+    }
+}
+
+impl Action {
+    /// Whether this action reads or writes main-array slot `index`
+    pub fn touches(&self, index: usize) -> bool {
+        match self.kind {
+            ActionKind::Compare | ActionKind::Swap | ActionKind::CompareExchange { .. } => self.i == index || self.j == index,
+            ActionKind::Write
+            | ActionKind::TempPush
+            | ActionKind::Probe
+            | ActionKind::Found
+            | ActionKind::Pivot
+            | ActionKind::Settled
+            | ActionKind::Append
+            | ActionKind::RemoveLast
+            | ActionKind::Assign => self.i == index,
+            _ => false,
+        }
+    }
+}
+
+/// Slots the element that started at index `origin` has occupied, in order, after replaying `history`.
+/// Swaps carry it along and a write of its origin lands it; ends early if a pop removes it.
+pub fn element_path(history: &[Action], origin: usize) -> Vec<usize> {
+    let mut path = vec![origin];
+    let mut at = origin;
+    for action in history {
+        let next = match action.kind {
+            ActionKind::Swap | ActionKind::CompareExchange { swap: true } if action.i == at => action.j,
+            ActionKind::Swap | ActionKind::CompareExchange { swap: true } if action.j == at => action.i,
+            ActionKind::Write if action.origin == origin => action.i,
+            ActionKind::RemoveLast if action.i == at => break,
+            _ => continue,
+        };
+        if next != at {
+            path.push(next);
+            at = next;
+        }
+    }
+    path
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -76,6 +205,7 @@ impl BarState {
 pub struct Bar {
     pub value: u32,
     pub state: BarState,
+    pub origin: usize, // Index of this element in the initial input
 }
 
 /// Info about the current animation for the renderer
@@ -86,6 +216,7 @@ pub struct AnimationInfo {
     pub target_idx: usize,
     pub source_height: f32,  // Normalized height of source bar
     pub is_temp_push: bool,  // True if pushing to temp array
+    #[allow(dead_code)]
     pub temp_target_idx: usize, // Target index in temp array
     pub thread_id: usize,    // Thread ID for coloring
 }
@@ -94,10 +225,21 @@ pub struct AnimationInfo {
 #[derive(Clone, Debug, Default)]
 pub struct TempArrayState {
     pub values: Vec<u32>,    // Current values in temp array
+    pub origins: Vec<usize>, // Original index of each temp value
+    #[allow(dead_code)]
     pub left_bound: usize,   // Left boundary of merge region
+    #[allow(dead_code)]
     pub right_bound: usize,  // Right boundary of merge region
 }
 
+/// Search progress for visualization
+#[derive(Clone, Debug, Default)]
+pub struct SearchState {
+    pub target: u32,
+    pub window: Option<(usize, usize)>, // Inclusive [lo, hi] still being searched
+    pub result: Option<Option<usize>>,  // None while running, then found index or None
+}
+
 /// Multi-thread temp arrays state
 #[derive(Clone, Debug, Default)]
 pub struct MultiTempArrayState {
@@ -114,6 +256,7 @@ impl MultiTempArrayState {
     pub fn clear_all(&mut self) {
         for arr in &mut self.arrays {
             arr.values.clear();
+            arr.origins.clear();
         }
     }
     
@@ -123,6 +266,76 @@ impl MultiTempArrayState {
     }
 }
 
+/// One recursive call of a divide-and-conquer sort, found by pairing its CallEnter and CallExit actions
+#[derive(Clone, Copy, Debug)]
+pub struct CallFrame {
+    pub left: usize,  // Half-open range [left, right) the call works on
+    pub right: usize,
+    pub depth: usize, // Nesting depth within the calling thread
+    pub thread_id: usize,
+    pub enter: usize, // Action indices of the call's CallEnter and CallExit
+    pub exit: usize,
+    pub end: usize, // calls[i + 1..end] is the subtree below call i
+}
+
+impl CallFrame {
+    /// Whether the call is on its thread's stack once `played` actions have run
+    pub fn is_active(&self, played: usize) -> bool {
+        self.enter < played && played <= self.exit
+    }
+
+    pub fn is_done(&self, played: usize) -> bool {
+        self.exit < played
+    }
+}
+
+/// Pair up call events per thread into the call tree: thread by thread, each in call order,
+/// so every call's subtree directly follows it
+fn call_tree(actions: &[Action]) -> Vec<CallFrame> {
+    let mut threads: Vec<Vec<CallFrame>> = Vec::new();
+    let mut stacks: Vec<Vec<usize>> = Vec::new();
+    for (idx, action) in actions.iter().enumerate() {
+        if stacks.len() <= action.thread_id {
+            stacks.resize(action.thread_id + 1, Vec::new());
+            threads.resize(action.thread_id + 1, Vec::new());
+        }
+        let (stack, calls) = (&mut stacks[action.thread_id], &mut threads[action.thread_id]);
+        match action.kind {
+            ActionKind::CallEnter => {
+                stack.push(calls.len());
+                calls.push(CallFrame {
+                    left: action.i,
+                    right: action.j,
+                    depth: stack.len() - 1,
+                    thread_id: action.thread_id,
+                    enter: idx,
+                    exit: actions.len(),
+                    end: 0,
+                });
+            }
+            ActionKind::CallExit => {
+                if let Some(call) = stack.pop() {
+                    calls[call].exit = idx;
+                    calls[call].end = calls.len();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut calls = Vec::new();
+    for mut thread in threads {
+        let offset = calls.len();
+        let unfinished = offset + thread.len();
+        for call in &mut thread {
+            // Calls still open when the actions run out span the rest of their thread
+            call.end = if call.end == 0 { unfinished } else { call.end + offset };
+        }
+        calls.extend(thread);
+    }
+    calls
+}
+
 pub struct Engine {
     bars: Vec<Bar>,
     actions: Vec<Action>,
@@ -141,9 +354,26 @@ pub struct Engine {
     pub temp_array: TempArrayState,        // For sequential mode
     pub multi_temp_arrays: MultiTempArrayState, // For parallel mode
     pub mode: SortMode,
+    pub algorithm: Algorithm,
+    pub search: SearchState,
+    pub select_k: usize,        // Target rank for selection algorithms
+    pivot: Option<usize>,       // Current partition pivot, highlighted every step
+    heap: Vec<u32>,             // Logical heap after all queued actions
+    pub last_popped: Option<u32>,
+    pub manual: ManualRun,      // Picks and yardsticks while the user sorts by hand
+    pub graph: GraphRun,        // Graph and traversal playback for graph algorithms
+    pub graph_kind: GraphKind,
+    pub dp: DpRun,              // Table playback for dynamic-programming algorithms
+    pub text: TextRun,          // Text and pattern playback for string matching
+    pub tree: TreeRun,          // Search tree and its recorded operations
+    pub hash: HashRun,          // Hash table and its recorded operations
     pub num_threads: usize,
     initial_values: Vec<u32>,  // Store initial values for mode switching
+    dataset: Dataset,          // Typed input the bar values were derived from
     pub merge_level: usize,    // Current merge phase level (segment size = chunk * 2^merge_level)
+    pub network_layer: Option<usize>, // Sorting-network layer played in the last step
+    pub network: Vec<Vec<Comparator>>, // Comparator layers of the sorting network being played
+    pub calls: Vec<CallFrame>,  // Recursion tree of the queued actions
     // Estimated CPU time tracking (in nanoseconds)
     pub est_time_ns: u64,           // Total estimated time in nanoseconds
     pub est_comparison_ns: u64,     // Time spent on comparisons
@@ -155,18 +385,22 @@ pub struct Engine {
 impl Engine {
     pub fn new(size: usize) -> Self {
         let mut rng = SmallRng::from_entropy();
-        let values: Vec<u32> = (0..size).map(|_| rng.gen_range(1..=1000)).collect();
+        let dataset = Dataset::random(ElementKind::Integers, size, &mut rng);
+        let values = dataset.bar_values();
         let max_value = values.iter().copied().max().unwrap_or(1);
         let mode = SortMode::Sequential;
         let num_threads = 8;
         
-        let actions = merge_sort_actions(&values);
+        let actions = generate_actions(&dataset, Algorithm::MergeSort, mode, num_threads, 0);
         let peak_memory = actions.iter().map(|a| a.memory).max().unwrap_or(0);
+        let calls = call_tree(&actions);
         let bars = values
             .iter()
-            .map(|&v| Bar {
+            .enumerate()
+            .map(|(origin, &v)| Bar {
                 value: v,
                 state: BarState::Idle,
+                origin,
             })
             .collect();
 
@@ -188,9 +422,26 @@ impl Engine {
             temp_array: TempArrayState::default(),
             multi_temp_arrays: MultiTempArrayState::new(num_threads),
             mode,
+            algorithm: Algorithm::MergeSort,
+            search: SearchState::default(),
+            select_k: size / 2,
+            pivot: None,
+            heap: Vec::new(),
+            last_popped: None,
+            manual: ManualRun::default(),
+            graph: GraphRun::default(),
+            graph_kind: GraphKind::Grid,
+            dp: DpRun::default(),
+            text: TextRun::default(),
+            tree: TreeRun::default(),
+            hash: HashRun::default(),
             num_threads,
             initial_values: values,
+            dataset,
             merge_level: 0,
+            network_layer: None,
+            network: Vec::new(),
+            calls,
             est_time_ns: 0,
             est_comparison_ns: 0,
             est_memory_ns: 0,
@@ -206,6 +457,94 @@ impl Engine {
         }
     }
 
+    /// Switch algorithm; searches re-arrange the same input into sorted order first
+    pub fn set_algorithm(&mut self, algorithm: Algorithm) {
+        if self.algorithm != algorithm {
+            self.algorithm = algorithm;
+            self.reset_with_dataset();
+        }
+    }
+
+    /// Choose which order statistic the selection algorithms look for
+    pub fn set_select_k(&mut self, k: usize) {
+        let k = k.min(self.initial_values.len().saturating_sub(1));
+        if self.select_k != k {
+            self.select_k = k;
+            if self.algorithm.is_selection() {
+                self.regenerate_actions();
+            }
+        }
+    }
+
+    /// Queue a priority-queue operation; its actions play after the ones already queued
+    pub fn apply_heap_op(&mut self, op: HeapOp) {
+        if self.algorithm != Algorithm::PriorityQueue {
+            return;
+        }
+        // Keep at least one element so there is always something to draw
+        if op == HeapOp::Pop && self.heap.len() <= 1 {
+            return;
+        }
+        HeapRecorder { values: &mut self.heap, actions: &mut self.actions }.apply(op);
+    }
+
+    /// Record and play one step of a manual sort right away, even while paused
+    pub fn apply_manual_op(&mut self, op: ManualOp) {
+        if self.algorithm != Algorithm::ManualSort {
+            return;
+        }
+        let values: Vec<u32> = self.bars.iter().map(|bar| bar.value).collect();
+        self.manual.apply(op, &values, &mut self.actions);
+        while self.cursor < self.actions.len() {
+            self.step_timer = self.step_delay;
+            self.step(Duration::ZERO);
+        }
+        if let Some(index) = self.manual.selected {
+            self.mark(index, BarState::Source);
+        }
+    }
+
+    /// Queue a search-tree operation; its events play after the ones already queued
+    pub fn apply_tree_op(&mut self, op: TreeOp) {
+        if self.algorithm.is_tree() {
+            self.tree.apply(op);
+        }
+    }
+
+    /// Queue a hash-table operation; its events play after the ones already queued
+    pub fn apply_hash_op(&mut self, op: HashOp) {
+        if self.algorithm.is_hash() {
+            self.hash.apply(op);
+        }
+    }
+
+    /// Priority queue contents once every queued action has played; the bars may still lag behind
+    pub fn heap(&self) -> &[u32] {
+        &self.heap
+    }
+
+    /// Switch graph shape; generates a fresh graph if a graph algorithm is active
+    pub fn set_graph_kind(&mut self, kind: GraphKind) {
+        if self.graph_kind != kind {
+            self.graph_kind = kind;
+            if self.algorithm.is_graph() {
+                self.reset_with_dataset();
+            }
+        }
+    }
+
+    pub fn element_kind(&self) -> ElementKind {
+        self.dataset.kind()
+    }
+
+    /// Switch element type; draws a fresh random input of that type
+    pub fn set_element_kind(&mut self, kind: ElementKind) {
+        if self.dataset.kind() != kind {
+            self.dataset = Dataset::random(kind, self.initial_values.len(), &mut self.rng);
+            self.reset_with_dataset();
+        }
+    }
+
     fn regenerate_actions(&mut self) {
         self.cursor = 0;
         self.comparisons = 0;
@@ -218,37 +557,101 @@ impl Engine {
         self.temp_array = TempArrayState::default();
         self.multi_temp_arrays = MultiTempArrayState::new(self.num_threads);
         self.merge_level = 0;
+        self.network_layer = None;
         self.est_time_ns = 0;
         self.est_comparison_ns = 0;
         self.est_memory_ns = 0;
         self.memory_allocs = 0;
         self.memory_deallocs = 0;
+        self.search.window = None;
+        self.search.result = None;
+        self.pivot = None;
+        self.last_popped = None;
         
-        // Restore bars to initial values
-        for (bar, &val) in self.bars.iter_mut().zip(self.initial_values.iter()) {
-            bar.value = val;
-            bar.state = BarState::Idle;
-        }
+        // Restore bars to initial values (priority-queue ops may have changed the length)
+        self.bars = self
+            .initial_values
+            .iter()
+            .enumerate()
+            .map(|(origin, &value)| Bar {
+                value,
+                state: BarState::Idle,
+                origin,
+            })
+            .collect();
+        self.max_value = self.initial_values.iter().copied().max().unwrap_or(1);
         
         // Generate actions based on mode
-        self.actions = match self.mode {
-            SortMode::Sequential => merge_sort_actions(&self.initial_values),
-            SortMode::Parallel => parallel_merge_sort_actions(&self.initial_values, self.num_threads),
-        };
+        self.actions = self.generate_actions();
+        self.calls = call_tree(&self.actions);
         self.peak_memory = self.actions.iter().map(|a| a.memory).max().unwrap_or(0);
     }
 
+    /// Play the same input again from the start
+    pub fn replay(&mut self) {
+        self.regenerate_actions();
+    }
+
+    /// Replay from the start until at least `action` steps have played, or none are left to play.
+    /// A manual sort runs out as soon as its recorded actions have played, sorted or not.
+    pub fn seek(&mut self, action: usize) {
+        self.replay();
+        loop {
+            let (steps, played) = self.steps();
+            if played >= action || played >= steps.len() || self.is_finished() {
+                break;
+            }
+            self.step(self.step_delay());
+        }
+    }
+
+    /// Time each action is shown before the next one plays
+    pub fn step_delay(&self) -> Duration {
+        Duration::from_secs_f32(self.step_delay)
+    }
+
     pub fn reset(&mut self) {
-        let size = self.bars.len();
-        let values: Vec<u32> = (0..size).map(|_| self.rng.gen_range(1..=1000)).collect();
+        self.dataset = Dataset::random(self.dataset.kind(), self.initial_values.len(), &mut self.rng);
+        self.reset_with_dataset();
+    }
+
+    fn reset_with_dataset(&mut self) {
+        let mut values = self.dataset.bar_values();
         self.max_value = values.iter().copied().max().unwrap_or(1);
+        self.search = SearchState::default();
+        self.pivot = None;
+        self.last_popped = None;
+        if self.algorithm.is_search() {
+            // Searches need sorted input; usually look for a value that is present
+            values.sort_unstable();
+            self.search.target = if !values.is_empty() && self.rng.gen_bool(0.7) {
+                values[self.rng.gen_range(0..values.len())]
+            } else {
+                self.rng.gen_range(1..=self.max_value)
+            };
+        }
+        match self.algorithm.subsystem() {
+            Subsystem::Array => {}
+            Subsystem::Graph => {
+                self.graph = GraphRun::new(Graph::generate(self.graph_kind, &mut self.rng), Vec::new(), false);
+            }
+            Subsystem::Dp => self.dp = DpRun::new(DpProblem::random(self.algorithm, &mut self.rng)),
+            Subsystem::Text => self.text = TextRun::random(self.algorithm, &mut self.rng),
+            Subsystem::Tree => {
+                let keys = self.distinct_keys(12);
+                self.tree = TreeRun::new(tree_kind(self.algorithm), keys);
+            }
+            Subsystem::Hash => {
+                // Enough keys to force one resize from the initial 8 buckets
+                let keys = self.distinct_keys(10);
+                self.hash = HashRun::new(probing(self.algorithm), keys);
+            }
+        }
         self.initial_values = values.clone();
         
         // Generate actions based on current mode
-        self.actions = match self.mode {
-            SortMode::Sequential => merge_sort_actions(&values),
-            SortMode::Parallel => parallel_merge_sort_actions(&values, self.num_threads),
-        };
+        self.actions = self.generate_actions();
+        self.calls = call_tree(&self.actions);
         self.peak_memory = self.actions.iter().map(|a| a.memory).max().unwrap_or(0);
         self.cursor = 0;
         self.comparisons = 0;
@@ -261,24 +664,281 @@ impl Engine {
         self.temp_array = TempArrayState::default();
         self.multi_temp_arrays = MultiTempArrayState::new(self.num_threads);
         self.merge_level = 0;
+        self.network_layer = None;
         self.est_time_ns = 0;
         self.est_comparison_ns = 0;
         self.est_memory_ns = 0;
         self.memory_allocs = 0;
         self.memory_deallocs = 0;
         
-        for (bar, val) in self.bars.iter_mut().zip(values.into_iter()) {
-            bar.value = val;
-            bar.state = BarState::Idle;
+        self.bars = values
+            .into_iter()
+            .enumerate()
+            .map(|(origin, value)| Bar {
+                value,
+                state: BarState::Idle,
+                origin,
+            })
+            .collect();
+    }
+
+    /// Distinct keys from 1..=99 in random order
+    fn distinct_keys(&mut self, count: usize) -> Vec<u32> {
+        let mut keys: Vec<u32> = (1..=99).collect();
+        for i in 0..count {
+            let j = self.rng.gen_range(i..keys.len());
+            keys.swap(i, j);
         }
+        keys.truncate(count);
+        keys
+    }
+
+    fn generate_actions(&mut self) -> Vec<Action> {
+        self.network = if self.algorithm.is_network() {
+            network_layers(self.algorithm, self.initial_values.len())
+        } else {
+            Vec::new()
+        };
+        match self.algorithm.subsystem() {
+            Subsystem::Array => self.generate_array_actions(),
+            Subsystem::Graph => {
+                // Graph algorithms replay their own event stream over the current graph
+                let graph = std::mem::take(&mut self.graph.graph);
+                let events = traversal_events(self.algorithm, &graph);
+                self.graph = GraphRun::new(graph, events, self.algorithm.is_mst());
+                Vec::new()
+            }
+            Subsystem::Dp => {
+                // Restart table playback on the same inputs
+                self.dp = DpRun::new(self.dp.problem.clone());
+                Vec::new()
+            }
+            Subsystem::Text => {
+                let text = std::mem::take(&mut self.text);
+                self.text = TextRun::new(self.algorithm, text.text, text.pattern);
+                Vec::new()
+            }
+            Subsystem::Tree => {
+                // Rebuild from the same keys; queued operations are dropped
+                self.tree = TreeRun::new(tree_kind(self.algorithm), self.tree.initial_keys.clone());
+                Vec::new()
+            }
+            Subsystem::Hash => {
+                self.hash = HashRun::new(probing(self.algorithm), self.hash.initial_keys.clone());
+                Vec::new()
+            }
+        }
+    }
+
+    fn generate_array_actions(&mut self) -> Vec<Action> {
+        if self.algorithm.is_search() {
+            search_actions(self.algorithm, &self.initial_values, self.search.target)
+        } else if self.algorithm == Algorithm::PriorityQueue {
+            let mut actions = Vec::new();
+            self.heap = self.initial_values.clone();
+            HeapRecorder { values: &mut self.heap, actions: &mut actions }.heapify();
+            actions
+        } else if self.algorithm == Algorithm::ManualSort {
+            // The user records the actions; every built-in sort on the same input is the yardstick
+            let baselines = Algorithm::ALL
+                .into_iter()
+                .filter(|algorithm| algorithm.is_sort())
+                .map(|algorithm| {
+                    let actions = generate_actions(&self.dataset, algorithm, SortMode::Sequential, self.num_threads, 0);
+                    Baseline::from_actions(algorithm, &actions)
+                })
+                .collect();
+            self.manual = ManualRun::new(baselines);
+            Vec::new()
+        } else {
+            generate_actions(&self.dataset, self.algorithm, self.mode, self.num_threads, self.select_k)
+        }
+    }
+
+    /// Every recorded step of the active run, and how many of them have played
+    fn steps(&self) -> (&dyn Steps, usize) {
+        match self.algorithm.subsystem() {
+            Subsystem::Array => (&self.actions, self.cursor),
+            Subsystem::Graph => (self.graph.events(), self.graph.played()),
+            Subsystem::Dp => (self.dp.events(), self.dp.played()),
+            Subsystem::Text => (self.text.events(), self.text.played()),
+            Subsystem::Tree => (self.tree.events(), self.tree.played()),
+            Subsystem::Hash => (self.hash.events(), self.hash.played()),
+        }
+    }
+
+    /// Captions of the most recently played steps, newest first
+    pub fn narration(&self, count: usize) -> Vec<&str> {
+        let (steps, played) = self.steps();
+        (0..played).rev().filter_map(|i| steps.step(i).note()).take(count).collect()
+    }
+
+    /// Number of actions played so far
+    pub fn played(&self) -> usize {
+        self.cursor
+    }
+
+    /// Actions played so far, oldest first
+    pub fn history(&self) -> &[Action] {
+        &self.actions[..self.cursor]
+    }
+
+    /// Calls on the stack once the played actions have run: each thread's outermost first.
+    /// Inactive subtrees are skipped whole, so this only walks the active calls and their siblings.
+    pub fn active_calls(&self) -> Vec<&CallFrame> {
+        let mut active = Vec::new();
+        let mut i = 0;
+        while let Some(call) = self.calls.get(i) {
+            if call.is_active(self.cursor) {
+                active.push(call);
+                i += 1;
+            } else {
+                i = call.end;
+            }
+        }
+        active
+    }
+
+    /// Pseudocode line of the most recently played action or event
+    pub fn current_line(&self) -> Option<usize> {
+        let (steps, played) = self.steps();
+        played.checked_sub(1).and_then(|last| steps.step(last).line())
+    }
+
+    /// Write the whole recorded stream, one tab-separated line per action or event with its caption
+    pub fn export_trace(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let (steps, _) = self.steps();
+        let subject = match self.algorithm.subsystem() {
+            Subsystem::Array => format!("{} elements", self.initial_values.len()),
+            Subsystem::Graph => format!("{} nodes and {} edges", self.graph.graph.node_count(), self.graph.graph.edges.len()),
+            Subsystem::Dp => self.dp.problem.describe(),
+            Subsystem::Text => {
+                let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+                format!("text {}, pattern {}", text(&self.text.text), text(&self.text.pattern))
+            }
+            Subsystem::Tree => format!("keys {:?}", self.tree.initial_keys),
+            Subsystem::Hash => format!("keys {:?}", self.hash.initial_keys),
+        };
+        writeln!(out, "# {} on {}", self.algorithm.label(), subject)?;
+        writeln!(out, "step\tkind\ti\tj\tvalue\tnote")?;
+        for index in 0..steps.len() {
+            let step = steps.step(index);
+            writeln!(out, "{}\t{}\t{}", index, step.columns(), step.note().unwrap_or(""))?;
+        }
+        out.flush()
     }
 
     pub fn bars(&self) -> (&[Bar], u32) {
         (&self.bars, self.max_value)
     }
 
+    pub fn is_finished(&self) -> bool {
+        match self.algorithm.subsystem() {
+            Subsystem::Array if self.algorithm == Algorithm::ManualSort => self.manual.sorted,
+            Subsystem::Array => self.cursor >= self.actions.len(),
+            Subsystem::Graph => self.graph.is_finished(),
+            Subsystem::Dp => self.dp.is_finished(),
+            Subsystem::Text => self.text.is_finished(),
+            Subsystem::Tree => self.tree.is_finished(),
+            Subsystem::Hash => self.hash.is_finished(),
+        }
+    }
+
+    /// Whether equal values kept their original relative order.
+    /// `None` if the input has no duplicate values to check.
+    pub fn stability(&self) -> Option<bool> {
+        // A stable sort by value keeps equal values in their current order,
+        // so only neighbours in that order need their origins compared
+        let mut order: Vec<usize> = (0..self.bars.len()).collect();
+        order.sort_by_key(|&i| self.bars[i].value);
+        let mut has_duplicates = false;
+        let mut preserved = true;
+        for pair in order.windows(2) {
+            let (a, b) = (&self.bars[pair[0]], &self.bars[pair[1]]);
+            if a.value == b.value {
+                has_duplicates = true;
+                preserved &= a.origin < b.origin;
+            }
+        }
+        has_duplicates.then_some(preserved)
+    }
+
+    /// For each bar sharing its value with others: its rank by original position among them,
+    /// and how many there are. `None` for bars with a unique value.
+    pub fn duplicate_ranks(&self) -> Vec<Option<(usize, usize)>> {
+        let mut order: Vec<usize> = (0..self.bars.len()).collect();
+        order.sort_unstable_by_key(|&i| (self.bars[i].value, self.bars[i].origin));
+        let mut ranks = vec![None; self.bars.len()];
+        for group in order.chunk_by(|&a, &b| self.bars[a].value == self.bars[b].value) {
+            if group.len() > 1 {
+                for (rank, &i) in group.iter().enumerate() {
+                    ranks[i] = Some((rank, group.len()));
+                }
+            }
+        }
+        ranks
+    }
+
     pub fn step(&mut self, dt: Duration) {
+        match self.algorithm.subsystem() {
+            Subsystem::Array => self.step_actions(dt),
+            Subsystem::Graph => {
+                if let Some(event) = self.play(dt, |engine| engine.graph.advance()) {
+                    self.count_graph_event(event);
+                }
+            }
+            Subsystem::Dp => {
+                if let Some(event) = self.play(dt, |engine| engine.dp.advance()) {
+                    self.count_dp_event(event);
+                }
+            }
+            Subsystem::Text => {
+                if let Some(event) = self.play(dt, |engine| engine.text.advance()) {
+                    self.count_text_event(event);
+                }
+            }
+            Subsystem::Tree => {
+                if let Some(kind) = self.play(dt, |engine| engine.tree.advance()) {
+                    self.count_tree_event(kind);
+                }
+            }
+            Subsystem::Hash => {
+                if let Some(kind) = self.play(dt, |engine| engine.hash.advance()) {
+                    self.count_hash_event(kind);
+                }
+            }
+        }
+    }
+
+    /// Advance the playback clock; true once the next step is due, which restarts the wait
+    fn due(&mut self, dt: Duration) -> bool {
+        self.time_elapsed += dt;
+        self.step_timer += dt.as_secs_f32();
+
+        if self.step_timer < self.step_delay {
+            return false;
+        }
+        self.step_timer = 0.0;
+        true
+    }
+
+    /// Take the next event of a run that replays its own events, once one is due
+    fn play<E>(&mut self, dt: Duration, advance: impl FnOnce(&mut Self) -> Option<E>) -> Option<E> {
+        if self.is_finished() || !self.due(dt) {
+            return None;
+        }
+        advance(self)
+    }
+
+    /// Play one action of the shared action stream
+    fn step_actions(&mut self, dt: Duration) {
         if self.cursor >= self.actions.len() {
+            // Searches and selections keep their highlight; sorts are fully sorted once done
+            if !self.algorithm.is_sort() {
+                self.pivot = None;
+                return;
+            }
             // Mark sorted once done
             for bar in &mut self.bars {
                 bar.state = BarState::Sorted;
@@ -286,13 +946,9 @@ impl Engine {
             return;
         }
 
-        self.time_elapsed += dt;
-        self.step_timer += dt.as_secs_f32();
-
-        if self.step_timer < self.step_delay {
+        if !self.due(dt) {
             return;
         }
-        self.step_timer = 0.0;
 
         // Clear transient states
         for bar in &mut self.bars {
@@ -300,10 +956,13 @@ impl Engine {
                 bar.state = BarState::Idle;
             }
         }
+        if let Some(pivot) = self.pivot {
+            self.mark(pivot, BarState::Source);
+        }
 
         // Process exactly one action
         if self.cursor < self.actions.len() {
-            let action = self.actions[self.cursor];
+            let action = self.actions[self.cursor].clone();
             let thread_id = action.thread_id;
             
             // Update current memory usage
@@ -314,14 +973,6 @@ impl Engine {
                 self.current_memory = action.memory;
             }
             
-            // Estimated CPU time costs (in nanoseconds)
-            // Based on typical modern CPU performance
-            const COMPARE_NS: u64 = 3;       // Simple integer comparison
-            const MEMORY_READ_NS: u64 = 5;   // L1 cache hit
-            const MEMORY_WRITE_NS: u64 = 5;  // L1 cache hit
-            const ALLOC_NS: u64 = 200;       // Small allocation (malloc overhead)
-            const FREE_NS: u64 = 100;        // Deallocation
-            
             match action.kind {
                 ActionKind::Compare => {
                     self.comparisons += 1;
@@ -349,6 +1000,12 @@ impl Engine {
                     
                     self.current_animation.active = false;
                     self.bars.swap(action.i, action.j);
+                    // Pivot highlight follows the pivot element
+                    if self.pivot == Some(action.i) {
+                        self.pivot = Some(action.j);
+                    } else if self.pivot == Some(action.j) {
+                        self.pivot = Some(action.i);
+                    }
                     let state = if self.mode == SortMode::Parallel {
                         BarState::from_thread_id(thread_id)
                     } else {
@@ -362,7 +1019,7 @@ impl Engine {
                     // Cost: 1 read from main array + 1 write to temp array
                     // First push in a merge also includes allocation cost
                     let is_first_push = if self.mode == SortMode::Parallel {
-                        self.multi_temp_arrays.arrays.get(thread_id).map_or(true, |a| a.values.is_empty())
+                        self.multi_temp_arrays.arrays.get(thread_id).is_none_or(|a| a.values.is_empty())
                     } else {
                         self.temp_array.values.is_empty()
                     };
@@ -393,6 +1050,7 @@ impl Engine {
                     if self.mode == SortMode::Parallel {
                         if let Some(arr) = self.multi_temp_arrays.arrays.get_mut(thread_id) {
                             arr.values.push(action.value);
+                            arr.origins.push(action.origin);
                         }
                         // Update current and peak memory from actual temp arrays
                         self.current_memory = self.multi_temp_arrays.total_memory();
@@ -401,6 +1059,7 @@ impl Engine {
                         }
                     } else {
                         self.temp_array.values.push(action.value);
+                        self.temp_array.origins.push(action.origin);
                     }
                     
                     // Mark source bar
@@ -435,15 +1094,18 @@ impl Engine {
                         if let Some(arr) = self.multi_temp_arrays.arrays.get_mut(thread_id) {
                             if !arr.values.is_empty() {
                                 arr.values.remove(0);
+                                arr.origins.remove(0);
                             }
                         }
                     } else if !self.temp_array.values.is_empty() {
                         self.temp_array.values.remove(0);
+                        self.temp_array.origins.remove(0);
                     }
                     
                     // Mark and update target bar
                     if let Some(bar) = self.bars.get_mut(action.i) {
                         bar.value = action.value;
+                        bar.origin = action.origin;
                         bar.state = if self.mode == SortMode::Parallel {
                             BarState::from_thread_id(thread_id)
                         } else {
@@ -463,9 +1125,11 @@ impl Engine {
                     if self.mode == SortMode::Parallel {
                         if let Some(arr) = self.multi_temp_arrays.arrays.get_mut(thread_id) {
                             arr.values.clear();
+                            arr.origins.clear();
                         }
                     } else {
                         self.temp_array.values.clear();
+                        self.temp_array.origins.clear();
                     }
                 }
                 ActionKind::MergePhase => {
@@ -473,10 +1137,106 @@ impl Engine {
                     self.merge_level = action.value as usize;
                     self.current_animation.active = false;
                 }
+                ActionKind::Probe => {
+                    self.comparisons += 1;
+                    // Cost: 1 memory read + 1 comparison against the target
+                    let cost = MEMORY_READ_NS + COMPARE_NS;
+                    self.est_comparison_ns += cost;
+                    self.est_time_ns += cost;
+                    
+                    self.current_animation.active = false;
+                    self.mark(action.i, BarState::Compare);
+                }
+                ActionKind::NarrowRange => {
+                    self.current_animation.active = false;
+                    self.search.window = Some((action.i, action.j));
+                }
+                ActionKind::Found => {
+                    self.current_animation.active = false;
+                    self.search.window = Some((action.i, action.i));
+                    self.search.result = Some(Some(action.i));
+                    self.mark(action.i, BarState::Sorted);
+                }
+                ActionKind::NotFound => {
+                    self.current_animation.active = false;
+                    self.search.window = None;
+                    self.search.result = Some(None);
+                }
+                ActionKind::Pivot => {
+                    self.current_animation.active = false;
+                    self.pivot = Some(action.i);
+                    self.mark(action.i, BarState::Source);
+                }
+                ActionKind::Settled => {
+                    self.current_animation.active = false;
+                    if self.pivot == Some(action.i) {
+                        self.pivot = None;
+                    }
+                    if let Some(bar) = self.bars.get_mut(action.i) {
+                        bar.state = BarState::Sorted;
+                    }
+                }
+                ActionKind::Append => {
+                    self.operations += 1;
+                    self.memory_ops += 1;
+                    let cost = MEMORY_WRITE_NS;
+                    self.est_memory_ns += cost;
+                    self.est_time_ns += cost;
+                    
+                    self.current_animation.active = false;
+                    self.max_value = self.max_value.max(action.value);
+                    self.bars.push(Bar {
+                        value: action.value,
+                        state: BarState::Swap,
+                        origin: action.origin,
+                    });
+                }
+                ActionKind::RemoveLast => {
+                    self.memory_ops += 1;
+                    let cost = MEMORY_READ_NS;
+                    self.est_memory_ns += cost;
+                    self.est_time_ns += cost;
+                    
+                    self.current_animation.active = false;
+                    self.bars.pop();
+                    self.last_popped = Some(action.value);
+                }
+                ActionKind::Assign => {
+                    self.operations += 1;
+                    self.memory_ops += 1;
+                    let cost = MEMORY_WRITE_NS;
+                    self.est_memory_ns += cost;
+                    self.est_time_ns += cost;
+                    
+                    self.current_animation.active = false;
+                    if let Some(bar) = self.bars.get_mut(action.i) {
+                        bar.value = action.value;
+                    }
+                    self.mark(action.i, BarState::Swap);
+                }
+                ActionKind::CompareExchange { .. } => {
+                    // A whole layer runs in one step: its comparators touch disjoint wires
+                    self.current_animation.active = false;
+                    self.network_layer = Some(action.value as usize);
+                    self.compare_exchange(&action);
+                    while let Some(next) = self.actions.get(self.cursor + 1).cloned() {
+                        if !matches!(next.kind, ActionKind::CompareExchange { .. }) || next.value != action.value {
+                            break;
+                        }
+                        self.cursor += 1;
+                        self.compare_exchange(&next);
+                    }
+                }
+                // Calls only move the recursion brackets and the pseudocode highlight
+                ActionKind::CallEnter | ActionKind::CallExit => {
+                    self.current_animation.active = false;
+                }
                 ActionKind::Done => {
+                    self.network_layer = None;
                     self.current_memory = 0;
                     self.current_animation.active = false;
                     self.temp_array.values.clear();
+                    self.temp_array.origins.clear();
                     self.multi_temp_arrays.clear_all();
                     for bar in &mut self.bars {
                         bar.state = BarState::Sorted;
@@ -487,6 +1247,281 @@ impl Engine {
         }
     }
 
+    /// Apply one network comparator, exchanging the pair if it is out of order
+    fn compare_exchange(&mut self, action: &Action) {
+        self.comparisons += 1;
+        // Cost: 2 memory reads + 1 comparison
+        let mut cost = 2 * MEMORY_READ_NS + COMPARE_NS;
+        self.est_comparison_ns += cost;
+        let state = if let ActionKind::CompareExchange { swap: true } = action.kind {
+            self.operations += 1;
+            self.memory_ops += 1;
+            // Exchange: 2 writes
+            self.est_memory_ns += 2 * MEMORY_WRITE_NS;
+            cost += 2 * MEMORY_WRITE_NS;
+            self.bars.swap(action.i, action.j);
+            BarState::Swap
+        } else {
+            BarState::Compare
+        };
+        self.est_time_ns += cost;
+        let state = if self.mode == SortMode::Parallel {
+            BarState::from_thread_id(action.thread_id)
+        } else {
+            state
+        };
+        self.mark(action.i, state);
+        self.mark(action.j, state);
+    }
+
+    /// Count the cost of a played graph event; relaxations count as comparisons, frontier traffic as memory ops
+    fn count_graph_event(&mut self, event: GraphEvent) {
+        match event.kind {
+            GraphEventKind::PushFrontier => {
+                self.memory_ops += 1;
+                let cost = MEMORY_WRITE_NS;
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            GraphEventKind::PopFrontier => {
+                self.memory_ops += 1;
+                let cost = MEMORY_READ_NS;
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            GraphEventKind::Visit => {
+                self.operations += 1;
+            }
+            GraphEventKind::RelaxEdge { improved } => {
+                self.comparisons += 1;
+                // Cost: read both distances + 1 comparison
+                let cost = 2 * MEMORY_READ_NS + COMPARE_NS;
+                self.est_comparison_ns += cost;
+                self.est_time_ns += cost;
+                if improved {
+                    // Write the new distance and parent
+                    self.memory_ops += 1;
+                    let cost = 2 * MEMORY_WRITE_NS;
+                    self.est_memory_ns += cost;
+                    self.est_time_ns += cost;
+                }
+            }
+            GraphEventKind::ExamineEdge => {
+                // Cost: read the next edge from the sorted list
+                self.memory_ops += 1;
+                let cost = MEMORY_READ_NS;
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            GraphEventKind::FindHop { .. } => {
+                // Cost: read parent[x] + compare it with x
+                self.memory_ops += 1;
+                let cost = MEMORY_READ_NS + COMPARE_NS;
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            GraphEventKind::Compress { .. } => {
+                self.memory_ops += 1;
+                let cost = MEMORY_WRITE_NS;
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            GraphEventKind::Union { rank_bump, .. } => {
+                self.comparisons += 1;
+                self.operations += 1;
+                // Cost: read and compare both ranks, write the parent (and the rank on a tie)
+                let compare_cost = 2 * MEMORY_READ_NS + COMPARE_NS;
+                let write_cost = if rank_bump { 2 * MEMORY_WRITE_NS } else { MEMORY_WRITE_NS };
+                self.est_comparison_ns += compare_cost;
+                self.est_memory_ns += write_cost;
+                self.est_time_ns += compare_cost + write_cost;
+            }
+            GraphEventKind::AcceptEdge => {
+                self.operations += 1;
+            }
+            GraphEventKind::PathStep | GraphEventKind::RejectEdge | GraphEventKind::Done => {}
+        }
+
+        // Frontier entries and union-find parents/ranks are node ids
+        let union_find_bytes = self.graph.union_find.as_ref().map_or(0, |uf| uf.parent.len() * (std::mem::size_of::<usize>() + 4));
+        self.current_memory = self.graph.frontier * std::mem::size_of::<usize>() + union_find_bytes;
+        self.peak_memory = self.peak_memory.max(self.current_memory);
+    }
+
+    /// Count the cost of a played DP table event; cell reads and writes are memory ops, input element tests are comparisons
+    fn count_dp_event(&mut self, event: DpEvent) {
+        match event.kind {
+            DpEventKind::Allocate => {
+                self.memory_allocs += 1;
+                self.est_memory_ns += ALLOC_NS;
+                self.est_time_ns += ALLOC_NS;
+                self.current_memory = self.dp.table_bytes();
+                self.peak_memory = self.peak_memory.max(self.current_memory);
+            }
+            DpEventKind::Compare => {
+                self.comparisons += 1;
+                // Cost: read both inputs + 1 comparison
+                let cost = 2 * MEMORY_READ_NS + COMPARE_NS;
+                self.est_comparison_ns += cost;
+                self.est_time_ns += cost;
+            }
+            DpEventKind::Read => {
+                self.memory_ops += 1;
+                self.est_memory_ns += MEMORY_READ_NS;
+                self.est_time_ns += MEMORY_READ_NS;
+            }
+            DpEventKind::Write => {
+                self.operations += 1;
+                self.memory_ops += 1;
+                self.est_memory_ns += MEMORY_WRITE_NS;
+                self.est_time_ns += MEMORY_WRITE_NS;
+            }
+            DpEventKind::Trace | DpEventKind::Done => {}
+        }
+    }
+
+    /// Count the cost of a played string-matching event; character and hash comparisons share the comparison counter
+    fn count_text_event(&mut self, event: TextEvent) {
+        match event.kind {
+            TextEventKind::Compare { .. } | TextEventKind::HashCompare { .. } => {
+                self.comparisons += 1;
+                // Cost: 2 memory reads + 1 comparison
+                let cost = 2 * MEMORY_READ_NS + COMPARE_NS;
+                self.est_comparison_ns += cost;
+                self.est_time_ns += cost;
+            }
+            TextEventKind::AuxRead => {
+                self.memory_ops += 1;
+                self.est_memory_ns += MEMORY_READ_NS;
+                self.est_time_ns += MEMORY_READ_NS;
+            }
+            TextEventKind::AuxWrite => {
+                self.memory_ops += 1;
+                self.est_memory_ns += MEMORY_WRITE_NS;
+                self.est_time_ns += MEMORY_WRITE_NS;
+            }
+            TextEventKind::Match => {
+                self.operations += 1;
+            }
+            TextEventKind::Align | TextEventKind::SpuriousHit | TextEventKind::Done => {}
+        }
+        self.current_memory = self.text.aux_bytes();
+        self.peak_memory = self.peak_memory.max(self.current_memory);
+    }
+
+    /// Count the cost of a played search-tree event; node allocation and rotations show up in the memory stats
+    fn count_tree_event(&mut self, kind: TreeEventKind) {
+        match kind {
+            TreeEventKind::Visit => {
+                self.comparisons += 1;
+                // Cost: read node key + 1 comparison
+                let cost = MEMORY_READ_NS + COMPARE_NS;
+                self.est_comparison_ns += cost;
+                self.est_time_ns += cost;
+            }
+            TreeEventKind::Insert => {
+                self.memory_allocs += 1;
+                self.operations += 1;
+                // Cost: allocate the node + link it into its parent
+                let cost = ALLOC_NS + MEMORY_WRITE_NS;
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            TreeEventKind::Remove => {
+                self.memory_deallocs += 1;
+                self.operations += 1;
+                let cost = FREE_NS + MEMORY_WRITE_NS;
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            TreeEventKind::Rotate { .. } => {
+                self.operations += 1;
+                self.memory_ops += 1;
+                // Cost: rewire up to 6 child/parent pointers
+                let cost = 6 * MEMORY_WRITE_NS;
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            TreeEventKind::Recolor => {
+                self.memory_ops += 1;
+                self.est_memory_ns += MEMORY_WRITE_NS;
+                self.est_time_ns += MEMORY_WRITE_NS;
+            }
+            TreeEventKind::ReplaceKey => {
+                self.memory_ops += 1;
+                let cost = MEMORY_READ_NS + MEMORY_WRITE_NS;
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            TreeEventKind::Found | TreeEventKind::NotFound => {}
+        }
+        // Node: key + three links + color/height
+        self.current_memory = self.tree.shape.nodes.len() * (4 + 3 * std::mem::size_of::<usize>() + 4);
+        self.peak_memory = self.peak_memory.max(self.current_memory);
+    }
+
+    /// Count the cost of a played hash-table event; resizing shows up as an allocation and a release
+    fn count_hash_event(&mut self, kind: HashEventKind) {
+        let chaining = self.hash.probing() == Probing::Chaining;
+        match kind {
+            HashEventKind::Hash => {
+                // Cost: multiply + shift, about one comparison
+                self.est_comparison_ns += COMPARE_NS;
+                self.est_time_ns += COMPARE_NS;
+            }
+            HashEventKind::Probe { .. } => {
+                self.comparisons += 1;
+                // Cost: read slot + 1 comparison
+                let cost = MEMORY_READ_NS + COMPARE_NS;
+                self.est_comparison_ns += cost;
+                self.est_time_ns += cost;
+            }
+            HashEventKind::Write => {
+                self.memory_ops += 1;
+                let mut cost = MEMORY_WRITE_NS;
+                if chaining {
+                    // New chain node
+                    self.memory_allocs += 1;
+                    cost += ALLOC_NS;
+                }
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            HashEventKind::Displace => {
+                self.operations += 1;
+                self.memory_ops += 1;
+                // Cost: read the resident + write the new key
+                let cost = MEMORY_READ_NS + MEMORY_WRITE_NS;
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            HashEventKind::Erase { .. } => {
+                self.memory_ops += 1;
+                let mut cost = MEMORY_WRITE_NS;
+                if chaining {
+                    self.memory_deallocs += 1;
+                    cost += FREE_NS;
+                }
+                self.est_memory_ns += cost;
+                self.est_time_ns += cost;
+            }
+            HashEventKind::Resize => {
+                self.memory_allocs += 1;
+                self.operations += 1;
+                self.est_memory_ns += ALLOC_NS;
+                self.est_time_ns += ALLOC_NS;
+            }
+            HashEventKind::FreeOld => {
+                self.memory_deallocs += 1;
+                self.est_memory_ns += FREE_NS;
+                self.est_time_ns += FREE_NS;
+            }
+            HashEventKind::Found | HashEventKind::NotFound | HashEventKind::Done => {}
+        }
+        self.current_memory = self.hash.table_bytes();
+        self.peak_memory = self.peak_memory.max(self.current_memory);
+    }
+
     fn mark(&mut self, idx: usize, state: BarState) {
         if let Some(bar) = self.bars.get_mut(idx) {
             if bar.state != BarState::Sorted {
@@ -495,3 +1530,169 @@ impl Engine {
         }
     }
 }
+
+fn probing(algorithm: Algorithm) -> Probing {
+    match algorithm {
+        Algorithm::QuadraticProbing => Probing::Quadratic,
+        Algorithm::DoubleHashing => Probing::Double,
+        Algorithm::RobinHoodHashing => Probing::RobinHood,
+        Algorithm::SeparateChaining => Probing::Chaining,
+        _ => Probing::Linear,
+    }
+}
+
+fn tree_kind(algorithm: Algorithm) -> TreeKind {
+    match algorithm {
+        Algorithm::AvlTree => TreeKind::Avl,
+        Algorithm::RedBlackTree => TreeKind::RedBlack,
+        _ => TreeKind::Bst,
+    }
+}
+
+/// Generate actions for the dataset, comparing elements with the comparator for their type
+fn generate_actions(dataset: &Dataset, algorithm: Algorithm, mode: SortMode, num_threads: usize, select_k: usize) -> Vec<Action> {
+    let bar_values = dataset.bar_values();
+    match dataset {
+        Dataset::Integers(v) => keyed_actions(Keys::new(v, &bar_values, u32::cmp), algorithm, mode, num_threads, select_k),
+        Dataset::Floats(v) => keyed_actions(Keys::new(v, &bar_values, nan_last), algorithm, mode, num_threads, select_k),
+        Dataset::Strings(v) => keyed_actions(Keys::new(v, &bar_values, String::cmp), algorithm, mode, num_threads, select_k),
+        Dataset::Tuples(v) => keyed_actions(Keys::new(v, &bar_values, by_key), algorithm, mode, num_threads, select_k),
+    }
+}
+
+fn keyed_actions<T, F>(keys: Keys<T, F>, algorithm: Algorithm, mode: SortMode, num_threads: usize, select_k: usize) -> Vec<Action>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if algorithm.is_selection() {
+        return select_actions(algorithm, keys, select_k);
+    }
+    if algorithm.is_network() {
+        // Networks are the same schedule in both modes; parallel mode only colours by comparator slot
+        return network_actions(algorithm, keys, num_threads);
+    }
+    match mode {
+        SortMode::Sequential => merge_sort_actions(keys),
+        SortMode::Parallel => parallel_merge_sort_actions(keys, num_threads),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn call_subtrees_follow_their_call_and_active_calls_skip_the_rest() {
+        for mode in [SortMode::Sequential, SortMode::Parallel] {
+            let mut engine = Engine::new(37);
+            engine.set_mode(mode);
+            let calls = &engine.calls;
+            for (i, call) in calls.iter().enumerate() {
+                for below in &calls[i + 1..call.end] {
+                    assert_eq!(below.thread_id, call.thread_id);
+                    assert!(below.depth > call.depth && call.enter < below.enter && below.exit < call.exit);
+                }
+                assert!(calls[call.end..].iter().all(|next| next.thread_id != call.thread_id || next.enter > call.exit));
+            }
+
+            for played in 0..=engine.actions.len() {
+                engine.cursor = played;
+                let expected: Vec<(usize, usize)> =
+                    engine.calls.iter().filter(|c| c.is_active(played)).map(|c| (c.thread_id, c.enter)).collect();
+                let active: Vec<(usize, usize)> = engine.active_calls().iter().map(|c| (c.thread_id, c.enter)).collect();
+                assert_eq!(active, expected, "{mode:?} after {played} actions");
+            }
+        }
+    }
+
+    /// Engine running `algorithm` over these integers instead of a random dataset
+    fn engine_on(values: &[u32], algorithm: Algorithm) -> Engine {
+        let mut engine = Engine::new(values.len());
+        engine.algorithm = algorithm;
+        engine.dataset = Dataset::Integers(values.to_vec());
+        engine.reset_with_dataset();
+        engine
+    }
+
+    #[test]
+    fn stability_compares_the_order_of_equal_values() {
+        let duplicates = [3, 1, 3, 2, 1, 3, 2, 1];
+        let mut merge = engine_on(&duplicates, Algorithm::MergeSort);
+        merge.seek(usize::MAX);
+        assert_eq!(merge.stability(), Some(true));
+        // Each group of equal values ranks its bars by original position
+        let ranks = merge.duplicate_ranks();
+        for (bar, rank) in merge.bars.iter().zip(&ranks) {
+            let (rank, count) = rank.expect("every value is duplicated");
+            let group: Vec<usize> = merge.bars.iter().filter(|b| b.value == bar.value).map(|b| b.origin).collect();
+            assert_eq!((group.iter().filter(|&&o| o < bar.origin).count(), group.len()), (rank, count));
+        }
+
+        // The network ends with both pairs of equal values reversed
+        let mut bitonic = engine_on(&[2, 2, 1, 1], Algorithm::BitonicSort);
+        bitonic.seek(usize::MAX);
+        assert_eq!(bitonic.stability(), Some(false));
+
+        let mut distinct = engine_on(&[5, 2, 8, 1, 9, 3], Algorithm::MergeSort);
+        distinct.seek(usize::MAX);
+        assert_eq!(distinct.stability(), None);
+        assert!(distinct.duplicate_ranks().iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn seek_returns_on_an_unsorted_manual_run() {
+        let mut engine = engine_on(&[3, 1, 2], Algorithm::ManualSort);
+        engine.seek(10);
+        assert_eq!(engine.played(), 0);
+        assert!(!engine.is_finished());
+    }
+
+    fn action(kind: ActionKind, i: usize, j: usize, origin: usize) -> Action {
+        Action { kind, i, j, value: 0, memory: 0, temp_idx: 0, thread_id: 0, origin, note: None, line: None }
+    }
+
+    #[test]
+    fn touches_reads_both_slots_of_pairs_and_i_otherwise() {
+        let compare = action(ActionKind::Compare, 1, 3, 1);
+        assert!(compare.touches(1) && compare.touches(3) && !compare.touches(2));
+        let exchange = action(ActionKind::CompareExchange { swap: false }, 0, 5, 0);
+        assert!(exchange.touches(0) && exchange.touches(5));
+        // A write's j is not a main-array slot
+        let write = action(ActionKind::Write, 2, 7, 4);
+        assert!(write.touches(2) && !write.touches(7) && !write.touches(4));
+        for kind in [ActionKind::CallEnter, ActionKind::NarrowRange, ActionKind::TempClear, ActionKind::Done] {
+            assert!(!action(kind, 0, 1, 0).touches(0), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn element_path_follows_swaps_and_writes_of_its_origin() {
+        let history = [
+            action(ActionKind::Compare, 0, 1, 0),
+            action(ActionKind::Swap, 0, 1, 0),                            // 0 -> 1
+            action(ActionKind::CompareExchange { swap: false }, 1, 2, 1), // Stays
+            action(ActionKind::CompareExchange { swap: true }, 2, 1, 2),  // 1 -> 2
+            action(ActionKind::Write, 2, 0, 5),                           // Another element lands on its slot
+            action(ActionKind::Write, 4, 0, 0),                           // 2 -> 4
+            action(ActionKind::Write, 4, 0, 0),                           // Already there
+            action(ActionKind::Swap, 3, 3, 3),
+        ];
+        assert_eq!(element_path(&history, 0), [0, 1, 2, 4]);
+        assert_eq!(element_path(&history, 3), [3]);
+        // Popping the slot it occupies ends the path
+        let popped = [action(ActionKind::Swap, 0, 2, 0), action(ActionKind::RemoveLast, 0, 0, 0), action(ActionKind::Swap, 0, 1, 0)];
+        assert_eq!(element_path(&popped, 2), [2, 0]);
+    }
+
+    #[test]
+    fn element_path_ends_where_the_element_settles() {
+        for algorithm in [Algorithm::MergeSort, Algorithm::BitonicSort] {
+            let mut engine = Engine::new(16);
+            engine.set_algorithm(algorithm);
+            engine.seek(usize::MAX);
+            for (slot, bar) in engine.bars.iter().enumerate() {
+                assert_eq!(element_path(engine.history(), bar.origin).last(), Some(&slot), "{algorithm:?}");
+            }
+        }
+    }
+}
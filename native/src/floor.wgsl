@@ -2,13 +2,24 @@ struct Globals {
     view_proj: mat4x4<f32>,
     bar_width: f32,
     max_value: f32,
-    focus_distance: f32,
-    focus_range: f32,
+    _padding: vec2<f32>,
 };
 
 @group(0) @binding(0)
 var<uniform> globals: Globals;
 
+// Colors from the active theme (ThemeUniform in theme.rs)
+struct Theme {
+    states: array<vec4<f32>, 40>,
+    fallback: vec4<f32>,
+    background: vec4<f32>,
+    floor_tint: vec4<f32>,
+    grid_glow: vec4<f32>,
+};
+
+@group(0) @binding(1)
+var<uniform> theme: Theme;
+
 struct VSIn {
     @location(0) position: vec3<f32>,
     @location(1) uv: vec2<f32>,
@@ -42,7 +53,8 @@ fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
     let uv = in.uv;
 
     // Sample PBR textures
-    let albedo = textureSample(floor_albedo, floor_sampler, uv).rgb;
+    let texel = textureSample(floor_albedo, floor_sampler, uv).rgb;
+    let albedo = texel * theme.floor_tint.rgb;
     let rma = textureSample(floor_rma, floor_sampler, uv).rgb;
     let rough = clamp(rma.r, 0.04, 1.0);
     let metal = rma.g;
@@ -65,10 +77,10 @@ fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
 
     var color = diff + spec_col;
 
-    // Emissive from bright traces
-    let glow_src = max(albedo.r, max(albedo.g, albedo.b));
+    // Emissive from bright traces (the grid), judged before tinting so every theme keeps its grid
+    let glow_src = max(texel.r, max(texel.g, texel.b));
     let glow = smoothstep(0.7, 0.95, glow_src);
-    color += glow * vec3<f32>(0.10, 0.40, 0.90);
+    color += glow * theme.grid_glow.rgb;
 
     return vec4<f32>(color, 1.0);
 }
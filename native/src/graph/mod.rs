@@ -0,0 +1,416 @@
+pub mod traversal;
+
+use rand::Rng;
+
+/// Shape of the generated graph
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphKind {
+    Grid,
+    Random,
+}
+
+impl GraphKind {
+    pub const ALL: [GraphKind; 2] = [GraphKind::Grid, GraphKind::Random];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GraphKind::Grid => "Grid (8×5)",
+            GraphKind::Random => "Random geometric",
+        }
+    }
+}
+
+/// Undirected weighted edge
+#[derive(Clone, Copy, Debug)]
+pub struct Edge {
+    pub a: usize,
+    pub b: usize,
+    pub weight: u32,
+}
+
+impl Edge {
+    pub fn other(&self, node: usize) -> usize {
+        if node == self.a { self.b } else { self.a }
+    }
+}
+
+/// Graph with node positions on the unit square (x, z) and adjacency lists of edge indices
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    pub positions: Vec<[f32; 2]>,
+    pub edges: Vec<Edge>,
+    pub adjacency: Vec<Vec<usize>>,
+    pub start: usize,
+    pub goal: usize,
+}
+
+impl Graph {
+    pub fn generate<R: Rng>(kind: GraphKind, rng: &mut R) -> Self {
+        match kind {
+            GraphKind::Grid => Self::grid(8, 5, rng),
+            GraphKind::Random => Self::random(24, rng),
+        }
+    }
+
+    fn with_positions(positions: Vec<[f32; 2]>) -> Self {
+        let n = positions.len();
+        Self {
+            positions,
+            edges: Vec::new(),
+            adjacency: vec![Vec::new(); n],
+            start: 0,
+            goal: n.saturating_sub(1),
+        }
+    }
+
+    /// 4-connected grid with random weights 1..=9, start top-left, goal bottom-right
+    pub fn grid<R: Rng>(cols: usize, rows: usize, rng: &mut R) -> Self {
+        let positions = (0..rows)
+            .flat_map(|r| (0..cols).map(move |c| [c as f32 / (cols - 1) as f32, r as f32 / (rows - 1) as f32]))
+            .collect();
+        let mut graph = Self::with_positions(positions);
+        for r in 0..rows {
+            for c in 0..cols {
+                let node = r * cols + c;
+                if c + 1 < cols {
+                    graph.add_edge(node, node + 1, rng.gen_range(1..=9));
+                }
+                if r + 1 < rows {
+                    graph.add_edge(node, node + cols, rng.gen_range(1..=9));
+                }
+            }
+        }
+        graph
+    }
+
+    /// Random points joined to their 3 nearest neighbours, weights proportional to length.
+    /// Components are bridged by their closest pair so the graph is always connected.
+    pub fn random<R: Rng>(n: usize, rng: &mut R) -> Self {
+        let positions: Vec<[f32; 2]> = (0..n).map(|_| [rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0)]).collect();
+        let mut graph = Self::with_positions(positions);
+
+        for node in 0..n {
+            let mut by_distance: Vec<usize> = (0..n).filter(|&other| other != node).collect();
+            by_distance.sort_by(|&x, &y| graph.distance(node, x).total_cmp(&graph.distance(node, y)));
+            for &other in by_distance.iter().take(3) {
+                graph.add_weighted_by_length(node, other);
+            }
+        }
+
+        loop {
+            let component = graph.component_ids();
+            if component.iter().all(|&c| c == 0) {
+                break;
+            }
+            // Closest pair between component 0 and the rest
+            let mut best: Option<(usize, usize)> = None;
+            for x in (0..n).filter(|&x| component[x] == 0) {
+                for y in (0..n).filter(|&y| component[y] != 0) {
+                    if best.is_none_or(|(bx, by)| graph.distance(x, y) < graph.distance(bx, by)) {
+                        best = Some((x, y));
+                    }
+                }
+            }
+            if let Some((x, y)) = best {
+                graph.add_weighted_by_length(x, y);
+            }
+        }
+
+        // Search from the leftmost to the rightmost node
+        let leftmost = |a: &usize, b: &usize| graph.positions[*a][0].total_cmp(&graph.positions[*b][0]);
+        graph.start = (0..n).min_by(leftmost).unwrap_or(0);
+        graph.goal = (0..n).max_by(leftmost).unwrap_or(0);
+        graph
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn distance(&self, a: usize, b: usize) -> f32 {
+        let (pa, pb) = (self.positions[a], self.positions[b]);
+        ((pa[0] - pb[0]).powi(2) + (pa[1] - pb[1]).powi(2)).sqrt()
+    }
+
+    fn add_edge(&mut self, a: usize, b: usize, weight: u32) {
+        let idx = self.edges.len();
+        self.edges.push(Edge { a, b, weight });
+        self.adjacency[a].push(idx);
+        self.adjacency[b].push(idx);
+    }
+
+    fn add_weighted_by_length(&mut self, a: usize, b: usize) {
+        let exists = self.adjacency[a].iter().any(|&e| self.edges[e].other(a) == b);
+        if !exists {
+            let weight = ((self.distance(a, b) * 20.0).round() as u32).max(1);
+            self.add_edge(a, b, weight);
+        }
+    }
+
+    /// Component id of each node (0 is the component of node 0)
+    fn component_ids(&self) -> Vec<usize> {
+        let n = self.node_count();
+        let mut ids = vec![usize::MAX; n];
+        let mut next = 0;
+        for root in 0..n {
+            if ids[root] != usize::MAX {
+                continue;
+            }
+            let mut stack = vec![root];
+            ids[root] = next;
+            while let Some(node) = stack.pop() {
+                for &e in &self.adjacency[node] {
+                    let other = self.edges[e].other(node);
+                    if ids[other] == usize::MAX {
+                        ids[other] = next;
+                        stack.push(other);
+                    }
+                }
+            }
+            next += 1;
+        }
+        ids
+    }
+
+    /// Admissible A* heuristic: straight-line distance scaled by the cheapest weight per unit length
+    pub fn heuristic(&self, from: usize, to: usize) -> u32 {
+        let scale = self
+            .edges
+            .iter()
+            .map(|e| e.weight as f32 / self.distance(e.a, e.b).max(f32::EPSILON))
+            .fold(f32::INFINITY, f32::min);
+        if scale.is_finite() {
+            (self.distance(from, to) * scale).floor() as u32
+        } else {
+            0
+        }
+    }
+}
+
+/// Display state of a node during playback
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeState {
+    Unseen,
+    Frontier,
+    Current,
+    Visited,
+    Path,
+}
+
+/// Display state of an edge during playback
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeState {
+    Idle,
+    Active, // Being examined / relaxed right now
+    Tree,   // Parent edge of its endpoint in the search tree
+    Path,
+}
+
+/// Union-find forest for Kruskal, drawn as an auxiliary bar row of parent pointers
+#[derive(Clone, Debug, Default)]
+pub struct UnionFindState {
+    pub parent: Vec<usize>,
+    pub rank: Vec<u32>,
+    pub active: Option<usize>,  // Node whose parent pointer was just read
+    pub changed: Option<usize>, // Node whose parent pointer was just rewritten
+    pub find_hops: usize,
+    pub compressions: usize,
+    pub unions: usize,
+}
+
+impl UnionFindState {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            ..Default::default()
+        }
+    }
+}
+
+/// Graph plus the playback of a traversal's events over it
+#[derive(Clone, Debug, Default)]
+pub struct GraphRun {
+    pub graph: Graph,
+    events: Vec<traversal::GraphEvent>,
+    cursor: usize,
+    pub nodes: Vec<NodeState>,
+    pub edges: Vec<EdgeState>,
+    pub dist: Vec<Option<u32>>,
+    parent_edge: Vec<Option<usize>>, // Search-tree edge per node; for spanning trees, only once joined
+    spanning: bool,                   // Kruskal or Prim: the tree is a spanning tree, not a search tree
+    active_edge: Option<usize>,
+    current: Option<usize>,
+    pub frontier: usize,
+    pub visited: usize,
+    pub tree_weight: u32,                    // Total weight of spanning-tree edges so far
+    pub union_find: Option<UnionFindState>, // Present when the algorithm uses union-find
+}
+
+impl GraphRun {
+    /// `spanning` is set for the minimum spanning tree algorithms
+    pub fn new(graph: Graph, events: Vec<traversal::GraphEvent>, spanning: bool) -> Self {
+        let n = graph.node_count();
+        let m = graph.edges.len();
+        let uses_union_find = events.iter().any(|e| matches!(e.kind, traversal::GraphEventKind::FindHop { .. }));
+        Self {
+            graph,
+            events,
+            cursor: 0,
+            nodes: vec![NodeState::Unseen; n],
+            edges: vec![EdgeState::Idle; m],
+            dist: vec![None; n],
+            parent_edge: vec![None; n],
+            spanning,
+            active_edge: None,
+            current: None,
+            frontier: 0,
+            visited: 0,
+            tree_weight: 0,
+            union_find: uses_union_find.then(|| UnionFindState::new(n)),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.events.len()
+    }
+
+    pub fn events(&self) -> &Vec<traversal::GraphEvent> {
+        &self.events
+    }
+
+    /// Number of events played so far
+    pub fn played(&self) -> usize {
+        self.cursor
+    }
+
+    /// Apply the next event, returning it so the engine can count its cost
+    pub fn advance(&mut self) -> Option<traversal::GraphEvent> {
+        use traversal::GraphEventKind;
+
+        let event = self.events.get(self.cursor)?.clone();
+        self.cursor += 1;
+
+        if let Some(uf) = self.union_find.as_mut() {
+            uf.active = None;
+            uf.changed = None;
+        }
+        // The previously examined edge falls back to tree or idle.
+        // Union-find work keeps the edge under consideration highlighted.
+        let is_union_find = matches!(
+            event.kind,
+            GraphEventKind::FindHop { .. } | GraphEventKind::Compress { .. } | GraphEventKind::Union { .. }
+        );
+        let finished_edge = if is_union_find { None } else { self.active_edge.take() };
+        if let Some(edge) = finished_edge {
+            if self.edges[edge] == EdgeState::Active {
+                let e = self.graph.edges[edge];
+                let is_tree = self.parent_edge[e.a] == Some(edge) || self.parent_edge[e.b] == Some(edge);
+                self.edges[edge] = if is_tree { EdgeState::Tree } else { EdgeState::Idle };
+            }
+        }
+
+        match event.kind {
+            GraphEventKind::PushFrontier => {
+                self.frontier += 1;
+                if self.nodes[event.node] == NodeState::Unseen {
+                    self.nodes[event.node] = NodeState::Frontier;
+                }
+            }
+            GraphEventKind::PopFrontier => {
+                self.frontier = self.frontier.saturating_sub(1);
+            }
+            GraphEventKind::Visit => {
+                if let Some(prev) = self.current.replace(event.node) {
+                    self.nodes[prev] = NodeState::Visited;
+                }
+                self.nodes[event.node] = NodeState::Current;
+                self.dist[event.node] = Some(event.dist);
+                // DFS and Prim report their tree edge on visit
+                if let Some(edge) = event.edge {
+                    self.parent_edge[event.node] = Some(edge);
+                    self.edges[edge] = EdgeState::Tree;
+                    if self.spanning {
+                        self.tree_weight += self.graph.edges[edge].weight;
+                    }
+                }
+                self.visited += 1;
+            }
+            GraphEventKind::RelaxEdge { improved } => {
+                if let Some(edge) = event.edge {
+                    self.edges[edge] = EdgeState::Active;
+                    self.active_edge = Some(edge);
+                    // Prim's cheaper edge is only a candidate until its node joins the tree
+                    if improved && !self.spanning {
+                        // Replace the node's old tree edge
+                        if let Some(old) = self.parent_edge[event.node].replace(edge) {
+                            if self.edges[old] == EdgeState::Tree {
+                                self.edges[old] = EdgeState::Idle;
+                            }
+                        }
+                    }
+                    if improved {
+                        self.dist[event.node] = Some(event.dist);
+                    }
+                }
+            }
+            GraphEventKind::ExamineEdge => {
+                if let Some(edge) = event.edge {
+                    self.edges[edge] = EdgeState::Active;
+                    self.active_edge = Some(edge);
+                }
+            }
+            GraphEventKind::PathStep => {
+                self.nodes[event.node] = NodeState::Path;
+                if let Some(edge) = event.edge {
+                    self.edges[edge] = EdgeState::Path;
+                }
+            }
+            GraphEventKind::FindHop { .. } => {
+                if let Some(uf) = self.union_find.as_mut() {
+                    uf.active = Some(event.node);
+                    uf.find_hops += 1;
+                }
+            }
+            GraphEventKind::Compress { root } => {
+                if let Some(uf) = self.union_find.as_mut() {
+                    uf.parent[event.node] = root;
+                    uf.changed = Some(event.node);
+                    uf.compressions += 1;
+                }
+            }
+            GraphEventKind::Union { root, rank_bump } => {
+                if let Some(uf) = self.union_find.as_mut() {
+                    uf.parent[event.node] = root;
+                    if rank_bump {
+                        uf.rank[root] += 1;
+                    }
+                    uf.changed = Some(event.node);
+                    uf.unions += 1;
+                }
+            }
+            GraphEventKind::AcceptEdge => {
+                if let Some(edge) = event.edge {
+                    let e = self.graph.edges[edge];
+                    self.edges[edge] = EdgeState::Tree;
+                    self.nodes[e.a] = NodeState::Visited;
+                    self.nodes[e.b] = NodeState::Visited;
+                    self.tree_weight += e.weight;
+                }
+            }
+            GraphEventKind::RejectEdge => {
+                if let Some(edge) = event.edge {
+                    self.edges[edge] = EdgeState::Idle;
+                }
+            }
+            GraphEventKind::Done => {
+                if let Some(prev) = self.current.take() {
+                    if self.nodes[prev] == NodeState::Current {
+                        self.nodes[prev] = NodeState::Visited;
+                    }
+                }
+            }
+        }
+        Some(event)
+    }
+}
@@ -0,0 +1,567 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use super::Graph;
+use crate::algorithms::{annotate, Algorithm};
+use crate::engine::Step;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphEventKind {
+    PushFrontier,                 // Node enters the queue / stack / priority queue
+    PopFrontier,                  // Node leaves the frontier
+    Visit,                        // Node is expanded (dist is final for BFS/Dijkstra/A*)
+    RelaxEdge { improved: bool }, // Edge to node examined; improved if dist got shorter
+    ExamineEdge,                  // Kruskal takes the next lightest edge (dist holds its weight)
+    PathStep,                     // Node (and edge to it) is on the final start→goal path
+    FindHop { parent: usize },    // Union-find reads parent[node]
+    Compress { root: usize },     // Path compression points node straight at root
+    Union { root: usize, rank_bump: bool }, // Root node is linked under root; rank_bump if ranks were equal
+    AcceptEdge,                   // Edge joins two components and enters the spanning tree
+    RejectEdge,                   // Edge would close a cycle
+    Done,
+}
+
+#[derive(Clone, Debug)]
+pub struct GraphEvent {
+    pub kind: GraphEventKind,
+    pub node: usize,
+    pub edge: Option<usize>,
+    pub dist: u32, // Hops for BFS/DFS, path weight for Dijkstra/A*, edge weight for Prim and Kruskal; 0 for union-find
+    pub note: Option<String>,
+    pub line: Option<usize>,
+}
+
+impl Step for GraphEvent {
+    fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    fn note_mut(&mut self) -> &mut Option<String> {
+        &mut self.note
+    }
+
+    fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    fn columns(&self) -> String {
+        let edge = self.edge.map_or("-".to_string(), |e| e.to_string());
+        format!("{:?}\t{}\t{}\t{}", self.kind, self.node, edge, self.dist)
+    }
+}
+
+fn event(kind: GraphEventKind, node: usize, edge: Option<usize>, dist: u32, line: usize) -> GraphEvent {
+    GraphEvent { kind, node, edge, dist, note: None, line: Some(line) }
+}
+
+// Searches and Prim share one loop shape; only the frontier and the test on line 6 differ
+const LINE_START: usize = 0;
+const LINE_POP: usize = 2;
+const LINE_STALE: usize = 3;
+const LINE_VISIT: usize = 4;
+const LINE_RELAX: usize = 6;
+const LINE_PUSH: usize = 7;
+const LINE_PATH: usize = 8;
+
+// Kruskal's edge loop, then find
+const LINE_EXAMINE: usize = 0;
+const LINE_REJECT: usize = 2;
+const LINE_UNION: usize = 3;
+const LINE_ACCEPT: usize = 4;
+const LINE_FIND_HOP: usize = 7;
+const LINE_COMPRESS: usize = 8;
+
+/// Listing the events' lines index into
+pub fn pseudocode(algorithm: Algorithm) -> &'static [&'static str] {
+    match algorithm {
+        Algorithm::Bfs => &[
+            "queue = [start]; hops[start] = 0",
+            "while queue is not empty:",
+            "    node = queue.pop_front()",
+            "    # nodes are queued once, so none is stale",
+            "    visit node; stop if node == goal",
+            "    for each edge node–next:",
+            "        if next is unseen: hops[next] = hops[node] + 1",
+            "            parent[next] = edge; queue.push_back(next)",
+            "walk parent edges back from the goal",
+        ],
+        Algorithm::Dfs => &[
+            "stack = [start]",
+            "while stack is not empty:",
+            "    node = stack.pop()",
+            "    if node is visited: continue",
+            "    visit node, parent[node] = edge it came by; stop if node == goal",
+            "    for each edge node–next, last first:",
+            "        if next is not visited:",
+            "            stack.push(next)",
+            "walk parent edges back from the goal",
+        ],
+        Algorithm::Dijkstra => &[
+            "dist[start] = 0; heap = [(0, start)]",
+            "while heap is not empty:",
+            "    node = heap.pop_min()",
+            "    if node is done: continue",
+            "    mark node done, dist[node] is final; stop if node == goal",
+            "    for each edge node–next of weight w, next not done:",
+            "        if dist[node] + w < dist[next]:",
+            "            dist[next] = dist[node] + w; parent[next] = edge; heap.push((dist[next], next))",
+            "walk parent edges back from the goal",
+        ],
+        Algorithm::AStar => &[
+            "dist[start] = 0; heap = [(h(start), start)]",
+            "while heap is not empty:",
+            "    node = heap.pop_min()   # lowest dist + h",
+            "    if node is done: continue",
+            "    mark node done, dist[node] is final; stop if node == goal",
+            "    for each edge node–next of weight w, next not done:",
+            "        if dist[node] + w < dist[next]:",
+            "            dist[next] = dist[node] + w; parent[next] = edge; heap.push((dist[next] + h(next), next))",
+            "walk parent edges back from the goal",
+        ],
+        Algorithm::Prim => &[
+            "key[start] = 0; heap = [(0, start)]",
+            "while heap is not empty:",
+            "    node = heap.pop_min()",
+            "    if node is in the tree: continue",
+            "    add node to the tree by edge via[node]",
+            "    for each edge node–next of weight w, next not in the tree:",
+            "        if w < key[next]:",
+            "            key[next] = w; via[next] = edge; heap.push((w, next))",
+        ],
+        Algorithm::Kruskal => &[
+            "for each edge a–b by increasing weight, until V - 1 are kept:",
+            "    ra, rb = find(a), find(b)",
+            "    if ra == rb: skip the edge, it would close a cycle",
+            "    union(ra, rb): the shallower root goes under the deeper",
+            "    keep the edge",
+            "",
+            "find(x):",
+            "    r = x; while parent[r] != r: r = parent[r]",
+            "    point every node on the way straight at r",
+        ],
+        _ => &[],
+    }
+}
+
+/// Generate the event stream for a graph algorithm: a start→goal search,
+/// or a minimum spanning tree for Kruskal and Prim
+pub fn traversal_events(algorithm: Algorithm, graph: &Graph) -> Vec<GraphEvent> {
+    let mut events = Vec::new();
+    if graph.node_count() == 0 {
+        return events;
+    }
+    let parent = match algorithm {
+        Algorithm::Bfs => Some(bfs(graph, &mut events)),
+        Algorithm::Dfs => Some(dfs(graph, &mut events)),
+        Algorithm::Dijkstra => Some(best_first(graph, false, &mut events)),
+        Algorithm::AStar => Some(best_first(graph, true, &mut events)),
+        Algorithm::Kruskal => {
+            kruskal(graph, &mut events);
+            None
+        }
+        Algorithm::Prim => {
+            prim(graph, &mut events);
+            None
+        }
+        _ => None,
+    };
+
+    // Walk parent edges back from the goal
+    if let Some(parent) = parent {
+        let mut path = Vec::new();
+        let mut node = graph.goal;
+        while let Some(edge) = parent[node] {
+            path.push((node, Some(edge)));
+            node = graph.edges[edge].other(node);
+        }
+        if node == graph.start {
+            path.push((node, None));
+            let mut cost = 0;
+            for &(node, edge) in path.iter().rev() {
+                events.push(event(GraphEventKind::PathStep, node, edge, 0, LINE_PATH));
+                match edge {
+                    Some(edge) => {
+                        cost += graph.edges[edge].weight;
+                        annotate(&mut events, format!("path: on to node {node}, total weight {cost}"));
+                    }
+                    None => annotate(&mut events, format!("path: traced back from the goal to start node {node}")),
+                }
+            }
+        }
+    }
+    events.push(GraphEvent { line: None, ..event(GraphEventKind::Done, graph.goal, None, 0, 0) });
+    annotate(&mut events, "done".to_string());
+    events
+}
+
+fn bfs(graph: &Graph, events: &mut Vec<GraphEvent>) -> Vec<Option<usize>> {
+    let n = graph.node_count();
+    let mut parent = vec![None; n];
+    let mut hops: Vec<Option<u32>> = vec![None; n];
+    let mut queue = VecDeque::new();
+
+    hops[graph.start] = Some(0);
+    queue.push_back(graph.start);
+    events.push(event(GraphEventKind::PushFrontier, graph.start, None, 0, LINE_START));
+    annotate(events, format!("enqueue the start node {}", graph.start));
+
+    while let Some(node) = queue.pop_front() {
+        let d = hops[node].unwrap_or(0);
+        events.push(event(GraphEventKind::PopFrontier, node, None, d, LINE_POP));
+        annotate(events, format!("dequeue node {node}, the oldest in the queue"));
+        events.push(event(GraphEventKind::Visit, node, None, d, LINE_VISIT));
+        annotate(events, format!("visit node {node}: {d} hops from the start"));
+        if node == graph.goal {
+            annotate(events, format!("visit node {node}: the goal, {d} hops from the start"));
+            break;
+        }
+        for &edge in &graph.adjacency[node] {
+            let next = graph.edges[edge].other(node);
+            let improved = hops[next].is_none();
+            events.push(event(GraphEventKind::RelaxEdge { improved }, next, Some(edge), d + 1, LINE_RELAX));
+            if improved {
+                annotate(events, format!("edge {node}–{next}: node {next} unseen, reached in {} hops", d + 1));
+                hops[next] = Some(d + 1);
+                parent[next] = Some(edge);
+                queue.push_back(next);
+                events.push(event(GraphEventKind::PushFrontier, next, Some(edge), d + 1, LINE_PUSH));
+                annotate(events, format!("enqueue node {next}"));
+            } else {
+                annotate(events, format!("edge {node}–{next}: node {next} already seen"));
+            }
+        }
+    }
+    parent
+}
+
+fn dfs(graph: &Graph, events: &mut Vec<GraphEvent>) -> Vec<Option<usize>> {
+    let n = graph.node_count();
+    let mut parent = vec![None; n];
+    let mut visited = vec![false; n];
+    // (node, edge it was reached by, depth)
+    let mut stack = vec![(graph.start, None, 0)];
+    events.push(event(GraphEventKind::PushFrontier, graph.start, None, 0, LINE_START));
+    annotate(events, format!("push the start node {}", graph.start));
+
+    while let Some((node, via, depth)) = stack.pop() {
+        let pop_line = if visited[node] { LINE_STALE } else { LINE_POP };
+        events.push(event(GraphEventKind::PopFrontier, node, via, depth, pop_line));
+        if visited[node] {
+            annotate(events, format!("pop node {node}: already visited, skip it"));
+            continue;
+        }
+        annotate(events, format!("pop node {node}, the newest on the stack"));
+        visited[node] = true;
+        parent[node] = via;
+        events.push(event(GraphEventKind::Visit, node, via, depth, LINE_VISIT));
+        let goal = if node == graph.goal { ", the goal" } else { "" };
+        annotate(events, format!("visit node {node}{goal} at depth {depth}"));
+        if node == graph.goal {
+            break;
+        }
+        // Push in reverse so the first neighbour is explored first
+        for &edge in graph.adjacency[node].iter().rev() {
+            let next = graph.edges[edge].other(node);
+            // DFS fixes parents on visit, not on push, so edges never count as improvements
+            events.push(event(GraphEventKind::RelaxEdge { improved: false }, next, Some(edge), depth + 1, LINE_RELAX));
+            if !visited[next] {
+                annotate(events, format!("edge {node}–{next}: node {next} not visited yet"));
+                stack.push((next, Some(edge), depth + 1));
+                events.push(event(GraphEventKind::PushFrontier, next, Some(edge), depth + 1, LINE_PUSH));
+                annotate(events, format!("push node {next}"));
+            } else {
+                annotate(events, format!("edge {node}–{next}: node {next} already visited"));
+            }
+        }
+    }
+    parent
+}
+
+/// Dijkstra, or A* when `use_heuristic` orders the frontier by g + h
+fn best_first(graph: &Graph, use_heuristic: bool, events: &mut Vec<GraphEvent>) -> Vec<Option<usize>> {
+    let n = graph.node_count();
+    let mut parent = vec![None; n];
+    let mut dist = vec![u32::MAX; n];
+    let mut done = vec![false; n];
+    let h = |node: usize| if use_heuristic { graph.heuristic(node, graph.goal) } else { 0 };
+    let mut frontier = BinaryHeap::new();
+
+    let priority = if use_heuristic { "distance + estimate" } else { "distance" };
+    dist[graph.start] = 0;
+    frontier.push(Reverse((h(graph.start), graph.start)));
+    events.push(event(GraphEventKind::PushFrontier, graph.start, None, 0, LINE_START));
+    annotate(events, format!("push the start node {} at distance 0", graph.start));
+
+    while let Some(Reverse((key, node))) = frontier.pop() {
+        let pop_line = if done[node] { LINE_STALE } else { LINE_POP };
+        events.push(event(GraphEventKind::PopFrontier, node, None, dist[node], pop_line));
+        // Stale entry: node already expanded with a shorter distance
+        if done[node] {
+            annotate(events, format!("pop node {node}: stale entry, already expanded"));
+            continue;
+        }
+        annotate(events, format!("pop node {node}, lowest {priority} {key}"));
+        done[node] = true;
+        events.push(event(GraphEventKind::Visit, node, None, dist[node], LINE_VISIT));
+        let goal = if node == graph.goal { ", the goal" } else { "" };
+        annotate(events, format!("expand node {node}{goal}: distance {} is final", dist[node]));
+        if node == graph.goal {
+            break;
+        }
+        for &edge in &graph.adjacency[node] {
+            let next = graph.edges[edge].other(node);
+            if done[next] {
+                continue;
+            }
+            let weight = graph.edges[edge].weight;
+            let candidate = dist[node] + weight;
+            let improved = candidate < dist[next];
+            events.push(event(GraphEventKind::RelaxEdge { improved }, next, Some(edge), candidate, LINE_RELAX));
+            let known = if dist[next] == u32::MAX { "∞".to_string() } else { dist[next].to_string() };
+            let verdict = if improved { "shorter, update it" } else { "no improvement" };
+            annotate(events, format!("relax {node}–{next}: {} + {weight} = {candidate} vs {known}: {verdict}", dist[node]));
+            if improved {
+                dist[next] = candidate;
+                parent[next] = Some(edge);
+                frontier.push(Reverse((candidate + h(next), next)));
+                events.push(event(GraphEventKind::PushFrontier, next, Some(edge), candidate, LINE_PUSH));
+                annotate(events, format!("push node {next} with {priority} {}", candidate + h(next)));
+            }
+        }
+    }
+    parent
+}
+
+/// Kruskal: scan edges by increasing weight, keeping those that join two union-find components.
+/// Edges are sorted up front; only the union-find work is recorded.
+fn kruskal(graph: &Graph, events: &mut Vec<GraphEvent>) {
+    let n = graph.node_count();
+    let mut parent: Vec<usize> = (0..n).collect();
+    let mut rank = vec![0u32; n];
+    let mut order: Vec<usize> = (0..graph.edges.len()).collect();
+    order.sort_by_key(|&e| graph.edges[e].weight);
+
+    let mut accepted = 0;
+    for e in order {
+        if accepted + 1 >= n {
+            break;
+        }
+        let edge = graph.edges[e];
+        let (a, b) = (edge.a, edge.b);
+        events.push(event(GraphEventKind::ExamineEdge, b, Some(e), edge.weight, LINE_EXAMINE));
+        annotate(events, format!("take the lightest remaining edge {a}–{b}, weight {}", edge.weight));
+        let root_a = find(&mut parent, a, events);
+        let root_b = find(&mut parent, b, events);
+        if root_a == root_b {
+            events.push(event(GraphEventKind::RejectEdge, b, Some(e), edge.weight, LINE_REJECT));
+            annotate(events, format!("{a} and {b} share root {root_a}: edge {a}–{b} would close a cycle"));
+            continue;
+        }
+
+        // Union by rank: the shallower tree goes under the deeper one
+        let (root, child) = if rank[root_a] < rank[root_b] { (root_b, root_a) } else { (root_a, root_b) };
+        let rank_bump = rank[root] == rank[child];
+        parent[child] = root;
+        if rank_bump {
+            rank[root] += 1;
+        }
+        events.push(event(GraphEventKind::Union { root, rank_bump }, child, None, 0, LINE_UNION));
+        let bump = if rank_bump { format!(", whose rank grows to {}", rank[root]) } else { String::new() };
+        annotate(events, format!("union by rank: root {child} goes under root {root}{bump}"));
+        events.push(event(GraphEventKind::AcceptEdge, b, Some(e), edge.weight, LINE_ACCEPT));
+        accepted += 1;
+        annotate(events, format!("edge {a}–{b} joins the tree ({accepted} of {} edges)", n - 1));
+    }
+}
+
+/// Find with path compression, recording every parent read and every re-pointed node
+fn find(parent: &mut [usize], node: usize, events: &mut Vec<GraphEvent>) -> usize {
+    let mut root = node;
+    loop {
+        events.push(event(GraphEventKind::FindHop { parent: parent[root] }, root, None, 0, LINE_FIND_HOP));
+        if parent[root] == root {
+            annotate(events, format!("find({node}): parent[{root}] = {root}, so {root} is the root"));
+            break;
+        }
+        annotate(events, format!("find({node}): parent[{root}] = {}, keep climbing", parent[root]));
+        root = parent[root];
+    }
+
+    let mut current = node;
+    while parent[current] != root {
+        let next = parent[current];
+        parent[current] = root;
+        events.push(event(GraphEventKind::Compress { root }, current, None, 0, LINE_COMPRESS));
+        annotate(events, format!("path compression: point {current} straight at root {root}"));
+        current = next;
+    }
+    root
+}
+
+/// Prim: grow the tree from the start node, always taking the lightest edge leaving it
+fn prim(graph: &Graph, events: &mut Vec<GraphEvent>) {
+    let n = graph.node_count();
+    let mut key = vec![u32::MAX; n];
+    let mut via: Vec<Option<usize>> = vec![None; n];
+    let mut in_tree = vec![false; n];
+    let mut frontier = BinaryHeap::new();
+
+    key[graph.start] = 0;
+    frontier.push(Reverse((0, graph.start)));
+    events.push(event(GraphEventKind::PushFrontier, graph.start, None, 0, LINE_START));
+    annotate(events, format!("grow the tree from the start node {}", graph.start));
+
+    while let Some(Reverse((_, node))) = frontier.pop() {
+        let pop_line = if in_tree[node] { LINE_STALE } else { LINE_POP };
+        events.push(event(GraphEventKind::PopFrontier, node, None, key[node], pop_line));
+        if in_tree[node] {
+            annotate(events, format!("pop node {node}: already in the tree, skip it"));
+            continue;
+        }
+        annotate(events, format!("pop node {node}, the cheapest to connect"));
+        in_tree[node] = true;
+        events.push(event(GraphEventKind::Visit, node, via[node], key[node], LINE_VISIT));
+        match via[node] {
+            Some(edge) => {
+                let from = graph.edges[edge].other(node);
+                annotate(events, format!("node {node} joins the tree by edge {from}–{node}, weight {}", key[node]));
+            }
+            None => annotate(events, format!("node {node} starts the tree")),
+        }
+        for &edge in &graph.adjacency[node] {
+            let next = graph.edges[edge].other(node);
+            if in_tree[next] {
+                continue;
+            }
+            let weight = graph.edges[edge].weight;
+            let improved = weight < key[next];
+            events.push(event(GraphEventKind::RelaxEdge { improved }, next, Some(edge), weight, LINE_RELAX));
+            let best = if key[next] == u32::MAX { "∞".to_string() } else { key[next].to_string() };
+            let verdict = if improved { "cheaper, new candidate" } else { "not cheaper" };
+            annotate(events, format!("edge {node}–{next}: weight {weight} vs best {best} for node {next}: {verdict}"));
+            if improved {
+                key[next] = weight;
+                via[next] = Some(edge);
+                frontier.push(Reverse((weight, next)));
+                events.push(event(GraphEventKind::PushFrontier, next, Some(edge), weight, LINE_PUSH));
+                annotate(events, format!("push node {next} at cost {weight}"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::graph::{EdgeState, GraphKind, GraphRun};
+
+    /// 0 -4- 1 -5- 3 -7- 4, with 0 -1- 2 -2- 1 and 2 -6- 3.
+    /// Shortest 0→4 path 0-2-3-4 costs 14; the spanning tree {0-2, 2-1, 1-3, 3-4} weighs 15.
+    fn small_graph() -> Graph {
+        let mut graph = Graph::with_positions(vec![[0.0, 0.5], [0.3, 0.0], [0.3, 1.0], [0.7, 0.5], [1.0, 0.5]]);
+        for (a, b, weight) in [(0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 5), (2, 3, 6), (3, 4, 7)] {
+            graph.add_edge(a, b, weight);
+        }
+        graph
+    }
+
+    fn generated_graphs() -> Vec<Graph> {
+        let mut rng = SmallRng::seed_from_u64(7);
+        (0..6).flat_map(|_| GraphKind::ALL.map(|kind| Graph::generate(kind, &mut rng))).collect()
+    }
+
+    /// Weight of the start→goal path the events trace out
+    fn path_cost(graph: &Graph, events: &[GraphEvent]) -> u32 {
+        events
+            .iter()
+            .filter(|e| e.kind == GraphEventKind::PathStep)
+            .filter_map(|e| e.edge)
+            .map(|edge| graph.edges[edge].weight)
+            .sum()
+    }
+
+    /// Weight of the edges a spanning-tree run accepts, and how many there are
+    fn tree_weight(graph: &Graph, events: &[GraphEvent]) -> (u32, usize) {
+        let edges: Vec<usize> = events
+            .iter()
+            .filter(|e| matches!(e.kind, GraphEventKind::AcceptEdge | GraphEventKind::Visit))
+            .filter_map(|e| e.edge)
+            .collect();
+        (edges.iter().map(|&edge| graph.edges[edge].weight).sum(), edges.len())
+    }
+
+    #[test]
+    fn events_point_at_listing_lines() {
+        let graph = small_graph();
+        for algorithm in [Algorithm::Bfs, Algorithm::Dfs, Algorithm::Dijkstra, Algorithm::AStar, Algorithm::Kruskal, Algorithm::Prim] {
+            let listing = pseudocode(algorithm);
+            for e in traversal_events(algorithm, &graph) {
+                match e.kind {
+                    GraphEventKind::Done => assert_eq!(e.line, None),
+                    _ => assert!(e.line.is_some_and(|line| line < listing.len() && !listing[line].is_empty()), "{algorithm:?} {e:?}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bfs_visits_nodes_at_their_hop_count() {
+        let graph = small_graph();
+        let events = traversal_events(Algorithm::Bfs, &graph);
+        let hops: Vec<(usize, u32)> =
+            events.iter().filter(|e| e.kind == GraphEventKind::Visit).map(|e| (e.node, e.dist)).collect();
+        assert_eq!(hops, [(0, 0), (1, 1), (2, 1), (3, 2), (4, 3)]);
+
+        // On a grid the hop count from the top-left corner is the Manhattan distance
+        let mut rng = SmallRng::seed_from_u64(1);
+        let grid = Graph::grid(5, 4, &mut rng);
+        for e in traversal_events(Algorithm::Bfs, &grid).iter().filter(|e| e.kind == GraphEventKind::Visit) {
+            assert_eq!(e.dist as usize, e.node % 5 + e.node / 5);
+        }
+    }
+
+    #[test]
+    fn dijkstra_and_astar_find_equally_short_paths() {
+        let graph = small_graph();
+        assert_eq!(path_cost(&graph, &traversal_events(Algorithm::Dijkstra, &graph)), 14);
+        assert_eq!(path_cost(&graph, &traversal_events(Algorithm::AStar, &graph)), 14);
+
+        for graph in generated_graphs() {
+            let dijkstra = traversal_events(Algorithm::Dijkstra, &graph);
+            let astar = traversal_events(Algorithm::AStar, &graph);
+            assert_eq!(path_cost(&graph, &dijkstra), path_cost(&graph, &astar));
+        }
+    }
+
+    #[test]
+    fn kruskal_and_prim_build_equally_light_spanning_trees() {
+        let graph = small_graph();
+        assert_eq!(tree_weight(&graph, &traversal_events(Algorithm::Kruskal, &graph)), (15, 4));
+        assert_eq!(tree_weight(&graph, &traversal_events(Algorithm::Prim, &graph)), (15, 4));
+
+        for graph in generated_graphs() {
+            let kruskal = tree_weight(&graph, &traversal_events(Algorithm::Kruskal, &graph));
+            let prim = tree_weight(&graph, &traversal_events(Algorithm::Prim, &graph));
+            assert_eq!(kruskal, prim);
+            assert_eq!(kruskal.1, graph.node_count() - 1);
+        }
+    }
+
+    #[test]
+    fn playback_marks_only_spanning_tree_edges_and_weight() {
+        let graph = small_graph();
+        for algorithm in [Algorithm::Kruskal, Algorithm::Prim, Algorithm::Dfs] {
+            let mut run = GraphRun::new(graph.clone(), traversal_events(algorithm, &graph), algorithm.is_mst());
+            while run.advance().is_some() {}
+            let tree_edges = run.edges.iter().filter(|&&e| e == EdgeState::Tree).count();
+            if algorithm.is_mst() {
+                assert_eq!((run.tree_weight, tree_edges), (15, 4), "{algorithm:?}");
+            } else {
+                assert_eq!(run.tree_weight, 0);
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+pub mod table;
+
+use table::{empty_buckets, probe_slot, HashEvent, HashEventKind, HashOp, HashTable, Probing, Slot};
+
+/// Live hash table plus the playback of the events its operations recorded
+#[derive(Clone, Debug)]
+pub struct HashRun {
+    model: HashTable,              // Logical table after every queued operation
+    pub initial_keys: Vec<u32>,
+    events: Vec<HashEvent>,
+    cursor: usize,
+    pub buckets: Vec<Vec<Slot>>,   // Table as of the last played event
+    pub old_buckets: Option<Vec<Vec<Slot>>>, // Table being drained by a rehash
+    pub home: Option<usize>,       // Home bucket of the current key
+    pub probed: Option<(usize, usize)>,  // (bucket, chain position) just compared
+    pub written: Option<(usize, usize)>, // (bucket, chain position) just written or erased
+    pub found: Option<Option<(usize, usize)>>, // Result of the last lookup
+    pub key: Option<u32>,          // Key of the operation being played
+    pub resizes: usize,
+    op_probes: usize,              // Probes of the operation in flight
+    pub total_probes: usize,       // Probes over all finished operations
+    pub ops: usize,
+    pub max_probes: usize,         // Longest probe sequence of a single operation
+}
+
+impl Default for HashRun {
+    fn default() -> Self {
+        Self::new(Probing::Linear, Vec::new())
+    }
+}
+
+impl HashRun {
+    /// Build a table by inserting `keys` in order; the build itself is played back
+    pub fn new(probing: Probing, keys: Vec<u32>) -> Self {
+        let mut model = HashTable::new(probing);
+        let mut events = Vec::new();
+        for &key in &keys {
+            model.apply(HashOp::Insert(key), &mut events);
+        }
+        Self {
+            model,
+            initial_keys: keys,
+            events,
+            cursor: 0,
+            buckets: empty_buckets(probing, HashTable::INITIAL_CAPACITY),
+            old_buckets: None,
+            home: None,
+            probed: None,
+            written: None,
+            found: None,
+            key: None,
+            resizes: 0,
+            op_probes: 0,
+            total_probes: 0,
+            ops: 0,
+            max_probes: 0,
+        }
+    }
+
+    pub fn probing(&self) -> Probing {
+        self.model.probing
+    }
+
+    /// Queue an operation; its events play after the ones already queued
+    pub fn apply(&mut self, op: HashOp) {
+        self.model.apply(op, &mut self.events);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.events.len()
+    }
+
+    pub fn events(&self) -> &Vec<HashEvent> {
+        &self.events
+    }
+
+    /// Number of events played so far
+    pub fn played(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn len(&self) -> usize {
+        self.buckets.iter().flatten().filter(|slot| matches!(slot, Slot::Key(_))).count()
+    }
+
+    pub fn tombstones(&self) -> usize {
+        self.buckets.iter().flatten().filter(|&&slot| slot == Slot::Tombstone).count()
+    }
+
+    pub fn load_factor(&self) -> f32 {
+        self.len() as f32 / self.buckets.len().max(1) as f32
+    }
+
+    pub fn mean_probes(&self) -> f32 {
+        self.total_probes as f32 / self.ops.max(1) as f32
+    }
+
+    /// Probes a successful lookup of each stored key needs: (mean, max)
+    pub fn probe_lengths(&self) -> (f32, usize) {
+        let lengths: Vec<usize> = self
+            .buckets
+            .iter()
+            .enumerate()
+            .flat_map(|(bucket, chain)| {
+                chain.iter().enumerate().filter_map(move |(pos, &slot)| match slot {
+                    Slot::Key(key) => Some(self.probe_length(key, bucket, pos)),
+                    _ => None,
+                })
+            })
+            .collect();
+        let max = lengths.iter().copied().max().unwrap_or(0);
+        (lengths.iter().sum::<usize>() as f32 / lengths.len().max(1) as f32, max)
+    }
+
+    /// Probes needed to reach key stored at (bucket, pos)
+    pub fn probe_length(&self, key: u32, bucket: usize, pos: usize) -> usize {
+        let probing = self.probing();
+        if probing == Probing::Chaining {
+            return pos + 1;
+        }
+        let capacity = self.buckets.len();
+        (0..capacity).position(|i| probe_slot(probing, key, i, capacity) == bucket).unwrap_or(0) + 1
+    }
+
+    /// Bytes held by the bucket arrays (and chain nodes), including a table being rehashed
+    pub fn table_bytes(&self) -> usize {
+        let bytes = |buckets: &Vec<Vec<Slot>>| match self.probing() {
+            // Head pointer per bucket, key + next pointer per node
+            Probing::Chaining => {
+                let entries: usize = buckets.iter().map(Vec::len).sum();
+                buckets.len() * std::mem::size_of::<usize>() + entries * (4 + std::mem::size_of::<usize>())
+            }
+            // Key + occupancy tag per slot
+            _ => buckets.len() * 8,
+        };
+        bytes(&self.buckets) + self.old_buckets.as_ref().map_or(0, bytes)
+    }
+
+    /// Apply the next event, returning its kind so the engine can count its cost
+    pub fn advance(&mut self) -> Option<HashEventKind> {
+        let event = self.events.get(self.cursor)?.clone();
+        self.cursor += 1;
+        self.probed = None;
+        self.written = None;
+
+        let at = (event.bucket, event.pos);
+        match event.kind {
+            HashEventKind::Hash => {
+                self.home = Some(event.bucket);
+                self.key = Some(event.key);
+                // Rehash moves keys out of the old table one at a time
+                if let Some(old) = &mut self.old_buckets {
+                    for chain in old.iter_mut() {
+                        if let Some(pos) = chain.iter().position(|&slot| slot == Slot::Key(event.key)) {
+                            if self.model.probing == Probing::Chaining {
+                                chain.remove(pos);
+                            } else {
+                                chain[pos] = Slot::Empty;
+                            }
+                        }
+                    }
+                }
+            }
+            HashEventKind::Probe { .. } => {
+                self.probed = Some(at);
+                self.op_probes += 1;
+            }
+            HashEventKind::Write | HashEventKind::Displace => {
+                let chain = &mut self.buckets[event.bucket];
+                if event.pos == chain.len() {
+                    chain.push(Slot::Key(event.key));
+                } else {
+                    chain[event.pos] = Slot::Key(event.key);
+                }
+                self.written = Some(at);
+            }
+            HashEventKind::Erase { tombstone } => {
+                let chain = &mut self.buckets[event.bucket];
+                if self.model.probing == Probing::Chaining {
+                    chain.remove(event.pos);
+                } else {
+                    chain[event.pos] = if tombstone { Slot::Tombstone } else { Slot::Empty };
+                }
+                self.written = Some(at);
+            }
+            HashEventKind::Found => self.found = Some(Some(at)),
+            HashEventKind::NotFound => self.found = Some(None),
+            HashEventKind::Resize => {
+                let old = std::mem::replace(&mut self.buckets, empty_buckets(self.model.probing, event.bucket));
+                self.old_buckets = Some(old);
+                self.resizes += 1;
+            }
+            HashEventKind::FreeOld => self.old_buckets = None,
+            HashEventKind::Done => {
+                self.ops += 1;
+                self.total_probes += self.op_probes;
+                self.max_probes = self.max_probes.max(self.op_probes);
+                self.op_probes = 0;
+                self.home = None;
+            }
+        }
+        Some(event.kind)
+    }
+}
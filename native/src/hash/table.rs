@@ -0,0 +1,689 @@
+use std::mem;
+
+use crate::algorithms::{annotate, Algorithm};
+use crate::engine::Step;
+
+/// Collision strategy of the table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Probing {
+    Linear,
+    Quadratic,
+    Double,
+    RobinHood,
+    Chaining,
+}
+
+impl Probing {
+    /// Load factor above which an insert grows the table first
+    fn max_load(self) -> f32 {
+        match self {
+            Probing::Chaining => 1.0,
+            _ => 0.75,
+        }
+    }
+}
+
+/// Contents of one open-addressing slot or one chain entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Slot {
+    Empty,
+    Tombstone, // Deleted key; lookups probe past it, inserts may reuse it
+    Key(u32),
+}
+
+/// Interactive operation on the live table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashOp {
+    Insert(u32),
+    Lookup(u32),
+    Delete(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashEventKind {
+    Hash,                      // Key hashed to its home bucket
+    Probe { hit: bool },       // Slot (or chain entry) compared against key
+    Write,                     // Slot (or chain entry) takes key
+    Displace,                  // Robin Hood: key takes a richer resident's slot; the resident moves on
+    Erase { tombstone: bool }, // Slot emptied (or chain entry unlinked)
+    Found,
+    NotFound,
+    Resize,                    // New bucket array of `bucket` buckets allocated; old keys are rehashed next
+    FreeOld,                   // Old bucket array released once every key moved
+    Done,                      // Operation finished
+}
+
+#[derive(Clone, Debug)]
+pub struct HashEvent {
+    pub kind: HashEventKind,
+    pub bucket: usize,
+    pub pos: usize, // Position within the chain; always 0 for open addressing
+    pub key: u32,
+    pub note: Option<String>,
+    pub line: Option<usize>,
+}
+
+impl Step for HashEvent {
+    fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    fn note_mut(&mut self) -> &mut Option<String> {
+        &mut self.note
+    }
+
+    fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    fn columns(&self) -> String {
+        format!("{:?}\t{}\t{}\t{}", self.kind, self.bucket, self.pos, self.key)
+    }
+}
+
+fn event(kind: HashEventKind, bucket: usize, pos: usize, key: u32, line: usize) -> HashEvent {
+    HashEvent { kind, bucket, pos, key, note: None, line: Some(line) }
+}
+
+/// Where each kind of step sits in a strategy's listing
+struct Lines {
+    hash: usize,
+    probe: usize,      // Probe that neither finds key nor ends the search
+    hit: usize,        // Probe, or lookup, that finds key
+    miss: usize,       // Probe, or lookup, that proves key absent
+    exists: usize,     // Insert of a key already present
+    resize: usize,     // Growing or rehashing, including every key moved
+    walk: usize,       // Insert passing an occupied slot on the way to a free one
+    store: usize,
+    swap: usize,       // Robin Hood displacement
+    absent: usize,     // Delete's lookup result
+    erase: usize,
+    shift: usize,      // Robin Hood backward shift: the follower inspected
+    shift_move: usize, // Robin Hood backward shift: the follower moved
+}
+
+// Tombstone probing and chaining share one shape; only the wording of the probe lines differs
+const LINES: Lines = Lines {
+    hash: 1,
+    probe: 2,
+    hit: 3,
+    miss: 4,
+    exists: 6,
+    resize: 7,
+    walk: 8,
+    store: 8,
+    swap: 8,
+    absent: 10,
+    erase: 11,
+    shift: 11,
+    shift_move: 11,
+};
+
+const ROBIN_HOOD_LINES: Lines = Lines {
+    hash: 1,
+    probe: 2,
+    hit: 3,
+    miss: 4,
+    exists: 6,
+    resize: 7,
+    walk: 8,
+    store: 9,
+    swap: 10,
+    absent: 12,
+    erase: 13,
+    shift: 14,
+    shift_move: 15,
+};
+
+/// Listing the events' lines index into
+pub fn pseudocode(algorithm: Algorithm) -> &'static [&'static str] {
+    match algorithm {
+        Algorithm::LinearProbing => &[
+            "find(key):",
+            "    b = hash(key)",
+            "    for i in 0..capacity: slot = b + i; step over tombstones and other keys",
+            "        if slot holds key: return slot",
+            "        if slot is empty: return not found",
+            "insert(key):",
+            "    if find(key) succeeds: return",
+            "    if the table is too full: allocate a new one and rehash every key into it",
+            "    store key in the first tombstone or empty slot on its probe sequence",
+            "delete(key):",
+            "    if find(key) fails: return",
+            "    leave a tombstone in its slot",
+        ],
+        Algorithm::QuadraticProbing => &[
+            "find(key):",
+            "    b = hash(key)",
+            "    for i in 0..capacity: slot = b + i(i + 1)/2; step over tombstones and other keys",
+            "        if slot holds key: return slot",
+            "        if slot is empty: return not found",
+            "insert(key):",
+            "    if find(key) succeeds: return",
+            "    if the table is too full: allocate a new one and rehash every key into it",
+            "    store key in the first tombstone or empty slot on its probe sequence",
+            "delete(key):",
+            "    if find(key) fails: return",
+            "    leave a tombstone in its slot",
+        ],
+        Algorithm::DoubleHashing => &[
+            "find(key):",
+            "    b = hash(key); s = step(key), odd",
+            "    for i in 0..capacity: slot = b + i·s; step over tombstones and other keys",
+            "        if slot holds key: return slot",
+            "        if slot is empty: return not found",
+            "insert(key):",
+            "    if find(key) succeeds: return",
+            "    if the table is too full: allocate a new one and rehash every key into it",
+            "    store key in the first tombstone or empty slot on its probe sequence",
+            "delete(key):",
+            "    if find(key) fails: return",
+            "    leave a tombstone in its slot",
+        ],
+        Algorithm::RobinHoodHashing => &[
+            "find(key):",
+            "    b = hash(key)",
+            "    for d in 0..capacity: slot = b + d; step over keys at least d from home",
+            "        if slot holds key: return slot",
+            "        if slot is empty or its key is nearer home than d: return not found",
+            "insert(key):",
+            "    if find(key) succeeds: return",
+            "    if the table is too full: allocate a new one and rehash every key into it",
+            "    for d from where find stopped: slot = b + d",
+            "        if slot is empty: store key there; return",
+            "        if its resident is nearer home than d: swap key with it, carry the resident on",
+            "delete(key):",
+            "    if find(key) fails: return",
+            "    empty its slot",
+            "    while the next slot holds a key away from home:",
+            "        move that key back one slot and empty the slot it left",
+        ],
+        Algorithm::SeparateChaining => &[
+            "find(key):",
+            "    b = hash(key)",
+            "    for each entry in chain[b]: follow the link past other keys",
+            "        if the entry holds key: return it",
+            "    return not found",
+            "insert(key):",
+            "    if find(key) succeeds: return",
+            "    if the table is too full: allocate twice the buckets and rehash every key into them",
+            "    append key to chain[b]",
+            "delete(key):",
+            "    if find(key) fails: return",
+            "    unlink key's entry from chain[b]",
+        ],
+        _ => &[],
+    }
+}
+
+/// Multiplicative hash; capacities are powers of two
+pub fn home(key: u32, capacity: usize) -> usize {
+    (key.wrapping_mul(2_654_435_761) >> 16) as usize & (capacity - 1)
+}
+
+/// Odd step for double hashing, so it visits every slot of a power-of-two table
+fn step(key: u32, capacity: usize) -> usize {
+    (key as usize % (capacity / 2)) * 2 + 1
+}
+
+/// Slot visited on the i-th probe for key
+pub fn probe_slot(probing: Probing, key: u32, i: usize, capacity: usize) -> usize {
+    let h = home(key, capacity);
+    match probing {
+        // Triangular offsets cover every slot of a power-of-two table
+        Probing::Quadratic => (h + i * (i + 1) / 2) & (capacity - 1),
+        Probing::Double => (h + i * step(key, capacity)) & (capacity - 1),
+        _ => (h + i) & (capacity - 1),
+    }
+}
+
+/// Hash table recording every probe as an event.
+/// Open addressing keeps one slot per bucket; chaining keeps a list per bucket.
+#[derive(Clone, Debug)]
+pub struct HashTable {
+    pub probing: Probing,
+    buckets: Vec<Vec<Slot>>,
+    len: usize,
+    tombstones: usize,
+}
+
+impl HashTable {
+    pub const INITIAL_CAPACITY: usize = 8;
+
+    pub fn new(probing: Probing) -> Self {
+        Self {
+            probing,
+            buckets: empty_buckets(probing, Self::INITIAL_CAPACITY),
+            len: 0,
+            tombstones: 0,
+        }
+    }
+
+    pub fn apply(&mut self, op: HashOp, events: &mut Vec<HashEvent>) {
+        match op {
+            HashOp::Insert(key) => self.insert(key, events),
+            HashOp::Lookup(key) => match self.find(key, events) {
+                Ok((bucket, pos)) => {
+                    events.push(event(HashEventKind::Found, bucket, pos, key, self.lines().hit));
+                    annotate(events, format!("found {key} in bucket {bucket}"));
+                }
+                Err(_) => {
+                    events.push(event(HashEventKind::NotFound, home(key, self.capacity()), 0, key, self.lines().miss));
+                    annotate(events, format!("{key} is not in the table"));
+                }
+            },
+            HashOp::Delete(key) => self.delete(key, events),
+        }
+        events.push(HashEvent { line: None, ..event(HashEventKind::Done, 0, 0, 0, 0) });
+    }
+
+    fn capacity(&self) -> usize {
+        self.buckets.len()
+    }
+
+    fn lines(&self) -> &'static Lines {
+        if self.probing == Probing::RobinHood { &ROBIN_HOOD_LINES } else { &LINES }
+    }
+
+    fn probe(&self, key: u32, i: usize) -> usize {
+        probe_slot(self.probing, key, i, self.capacity())
+    }
+
+    /// Locate key, recording the hash and every probe.
+    /// When absent, returns the probe index an insert should start from.
+    fn find(&self, key: u32, events: &mut Vec<HashEvent>) -> Result<(usize, usize), usize> {
+        let lines = self.lines();
+        let h = home(key, self.capacity());
+        events.push(event(HashEventKind::Hash, h, 0, key, lines.hash));
+        annotate(events, format!("hash {key} to bucket {h}"));
+        if self.probing == Probing::Chaining {
+            for (pos, &slot) in self.buckets[h].iter().enumerate() {
+                let hit = slot == Slot::Key(key);
+                events.push(event(HashEventKind::Probe { hit }, h, pos, key, if hit { lines.hit } else { lines.probe }));
+                let verdict = if hit { "match" } else { "no match, follow the chain" };
+                annotate(events, format!("entry {pos} of bucket {h} holds {}: {verdict}", describe(slot)));
+                if hit {
+                    return Ok((h, pos));
+                }
+            }
+            return Err(0);
+        }
+
+        let mut first_tombstone = None;
+        for i in 0..self.capacity() {
+            let bucket = self.probe(key, i);
+            let slot = self.buckets[bucket][0];
+            let hit = slot == Slot::Key(key);
+            // Robin Hood invariant: once our distance exceeds the resident's, key cannot be further on
+            let overtaken = match slot {
+                Slot::Key(resident) => self.probing == Probing::RobinHood && self.distance(resident, bucket) < i,
+                _ => false,
+            };
+            let line = match slot {
+                _ if hit => lines.hit,
+                Slot::Empty => lines.miss,
+                _ if overtaken => lines.miss,
+                _ => lines.probe,
+            };
+            events.push(event(HashEventKind::Probe { hit }, bucket, 0, key, line));
+            match slot {
+                _ if hit => {
+                    annotate(events, format!("bucket {bucket} holds {key}"));
+                    return Ok((bucket, 0));
+                }
+                Slot::Empty => {
+                    annotate(events, format!("bucket {bucket} is empty: {key} is not further on"));
+                    return Err(first_tombstone.unwrap_or(i));
+                }
+                Slot::Tombstone => {
+                    annotate(events, format!("bucket {bucket} holds a tombstone: keep probing"));
+                    first_tombstone.get_or_insert(i);
+                }
+                Slot::Key(resident) if overtaken => {
+                    let distance = self.distance(resident, bucket);
+                    annotate(events, format!("bucket {bucket} holds {resident}, {distance} from home, closer than our {i}: {key} would have taken it"));
+                    return Err(i);
+                }
+                Slot::Key(resident) => annotate(events, format!("bucket {bucket} holds {resident}: keep probing")),
+            }
+        }
+        Err(first_tombstone.unwrap_or(0))
+    }
+
+    /// How far a key sits from its home bucket (linear probe count)
+    fn distance(&self, key: u32, bucket: usize) -> usize {
+        bucket.wrapping_sub(home(key, self.capacity())) & (self.capacity() - 1)
+    }
+
+    fn insert(&mut self, key: u32, events: &mut Vec<HashEvent>) {
+        let mut probed = true;
+        let mut start = match self.find(key, events) {
+            Ok((bucket, pos)) => {
+                // Keys are unique; report the existing entry instead
+                events.push(event(HashEventKind::Found, bucket, pos, key, self.lines().exists));
+                annotate(events, format!("{key} is already in bucket {bucket}"));
+                return;
+            }
+            Err(start) => start,
+        };
+        let occupied = self.len + self.tombstones + 1;
+        let max_load = self.probing.max_load() * self.capacity() as f32;
+        if occupied as f32 > max_load {
+            // Mostly tombstones: rehash into a table of the same size instead of growing
+            let grow = (self.len + 1) as f32 > max_load / 2.0;
+            let capacity = if grow { self.capacity() * 2 } else { self.capacity() };
+            self.resize(capacity, events);
+            // The probe sequence changed with the capacity
+            let h = home(key, self.capacity());
+            events.push(event(HashEventKind::Hash, h, 0, key, self.lines().resize));
+            annotate(events, format!("hash {key} again for the new table: bucket {h}"));
+            start = 0;
+            probed = false;
+        }
+        if self.place(key, start, probed, false, events) {
+            self.len += 1;
+        }
+    }
+
+    /// Store a key known to be absent, starting at probe index `start`.
+    /// While `rehashing`, every event belongs to the resize line of the listing.
+    /// Occupied slots passed on the way are recorded as probes, except the start
+    /// slot when `find` already probed it.
+    /// Returns false if the probe sequence found no free slot, in which case the key
+    /// being carried (for Robin Hood, possibly an evicted resident) was dropped.
+    /// The load limit always leaves a free slot, so this only guards `len`.
+    fn place(&mut self, mut key: u32, start: usize, mut probed: bool, rehashing: bool, events: &mut Vec<HashEvent>) -> bool {
+        let capacity = self.capacity();
+        let lines = self.lines();
+        let line = |line: usize| if rehashing { lines.resize } else { line };
+        if self.probing == Probing::Chaining {
+            let h = home(key, capacity);
+            self.buckets[h].push(Slot::Key(key));
+            events.push(event(HashEventKind::Write, h, self.buckets[h].len() - 1, key, line(lines.store)));
+            annotate(events, format!("append {key} to the chain of bucket {h}"));
+            return true;
+        }
+
+        let mut i = start;
+        while i <= capacity {
+            let bucket = self.probe(key, i);
+            match self.buckets[bucket][0] {
+                Slot::Empty => {
+                    self.buckets[bucket][0] = Slot::Key(key);
+                    events.push(event(HashEventKind::Write, bucket, 0, key, line(lines.store)));
+                    annotate(events, format!("store {key} in empty bucket {bucket}"));
+                    return true;
+                }
+                Slot::Tombstone => {
+                    self.buckets[bucket][0] = Slot::Key(key);
+                    self.tombstones -= 1;
+                    events.push(event(HashEventKind::Write, bucket, 0, key, line(lines.store)));
+                    annotate(events, format!("store {key} over the tombstone in bucket {bucket}"));
+                    return true;
+                }
+                // Robin Hood: take from the rich, then carry on placing the evicted key
+                Slot::Key(resident) if self.probing == Probing::RobinHood && self.distance(resident, bucket) < i => {
+                    if !probed {
+                        events.push(event(HashEventKind::Probe { hit: false }, bucket, 0, key, line(lines.swap)));
+                    }
+                    self.buckets[bucket][0] = Slot::Key(key);
+                    events.push(event(HashEventKind::Displace, bucket, 0, key, line(lines.swap)));
+                    let distance = self.distance(resident, bucket);
+                    annotate(events, format!("{key} is {i} from home, {resident} only {distance}: take its bucket and move {resident} on"));
+                    i = distance;
+                    key = resident;
+                }
+                Slot::Key(resident) => {
+                    if !probed {
+                        events.push(event(HashEventKind::Probe { hit: false }, bucket, 0, key, line(lines.walk)));
+                        annotate(events, format!("bucket {bucket} holds {resident}: probe on"));
+                    }
+                }
+            }
+            probed = false;
+            i += 1;
+        }
+        debug_assert!(false, "no free slot for {key} in a table of {capacity}");
+        false
+    }
+
+    fn delete(&mut self, key: u32, events: &mut Vec<HashEvent>) {
+        let lines = self.lines();
+        let Ok((bucket, pos)) = self.find(key, events) else {
+            events.push(event(HashEventKind::NotFound, home(key, self.capacity()), 0, key, lines.absent));
+            annotate(events, format!("{key} is not in the table, nothing to delete"));
+            return;
+        };
+        events.push(event(HashEventKind::Found, bucket, pos, key, lines.absent));
+        annotate(events, format!("found {key} in bucket {bucket}"));
+        self.len -= 1;
+        match self.probing {
+            Probing::Chaining => {
+                self.buckets[bucket].remove(pos);
+                events.push(event(HashEventKind::Erase { tombstone: false }, bucket, pos, key, lines.erase));
+                annotate(events, format!("unlink {key} from the chain of bucket {bucket}"));
+            }
+            Probing::RobinHood => {
+                // Backward-shift deletion: pull displaced followers one slot towards home
+                let mask = self.capacity() - 1;
+                let mut hole = bucket;
+                self.buckets[hole][0] = Slot::Empty;
+                events.push(event(HashEventKind::Erase { tombstone: false }, hole, 0, key, lines.erase));
+                annotate(events, format!("empty bucket {hole}; no tombstone needed"));
+                loop {
+                    let next = (hole + 1) & mask;
+                    let Slot::Key(follower) = self.buckets[next][0] else {
+                        break;
+                    };
+                    events.push(event(HashEventKind::Probe { hit: false }, next, 0, follower, lines.shift));
+                    let distance = self.distance(follower, next);
+                    if distance == 0 {
+                        annotate(events, format!("{follower} in bucket {next} is at home: stop shifting"));
+                        break;
+                    }
+                    annotate(events, format!("{follower} in bucket {next} is {distance} from home: shift it back"));
+                    self.buckets[hole][0] = Slot::Key(follower);
+                    events.push(event(HashEventKind::Write, hole, 0, follower, lines.shift_move));
+                    annotate(events, format!("move {follower} into bucket {hole}"));
+                    self.buckets[next][0] = Slot::Empty;
+                    events.push(event(HashEventKind::Erase { tombstone: false }, next, 0, follower, lines.shift_move));
+                    annotate(events, format!("empty bucket {next}"));
+                    hole = next;
+                }
+            }
+            _ => {
+                self.buckets[bucket][0] = Slot::Tombstone;
+                self.tombstones += 1;
+                events.push(event(HashEventKind::Erase { tombstone: true }, bucket, 0, key, lines.erase));
+                annotate(events, format!("leave a tombstone in bucket {bucket} so keys probed past it stay reachable"));
+            }
+        }
+    }
+
+    /// Allocate a new bucket array and re-insert every key; tombstones are dropped
+    fn resize(&mut self, capacity: usize, events: &mut Vec<HashEvent>) {
+        let reason = if capacity > self.capacity() { "load factor too high" } else { "mostly tombstones" };
+        let old = mem::replace(&mut self.buckets, empty_buckets(self.probing, capacity));
+        self.tombstones = 0;
+        let line = self.lines().resize;
+        events.push(event(HashEventKind::Resize, capacity, 0, 0, line));
+        annotate(events, format!("{reason}: allocate {capacity} buckets and rehash every key"));
+        for slot in old.into_iter().flatten() {
+            if let Slot::Key(key) = slot {
+                let h = home(key, capacity);
+                events.push(event(HashEventKind::Hash, h, 0, key, line));
+                annotate(events, format!("rehash {key}: bucket {h} in the new table"));
+                if !self.place(key, 0, false, true, events) {
+                    self.len -= 1;
+                }
+            }
+        }
+        events.push(event(HashEventKind::FreeOld, 0, 0, 0, line));
+        annotate(events, "free the old bucket array".to_string());
+    }
+}
+
+fn describe(slot: Slot) -> String {
+    match slot {
+        Slot::Empty => "nothing".to_string(),
+        Slot::Tombstone => "a tombstone".to_string(),
+        Slot::Key(key) => key.to_string(),
+    }
+}
+
+pub fn empty_buckets(probing: Probing, capacity: usize) -> Vec<Vec<Slot>> {
+    match probing {
+        Probing::Chaining => vec![Vec::new(); capacity],
+        _ => vec![vec![Slot::Empty]; capacity],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    fn stored_keys(table: &HashTable) -> BTreeSet<u32> {
+        let keys = table.buckets.iter().flatten().filter_map(|slot| match slot {
+            Slot::Key(key) => Some(*key),
+            _ => None,
+        });
+        keys.collect()
+    }
+
+    /// Robin Hood keeps runs sorted by distance: a key is at most one step further from home than its predecessor
+    fn check_robin_hood(table: &HashTable) {
+        let capacity = table.capacity();
+        for bucket in 0..capacity {
+            let next = (bucket + 1) & (capacity - 1);
+            if let (Slot::Key(a), Slot::Key(b)) = (table.buckets[bucket][0], table.buckets[next][0]) {
+                assert!(table.distance(b, next) <= table.distance(a, bucket) + 1, "{b} at {next} is too far behind {a}");
+            } else if let Slot::Key(b) = table.buckets[next][0] {
+                assert_eq!(table.distance(b, next), 0, "{b} at {next} follows a free slot but is not home");
+            }
+        }
+    }
+
+    #[test]
+    fn random_operations_match_a_set() {
+        let probings = [Probing::Linear, Probing::Quadratic, Probing::Double, Probing::RobinHood, Probing::Chaining];
+        let mut rng = SmallRng::seed_from_u64(36);
+        for probing in probings {
+            let mut table = HashTable::new(probing);
+            let mut model = BTreeSet::new();
+            let mut backward_shifts = 0;
+            for _ in 0..4000 {
+                let key = rng.gen_range(0..48);
+                let mut events = Vec::new();
+                match rng.gen_range(0..3) {
+                    0 => {
+                        table.apply(HashOp::Insert(key), &mut events);
+                        model.insert(key);
+                    }
+                    1 => {
+                        table.apply(HashOp::Delete(key), &mut events);
+                        model.remove(&key);
+                        backward_shifts += usize::from(events.iter().any(|e| e.kind == HashEventKind::Write));
+                    }
+                    _ => {
+                        table.apply(HashOp::Lookup(key), &mut events);
+                        let found = events.iter().any(|e| e.kind == HashEventKind::Found);
+                        assert_eq!(found, model.contains(&key), "{probing:?} lookup of {key}");
+                    }
+                }
+
+                assert_eq!(stored_keys(&table), model, "{probing:?}");
+                assert_eq!(table.len, model.len());
+                let tombstones = table.buckets.iter().flatten().filter(|&&slot| slot == Slot::Tombstone).count();
+                assert_eq!(table.tombstones, tombstones);
+                for &key in &model {
+                    assert!(table.find(key, &mut Vec::new()).is_ok(), "{probing:?} lost {key}");
+                }
+                if probing == Probing::RobinHood {
+                    check_robin_hood(&table);
+                }
+            }
+            if probing == Probing::RobinHood {
+                assert!(backward_shifts > 0, "no backward shift");
+            }
+        }
+    }
+
+    #[test]
+    fn events_point_at_their_strategys_listing() {
+        let strategies = [
+            (Probing::Linear, Algorithm::LinearProbing),
+            (Probing::Quadratic, Algorithm::QuadraticProbing),
+            (Probing::Double, Algorithm::DoubleHashing),
+            (Probing::RobinHood, Algorithm::RobinHoodHashing),
+            (Probing::Chaining, Algorithm::SeparateChaining),
+        ];
+        let mut rng = SmallRng::seed_from_u64(36);
+        for (probing, algorithm) in strategies {
+            let listing = pseudocode(algorithm);
+            let mut table = HashTable::new(probing);
+            let mut events = Vec::new();
+            for _ in 0..400 {
+                let key = rng.gen_range(0..48);
+                let op = [HashOp::Insert(key), HashOp::Delete(key), HashOp::Lookup(key)][rng.gen_range(0..3)];
+                table.apply(op, &mut events);
+            }
+            // The line each kind of event executes must spell out what the event does
+            let lines_of = |kinds: &[HashEventKind]| -> BTreeSet<&str> {
+                events.iter().filter(|e| kinds.contains(&e.kind)).map(|e| listing[e.line.unwrap()]).collect()
+            };
+            for e in &events {
+                match e.kind {
+                    HashEventKind::Done => assert_eq!(e.line, None),
+                    _ => assert!(e.line.is_some_and(|line| line < listing.len()), "{probing:?} {e:?}"),
+                }
+            }
+            assert!(lines_of(&[HashEventKind::Hash]).iter().any(|line| line.contains("b = hash(key)")));
+            assert!(lines_of(&[HashEventKind::Resize, HashEventKind::FreeOld]).iter().all(|line| line.contains("rehash")));
+            let erased = lines_of(&[HashEventKind::Erase { tombstone: false }, HashEventKind::Erase { tombstone: true }]);
+            match probing {
+                Probing::Chaining => {
+                    assert_eq!(lines_of(&[HashEventKind::Probe { hit: false }]), BTreeSet::from([listing[2]]));
+                    assert!(listing[2].contains("chain[b]") && listing[8].contains("append"));
+                    assert_eq!(erased, BTreeSet::from(["    unlink key's entry from chain[b]"]));
+                }
+                Probing::RobinHood => {
+                    assert!(lines_of(&[HashEventKind::Displace]).iter().all(|line| line.contains("swap key")));
+                    assert!(lines_of(&[HashEventKind::Write]).iter().any(|line| line.contains("move that key back")));
+                    assert!(erased.iter().all(|line| !line.contains("tombstone")));
+                    assert!(erased.iter().any(|line| line.contains("move that key back")));
+                }
+                _ => {
+                    assert!(lines_of(&[HashEventKind::Displace]).is_empty());
+                    assert_eq!(erased, BTreeSet::from(["    leave a tombstone in its slot"]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tombstones_are_rehashed_away_in_place() {
+        for probing in [Probing::Linear, Probing::Quadratic, Probing::Double] {
+            let mut table = HashTable::new(probing);
+            let mut events = Vec::new();
+            table.apply(HashOp::Insert(7), &mut events);
+            // Churn fresh keys until their tombstones force a rehash; one live key does not need a bigger table
+            let rehashed = (100..200).any(|key| {
+                events.clear();
+                table.apply(HashOp::Insert(key), &mut events);
+                table.apply(HashOp::Delete(key), &mut events);
+                events.iter().any(|e| e.kind == HashEventKind::Resize)
+            });
+            assert!(rehashed, "{probing:?} never rehashed");
+            assert_eq!(table.capacity(), HashTable::INITIAL_CAPACITY);
+            // The delete after the rehash left one tombstone
+            assert_eq!(table.tombstones, 1);
+            assert!(table.find(7, &mut Vec::new()).is_ok());
+            assert_eq!(stored_keys(&table), BTreeSet::from([7]));
+        }
+    }
+}
@@ -0,0 +1,33 @@
+struct Globals {
+    view_proj: mat4x4<f32>,
+    bar_width: f32,
+    max_value: f32,
+    _padding: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> globals: Globals;
+
+struct VertexIn {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+struct VertexOut {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(input: VertexIn) -> VertexOut {
+    var out: VertexOut;
+    out.position = globals.view_proj * vec4<f32>(input.position, 1.0);
+    out.color = input.color;
+    return out;
+}
+
+@fragment
+fn fs_main(input: VertexOut) -> @location(0) vec4<f32> {
+    // Premultiplied alpha to match the bar blend state
+    return vec4<f32>(input.color.rgb * input.color.a, input.color.a);
+}
@@ -1,23 +1,257 @@
 mod renderer;
 mod engine;
 mod algorithms;
+mod element;
+mod graph;
+mod dp;
+mod text;
+mod tree;
+mod hash;
+mod clip;
+mod svg;
+mod theme;
+mod audio;
+mod camera;
+mod post;
+#[cfg(unix)]
+mod tui;
 
-use anyhow::Result;
-use engine::{Engine, SortMode};
-use renderer::Renderer;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use audio::{AudioOutput, SoundSettings, Synth};
+use camera::CameraPreset;
+use clip::{ClipFormat, ClipWriter};
+use engine::Engine;
+use renderer::{Renderer, UiRequests};
+use theme::Theme;
 use winit::{
     dpi::PhysicalSize,
     event::{ElementState, Event, WindowEvent},
     event_loop::EventLoop,
     keyboard::{KeyCode, PhysicalKey},
-    window::WindowBuilder,
+    window::{Window, WindowBuilder},
 };
 
+/// What to run, chosen on the command line; no arguments opens the window
+enum Frontend {
+    Window,
+    Terminal, // `--tui`: text-mode front-end for SSH and headless machines
+    Export(Export),
+}
+
+/// Headless export requested on the command line:
+/// `--frames <dir> | --clip <file.gif|file.png> [--size WxH] [--fps n] [--actions-per-frame r] [--fallback]`,
+/// `--svg <dir> [--action n]`, which needs no GPU, or `--wav <file>` for the run's sound
+struct Export {
+    target: ExportTarget,
+    size: PhysicalSize<u32>,
+    fps: u32,
+    actions_per_frame: Option<f32>, // None plays in real time, one action per step delay
+    fallback: bool,                 // Force wgpu's software adapter
+}
+
+enum ExportTarget {
+    Frames(PathBuf), // Numbered PNGs in a directory
+    Clip(PathBuf, ClipFormat),
+    Svg(PathBuf, Option<usize>), // One snapshot at an action index, or every step plus a flipbook
+    Wav(PathBuf),                // Sonification, timed like a clip with the same --fps and --actions-per-frame
+}
+
+const USAGE: &str = "expected nothing, --theme <name|file.toml>, --tui, or --frames <dir> or --clip <file.gif|file.png>, \
+    optionally with --size WxH, --fps <n>, --actions-per-frame <r> and --fallback; \
+    or --svg <dir>, optionally with --action <n>; or --wav <file>";
+
 fn main() -> Result<()> {
-    pollster::block_on(run())
+    let (frontend, theme) = parse_args()?;
+    match frontend {
+        Frontend::Window => pollster::block_on(run(theme)),
+        Frontend::Terminal => run_terminal(&theme),
+        Frontend::Export(export) => pollster::block_on(export_run(export, theme)),
+    }
+}
+
+#[cfg(unix)]
+fn run_terminal(theme: &Theme) -> Result<()> {
+    tui::run(Engine::new(12), theme)
+}
+
+#[cfg(not(unix))]
+fn run_terminal(_theme: &Theme) -> Result<()> {
+    bail!("the terminal front-end is only available on Unix-like systems")
+}
+
+/// The front-end to run and the color theme every front-end uses (`--theme`, default "Default")
+fn parse_args() -> Result<(Frontend, Theme)> {
+    let mut theme = Theme::default();
+    let mut terminal = false;
+    let mut target = None;
+    let mut size = None;
+    let mut fps = 30;
+    let mut actions_per_frame = None;
+    let mut fallback = false;
+    let mut svg_action = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().with_context(|| format!("{arg} needs a value; {USAGE}"));
+        match arg.as_str() {
+            "--frames" => target = Some(ExportTarget::Frames(PathBuf::from(value()?))),
+            "--clip" => {
+                let path = PathBuf::from(value()?);
+                target = Some(ExportTarget::Clip(path.clone(), ClipFormat::from_path(&path)?));
+            }
+            "--size" => {
+                let value = value()?;
+                let parsed = value
+                    .split_once('x')
+                    .and_then(|(w, h)| Some(PhysicalSize::new(w.parse().ok()?, h.parse().ok()?)))
+                    .filter(|s: &PhysicalSize<u32>| s.width > 0 && s.height > 0);
+                size = Some(parsed.with_context(|| format!("invalid --size {value}, expected e.g. 640x360"))?);
+            }
+            "--fps" => {
+                let value = value()?;
+                fps = value.parse().with_context(|| format!("invalid --fps {value}"))?;
+                if fps == 0 {
+                    bail!("--fps must be at least 1");
+                }
+            }
+            "--actions-per-frame" => {
+                let value = value()?;
+                let ratio: f32 = value.parse().with_context(|| format!("invalid --actions-per-frame {value}"))?;
+                if !(ratio > 0.0 && ratio.is_finite()) {
+                    bail!("--actions-per-frame must be positive");
+                }
+                actions_per_frame = Some(ratio);
+            }
+            "--fallback" => fallback = true,
+            "--tui" => terminal = true,
+            "--theme" => theme = Theme::resolve(&value()?)?,
+            "--wav" => target = Some(ExportTarget::Wav(PathBuf::from(value()?))),
+            "--svg" => target = Some(ExportTarget::Svg(PathBuf::from(value()?), None)),
+            "--action" => {
+                let value = value()?;
+                svg_action = Some(value.parse().with_context(|| format!("invalid --action {value}"))?);
+            }
+            other => bail!("unknown argument {other}; {USAGE}"),
+        }
+    }
+    let target = match (target, svg_action) {
+        (Some(ExportTarget::Svg(dir, _)), action) => Some(ExportTarget::Svg(dir, action)),
+        (_, Some(_)) => bail!("--action only applies to --svg"),
+        (target, None) => target,
+    };
+    let Some(target) = target else {
+        if size.is_some() || actions_per_frame.is_some() || fallback {
+            bail!("export options given without an output; {USAGE}");
+        }
+        return Ok((if terminal { Frontend::Terminal } else { Frontend::Window }, theme));
+    };
+    if terminal {
+        bail!("--tui cannot be combined with an export; {USAGE}");
+    }
+    // Clips default to a smaller frame so GIFs stay small enough for docs and slides
+    let size = size.unwrap_or(match target {
+        ExportTarget::Frames(_) => PhysicalSize::new(1280, 720),
+        ExportTarget::Clip(..) | ExportTarget::Svg(..) | ExportTarget::Wav(_) => PhysicalSize::new(640, 360),
+    });
+    Ok((Frontend::Export(Export { target, size, fps, actions_per_frame, fallback }), theme))
+}
+
+/// Move the engine on by one frame's worth of playback
+fn advance(engine: &mut Engine, export: &Export) {
+    let delay = engine.step_delay();
+    let mut remaining = match export.actions_per_frame {
+        Some(ratio) => delay.mul_f32(ratio),
+        None => Duration::from_secs_f64(1.0 / export.fps as f64),
+    };
+    // Engine::step plays at most one action per call, so feed it one step delay at a time
+    while !remaining.is_zero() {
+        let part = remaining.min(delay);
+        engine.step(part);
+        remaining -= part;
+    }
 }
 
-async fn run() -> Result<()> {
+/// Call `frame` after every frame of the run, holding the finished state for one more second.
+/// Returns the number of frames.
+fn play_frames(engine: &mut Engine, export: &Export, mut frame: impl FnMut(&Engine, u32) -> Result<()>) -> Result<u32> {
+    let mut count = 0;
+    let mut hold = export.fps;
+    while hold > 0 {
+        advance(engine, export);
+        frame(engine, count)?;
+        count += 1;
+        if engine.is_finished() {
+            hold -= 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Play the default run offscreen and write it out as PNG frames, an animated clip, SVG snapshots or a WAV
+async fn export_run(export: Export, theme: Theme) -> Result<()> {
+    let mut engine = Engine::new(12);
+    let dt = Duration::from_secs_f64(1.0 / export.fps as f64);
+
+    match &export.target {
+        // SVG snapshots are drawn on the CPU, so they never touch wgpu
+        ExportTarget::Svg(dir, Some(action)) => svg::export_action(&mut engine, &theme, dir, *action)?,
+        ExportTarget::Svg(dir, None) => svg::export_flipbook(&mut engine, &theme, dir)?,
+        ExportTarget::Wav(path) => {
+            let mut synth = Synth::new(SoundSettings { muted: false, ..Default::default() });
+            let mut samples = Vec::new();
+            play_frames(&mut engine, &export, |engine, index| {
+                synth.follow(engine);
+                // Cumulative sample counts keep the audio in step with the frames at any fps
+                let start = samples.len();
+                samples.resize((index as usize + 1) * audio::SAMPLE_RATE as usize / export.fps as usize, 0.0);
+                synth.render(&mut samples[start..]);
+                Ok(())
+            })?;
+            audio::write_wav(path, &samples)?;
+            eprintln!("Wrote {:.1} s of audio to {}", samples.len() as f32 / audio::SAMPLE_RATE as f32, path.display());
+        }
+        ExportTarget::Frames(dir) => {
+            std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+            let mut renderer = Renderer::new_offscreen(export.size, export.fallback).await?;
+            renderer.set_theme(theme);
+            let count = play_frames(&mut engine, &export, |engine, index| {
+                draw(&mut renderer, engine, dt, None)?;
+                let path = dir.join(format!("frame_{index:05}.png"));
+                renderer.read_frame()?.save(&path).with_context(|| format!("cannot write {}", path.display()))
+            })?;
+            eprintln!("Wrote {count} frames to {}", dir.display());
+        }
+        ExportTarget::Clip(path, format) => {
+            let mut renderer = Renderer::new_offscreen(export.size, export.fallback).await?;
+            renderer.set_theme(theme);
+            // APNG needs the frame count before the first frame: dry-run the playback, then replay it
+            let frame_count = if *format == ClipFormat::Apng {
+                let count = play_frames(&mut engine, &export, |_, _| Ok(()))?;
+                engine.replay();
+                count
+            } else {
+                0
+            };
+            let mut clip = ClipWriter::create(path, *format, export.size.width, export.size.height, export.fps, frame_count)?;
+            let count = play_frames(&mut engine, &export, |engine, _| {
+                draw(&mut renderer, engine, dt, None)?;
+                clip.add_frame(&renderer.read_frame()?)
+            })?;
+            clip.finish()?;
+            eprintln!("Wrote {count} frames to {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Render the engine's current state; `window` is None for offscreen frames
+fn draw(renderer: &mut Renderer, engine: &Engine, dt: Duration, window: Option<&Window>) -> Result<UiRequests> {
+    renderer.render(engine, dt, window)
+}
+
+async fn run(theme: Theme) -> Result<()> {
     let event_loop = EventLoop::new()?;
     let window = WindowBuilder::new()
         .with_title("Algorithm Native - wgpu")
@@ -28,8 +262,16 @@ async fn run() -> Result<()> {
     let window: &'static _ = Box::leak(Box::new(window));
 
     let mut renderer = Renderer::new(window).await?;
+    renderer.set_theme(theme);
     // Fewer bars for a focused scene
     let mut engine = Engine::new(12);
+    // The app runs silently when no audio player is available
+    let audio = AudioOutput::start(SoundSettings::default())
+        .map_err(|err| eprintln!("Sound disabled: {err}"))
+        .ok();
+    if audio.is_some() {
+        renderer.enable_sound(SoundSettings::default());
+    }
 
     let mut paused = false;
     let window_ref = window;
@@ -45,13 +287,20 @@ async fn run() -> Result<()> {
                 WindowEvent::Resized(size) => {
                     renderer.resize(size);
                 }
-                WindowEvent::KeyboardInput { event, .. } => {
-                    if event.state == ElementState::Released {
-                        match event.physical_key {
-                            PhysicalKey::Code(KeyCode::Space) => paused = !paused,
-                            PhysicalKey::Code(KeyCode::KeyR) => engine.reset(),
-                            _ => {}
+                WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Released && !renderer.wants_keyboard() => {
+                    match event.physical_key {
+                        PhysicalKey::Code(KeyCode::Space) => paused = !paused,
+                        PhysicalKey::Code(KeyCode::KeyR) => {
+                            engine.reset();
+                            renderer.unpin();
                         }
+                        // Play exactly one action, handy while paused
+                        PhysicalKey::Code(KeyCode::KeyN | KeyCode::ArrowRight) => engine.step(engine.step_delay()),
+                        PhysicalKey::Code(KeyCode::Digit1) => renderer.set_camera_preset(CameraPreset::Front),
+                        PhysicalKey::Code(KeyCode::Digit2) => renderer.set_camera_preset(CameraPreset::TopDown),
+                        PhysicalKey::Code(KeyCode::Digit3) => renderer.set_camera_preset(CameraPreset::Isometric),
+                        PhysicalKey::Code(KeyCode::Digit4) => renderer.set_camera_preset(CameraPreset::FollowActive),
+                        _ => {}
                     }
                 }
                 WindowEvent::RedrawRequested => {
@@ -62,24 +311,49 @@ async fn run() -> Result<()> {
                     if !paused {
                         engine.step(dt);
                     }
-                    let (bars, max_val) = engine.bars();
-                    let mode = engine.mode;
-                    let merge_level = engine.merge_level;
-                    match renderer.render(
-                        bars, max_val, 
-                        engine.comparisons, engine.operations, engine.memory_ops,
-                        engine.time_elapsed, engine.current_memory, engine.peak_memory, 
-                        engine.current_animation.clone(), 
-                        &engine.temp_array, &engine.multi_temp_arrays, 
-                        mode, merge_level,
-                        engine.est_time_ns, engine.est_comparison_ns, engine.est_memory_ns, 
-                        engine.memory_allocs, engine.memory_deallocs,
-                        dt, window
-                    ) {
-                        Ok(Some(new_mode)) => {
-                            engine.set_mode(new_mode);
+                    if let Some(audio) = &audio {
+                        audio.follow(&engine);
+                    }
+                    match draw(&mut renderer, &engine, dt, Some(window)) {
+                        Ok(requests) => {
+                            if let Some(new_mode) = requests.mode {
+                                engine.set_mode(new_mode);
+                            }
+                            if let Some(algorithm) = requests.algorithm {
+                                engine.set_algorithm(algorithm);
+                            }
+                            if let Some(k) = requests.select_k {
+                                engine.set_select_k(k);
+                            }
+                            if let Some(op) = requests.heap_op {
+                                engine.apply_heap_op(op);
+                            }
+                            if let Some(op) = requests.manual_op {
+                                engine.apply_manual_op(op);
+                            }
+                            if let Some(kind) = requests.element_kind {
+                                engine.set_element_kind(kind);
+                            }
+                            if let Some(op) = requests.tree_op {
+                                engine.apply_tree_op(op);
+                            }
+                            if let Some(op) = requests.hash_op {
+                                engine.apply_hash_op(op);
+                            }
+                            if requests.export_trace {
+                                let path = std::path::Path::new("trace.tsv");
+                                match engine.export_trace(path) {
+                                    Ok(()) => eprintln!("Trace written to {}", path.display()),
+                                    Err(err) => eprintln!("Trace export failed: {err}"),
+                                }
+                            }
+                            if let Some(kind) = requests.graph_kind {
+                                engine.set_graph_kind(kind);
+                            }
+                            if let (Some(settings), Some(audio)) = (requests.sound, &audio) {
+                                audio.set_settings(settings);
+                            }
                         }
-                        Ok(None) => {}
                         Err(err) => {
                             eprintln!("Render error: {err:?}");
                             target.exit();
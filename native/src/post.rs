@@ -0,0 +1,80 @@
+/// Tone mapping operator applied by the final post pass
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tonemap {
+    Aces,
+    Reinhard,
+    None, // Plain clamp to [0, 1]
+}
+
+impl Tonemap {
+    pub const ALL: [Tonemap; 3] = [Tonemap::Aces, Tonemap::Reinhard, Tonemap::None];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Tonemap::Aces => "ACES",
+            Tonemap::Reinhard => "Reinhard",
+            Tonemap::None => "None",
+        }
+    }
+}
+
+/// Post-processing controls edited in the egui panel; the defaults reproduce the original look
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PostSettings {
+    pub enabled: bool, // Off skips the blur passes, bloom, depth of field and vignette
+    pub exposure: f32,
+    pub bloom_intensity: f32,
+    pub bloom_threshold: f32, // Blurred brightness below this adds no bloom
+    pub blur_radius: f32,     // Texel spacing of the blur taps
+    pub focus_distance: f32,  // View-space depth that stays sharp
+    pub focus_range: f32,     // Depth distance over which blur reaches full strength
+    pub tonemap: Tonemap,
+}
+
+impl Default for PostSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            exposure: 1.0,
+            bloom_intensity: 0.28,
+            bloom_threshold: 0.0,
+            blur_radius: 1.0,
+            focus_distance: 2.3,
+            focus_range: 2.5,
+            tonemap: Tonemap::Aces,
+        }
+    }
+}
+
+/// Uniform block read by the blur and tonemap passes in `post.wgsl`
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PostParams {
+    exposure: f32,
+    bloom_intensity: f32,
+    bloom_threshold: f32,
+    blur_radius: f32,
+    focus_distance: f32,
+    focus_range: f32,
+    tonemap: u32, // 0 ACES, 1 Reinhard, 2 none
+    enabled: u32,
+}
+
+impl From<&PostSettings> for PostParams {
+    fn from(settings: &PostSettings) -> Self {
+        Self {
+            exposure: settings.exposure,
+            bloom_intensity: settings.bloom_intensity,
+            bloom_threshold: settings.bloom_threshold,
+            blur_radius: settings.blur_radius,
+            focus_distance: settings.focus_distance,
+            focus_range: settings.focus_range.max(0.01),
+            tonemap: match settings.tonemap {
+                Tonemap::Aces => 0,
+                Tonemap::Reinhard => 1,
+                Tonemap::None => 2,
+            },
+            enabled: settings.enabled as u32,
+        }
+    }
+}
@@ -11,13 +11,27 @@ fn vs_fullscreen(@location(0) position: vec2<f32>, @location(1) uv: vec2<f32>) -
     return o;
 }
 
+// Runtime settings from the post-processing panel (PostParams in post.rs)
+struct PostParams {
+    exposure: f32,
+    bloom_intensity: f32,
+    bloom_threshold: f32,
+    blur_radius: f32,
+    focus_distance: f32,
+    focus_range: f32,
+    tonemap: u32, // 0 ACES, 1 Reinhard, 2 none
+    enabled: u32,
+};
+
+@group(1) @binding(0) var<uniform> post: PostParams;
+
 @group(0) @binding(0) var src_tex: texture_2d<f32>;
 @group(0) @binding(1) var src_samp: sampler;
 
 // Horizontal blur (manually unrolled for WebGPU const indexing)
 @fragment
 fn fs_blur_h(in: VSOut) -> @location(0) vec4<f32> {
-    let texel = 1.0 / vec2<f32>(textureDimensions(src_tex));
+    let texel = post.blur_radius / vec2<f32>(textureDimensions(src_tex));
     let w0 = 0.204164;
     let w1 = 0.304005;
     let w2 = 0.193783;
@@ -40,7 +54,7 @@ fn fs_blur_h(in: VSOut) -> @location(0) vec4<f32> {
 // Vertical blur (manually unrolled)
 @fragment
 fn fs_blur_v(in: VSOut) -> @location(0) vec4<f32> {
-    let texel = 1.0 / vec2<f32>(textureDimensions(src_tex));
+    let texel = post.blur_radius / vec2<f32>(textureDimensions(src_tex));
     let w0 = 0.204164;
     let w1 = 0.304005;
     let w2 = 0.193783;
@@ -62,7 +76,7 @@ fn fs_blur_v(in: VSOut) -> @location(0) vec4<f32> {
 
 @group(0) @binding(0) var scene_tex: texture_2d<f32>;
 @group(0) @binding(1) var bloom_tex: texture_2d<f32>;
-@group(0) @binding(2) var depth_tex: texture_depth_2d;
+@group(0) @binding(2) var depth_tex: texture_2d<f32>;
 @group(0) @binding(3) var post_samp: sampler;
 
 fn aces_tonemap(x: vec3<f32>) -> vec3<f32> {
@@ -74,6 +88,18 @@ fn aces_tonemap(x: vec3<f32>) -> vec3<f32> {
     return clamp((x * (a * x + b)) / (x * (c * x + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
 }
 
+fn reinhard_tonemap(x: vec3<f32>) -> vec3<f32> {
+    return x / (vec3<f32>(1.0) + x);
+}
+
+fn tonemap(x: vec3<f32>) -> vec3<f32> {
+    switch post.tonemap {
+        case 0u: { return aces_tonemap(x); }
+        case 1u: { return reinhard_tonemap(x); }
+        default: { return clamp(x, vec3<f32>(0.0), vec3<f32>(1.0)); }
+    }
+}
+
 fn linearize_depth(depth: f32) -> f32 {
     // Matches near/far used in renderer
     let near = 0.1;
@@ -86,20 +112,27 @@ fn linearize_depth(depth: f32) -> f32 {
 fn fs_tonemap(in: VSOut) -> @location(0) vec4<f32> {
     let scene = textureSample(scene_tex, post_samp, in.uv).rgb;
     let bloom = textureSample(bloom_tex, post_samp, in.uv).rgb;
-    let depth = textureSample(depth_tex, post_samp, in.uv);
+    // With post disabled the blur passes are skipped, so only exposure and tone mapping apply
+    if post.enabled == 0u {
+        return vec4<f32>(tonemap(clamp(scene, vec3<f32>(0.0), vec3<f32>(4.0)) * post.exposure), 1.0);
+    }
+    // Depth is fetched as a float texel: depth samplers and depth loads are unsupported on GL backends
+    let depth_size = vec2<i32>(textureDimensions(depth_tex));
+    let depth_texel = clamp(vec2<i32>(in.uv * vec2<f32>(depth_size)), vec2<i32>(0), depth_size - 1);
+    let depth = textureLoad(depth_tex, depth_texel, 0).r;
     let linear_depth = linearize_depth(depth);
 
-    let focus = 2.3;
-    let focus_range = 2.5;
-    let coc = clamp(abs(linear_depth - focus) / focus_range, 0.0, 1.0);
+    let coc = clamp(abs(linear_depth - post.focus_distance) / post.focus_range, 0.0, 1.0);
 
     // Clamp scene to avoid washout and tone map after reduced bloom
     let scene_clamped = clamp(scene, vec3<f32>(0.0), vec3<f32>(4.0));
     let blurred = bloom; // blur_b is already blurred scene
     let dof_mix = mix(scene_clamped, blurred, coc * 0.5);
 
-    let color = dof_mix + bloom * 0.28;
-    let mapped = aces_tonemap(color);
+    // Threshold applies to the blurred image, which doubles as the depth-of-field blur
+    let glow = max(bloom - vec3<f32>(post.bloom_threshold), vec3<f32>(0.0));
+    let color = (dof_mix + glow * post.bloom_intensity) * post.exposure;
+    let mapped = tonemap(color);
     // Softer vignette
     let d = length(in.uv * 2.0 - 1.0);
     let vig = mix(1.0, 0.9, smoothstep(0.92, 1.10, d));
@@ -1,12 +1,26 @@
+use std::collections::HashMap;
 use std::mem;
 
 use anyhow::Result;
 use wgpu::util::DeviceExt;
 use winit::{dpi::PhysicalSize, window::Window, event::WindowEvent};
 
-use glam::{Mat4, Vec3};
-
-use crate::engine::{Bar, AnimationInfo, TempArrayState, SortMode, MultiTempArrayState, BarState};
+use glam::{Vec2, Vec3};
+
+use crate::algorithms::heap::HeapOp;
+use crate::algorithms::manual::{self, ManualOp};
+use crate::algorithms::network::Comparator;
+use crate::algorithms::Algorithm;
+use crate::element::ElementKind;
+use crate::audio::SoundSettings;
+use crate::camera::{Camera, CameraPreset};
+use crate::post::{PostParams, PostSettings, Tonemap};
+use crate::theme::{Theme, ThemeUniform, OUTSIDE_WINDOW, PINNED, SEARCH_WINDOW, STABILITY_TINTS, THREAD_UNDERLINES};
+use crate::engine::{element_path, Engine, SortMode, BarState};
+use crate::graph::{EdgeState, GraphKind, NodeState};
+use crate::text::matching::Cell;
+use crate::hash::table::{HashOp, Probing, Slot};
+use crate::tree::model::TreeOp;
 
 #[repr(C)]
 #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -30,6 +44,13 @@ struct PlatformVertex {
     uv: [f32; 2],
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LineVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 struct Instance {
@@ -45,12 +66,28 @@ struct Globals {
     view_proj: [[f32; 4]; 4],
     bar_width: f32,
     max_value: f32,
-    focus_distance: f32,
-    focus_range: f32,
+    _padding: [f32; 2], // Uniform structs round up to 16 bytes
+}
+
+/// Changes requested through the egui panel, applied to the engine by the caller
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UiRequests {
+    pub mode: Option<SortMode>,
+    pub algorithm: Option<Algorithm>,
+    pub element_kind: Option<ElementKind>,
+    pub select_k: Option<usize>,
+    pub heap_op: Option<HeapOp>,
+    pub manual_op: Option<ManualOp>,
+    pub graph_kind: Option<GraphKind>,
+    pub tree_op: Option<TreeOp>,
+    pub hash_op: Option<HashOp>,
+    pub export_trace: bool,
+    pub sound: Option<SoundSettings>, // Set when the mute toggle or volume changed
 }
 
 pub struct Renderer<'a> {
-    surface: wgpu::Surface<'a>,
+    surface: Option<wgpu::Surface<'a>>,    // Window swapchain; None when rendering offscreen
+    offscreen: Option<wgpu::Texture>,      // Frame target read back by `read_frame` when there is no window
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
@@ -59,6 +96,7 @@ pub struct Renderer<'a> {
     // Pipelines
     bar_pipeline: wgpu::RenderPipeline,
     floor_pipeline: wgpu::RenderPipeline,
+    line_pipeline: wgpu::RenderPipeline,
     blur_pipeline_h: wgpu::RenderPipeline,
     blur_pipeline_v: wgpu::RenderPipeline,
     tonemap_pipeline: wgpu::RenderPipeline,
@@ -71,8 +109,12 @@ pub struct Renderer<'a> {
     platform_index_buffer: wgpu::Buffer,
     platform_index_count: u32,
     instance_buffer: wgpu::Buffer,
+    line_vertex_buffer: wgpu::Buffer,
     globals_buffer: wgpu::Buffer,
     globals_bind: wgpu::BindGroup,
+    theme_buffer: wgpu::Buffer,
+    post_params_buffer: wgpu::Buffer,
+    post_params_bind: wgpu::BindGroup,
 
     fullscreen_buffer: wgpu::Buffer,
 
@@ -106,18 +148,43 @@ pub struct Renderer<'a> {
 
     // Egui
     egui_ctx: egui::Context,
-    egui_state: egui_winit::State,
+    egui_state: Option<egui_winit::State>, // Window input; offscreen frames run egui without input
     egui_renderer: egui_wgpu::Renderer,
 
+    camera: Camera,
+    post: PostSettings,
+    themes: Vec<Theme>, // Built-in themes plus any loaded from a file
+    theme: usize,       // Index of the active theme
+
     // Animation state
     animated_heights: Vec<f32>,
     animated_offsets: Vec<f32>,  // X position offsets for animation
     flying_bar_progress: f32,    // 0.0 to 1.0 for flying bar animation
+    tree_positions: HashMap<usize, [f32; 2]>, // Animated (x, z) of each search-tree node by id
+
+    // Overlays
+    show_stability: bool,        // Tint equal values by original order
+    sound: Option<SoundSettings>, // None without an audio player, which hides the controls
+
+    // Bar inspection
+    pick_boxes: Vec<(Vec3, Vec3)>, // World-space (min, max) of each main-array bar, from the last frame drawn
+    pointer_over_ui: bool,
+    pinned: Option<usize>,         // Original index of the element whose path is traced
+
+    // Priority-queue operation inputs
+    heap_push_value: u32,
+    heap_key_idx: usize,
+    heap_key_value: u32,
+
+    // Search-tree operation input
+    tree_key: u32,
+
+    // Hash-table operation input
+    hash_key: u32,
 }
 
 impl<'a> Renderer<'a> {
     pub async fn new(window: &'a Window) -> Result<Self> {
-        let size = window.inner_size();
         let instance = wgpu::Instance::default();
         let surface = instance.create_surface(window)?;
         let adapter = instance
@@ -129,6 +196,40 @@ impl<'a> Renderer<'a> {
             .await
             .ok_or_else(|| anyhow::anyhow!("No suitable GPU adapters found"))?;
 
+        let mut renderer = Self::with_target(&adapter, Some(surface), window.inner_size()).await?;
+        renderer.egui_state = Some(egui_winit::State::new(
+            renderer.egui_ctx.clone(),
+            egui::ViewportId::ROOT,
+            &window,
+            Some(window.scale_factor() as f32),
+            None,
+        ));
+        Ok(renderer)
+    }
+
+    /// Renderer without a window that draws every frame into a texture for `read_frame`.
+    /// Falls back to wgpu's software adapter when no GPU is available, or always with `force_fallback_adapter`.
+    pub async fn new_offscreen(size: PhysicalSize<u32>, force_fallback_adapter: bool) -> Result<Self> {
+        let instance = wgpu::Instance::default();
+        let mut adapter = None;
+        for fallback in [force_fallback_adapter, true] {
+            adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::HighPerformance,
+                    compatible_surface: None,
+                    force_fallback_adapter: fallback,
+                })
+                .await;
+            if adapter.is_some() {
+                break;
+            }
+        }
+        let adapter = adapter.ok_or_else(|| anyhow::anyhow!("No GPU or software fallback adapter found"))?;
+        Self::with_target(&adapter, None, size).await
+    }
+
+    async fn with_target(adapter: &wgpu::Adapter, surface: Option<wgpu::Surface<'a>>, size: PhysicalSize<u32>) -> Result<Self> {
+
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
@@ -140,13 +241,20 @@ impl<'a> Renderer<'a> {
             )
             .await?;
 
-        let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .copied()
-            .find(|f| f.is_srgb())
-            .unwrap_or(surface_caps.formats[0]);
+        // Offscreen frames are plain sRGB RGBA so they can be written out as PNG without conversion
+        let (surface_format, alpha_mode) = match &surface {
+            Some(surface) => {
+                let surface_caps = surface.get_capabilities(adapter);
+                let format = surface_caps
+                    .formats
+                    .iter()
+                    .copied()
+                    .find(|f| f.is_srgb())
+                    .unwrap_or(surface_caps.formats[0]);
+                (format, surface_caps.alpha_modes[0])
+            }
+            None => (wgpu::TextureFormat::Rgba8UnormSrgb, wgpu::CompositeAlphaMode::Auto),
+        };
 
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -154,11 +262,14 @@ impl<'a> Renderer<'a> {
             width: size.width,
             height: size.height,
             present_mode: wgpu::PresentMode::AutoVsync,
-            alpha_mode: surface_caps.alpha_modes[0],
+            alpha_mode,
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
         };
-        surface.configure(&device, &config);
+        if let Some(surface) = &surface {
+            surface.configure(&device, &config);
+        }
+        let offscreen = surface.is_none().then(|| create_frame_target(&device, size, surface_format));
 
         // Bar geometry (Cube)
         // X: -0.5..0.5, Y: 0.0..1.0, Z: -0.5..0.5
@@ -239,6 +350,13 @@ impl<'a> Renderer<'a> {
             mapped_at_creation: false,
         });
 
+        let line_vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("LineVertexBuffer"),
+            size: 512 * mem::size_of::<LineVertex>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
         let globals_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Globals"),
             size: mem::size_of::<Globals>() as u64,
@@ -246,11 +364,68 @@ impl<'a> Renderer<'a> {
             mapped_at_creation: false,
         });
 
+        // Theme colors sit next to the globals so every scene shader can read them
+        let theme_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Theme"),
+            size: mem::size_of::<ThemeUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
         let globals_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("GlobalsLayout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let globals_bind = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("GlobalsBindGroup"),
+            layout: &globals_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: globals_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: theme_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        // Post-processing settings, shared by the blur and tonemap passes
+        let post_params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("PostParams"),
+            size: mem::size_of::<PostParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let post_params_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("PostParamsLayout"),
             entries: &[wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
+                visibility: wgpu::ShaderStages::FRAGMENT,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
                     has_dynamic_offset: false,
@@ -260,12 +435,12 @@ impl<'a> Renderer<'a> {
             }],
         });
 
-        let globals_bind = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("GlobalsBindGroup"),
-            layout: &globals_layout,
+        let post_params_bind = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("PostParamsBindGroup"),
+            layout: &post_params_layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
-                resource: globals_buffer.as_entire_binding(),
+                resource: post_params_buffer.as_entire_binding(),
             }],
         });
 
@@ -359,7 +534,8 @@ impl<'a> Renderer<'a> {
                     binding: 2,
                     visibility: wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Depth,
+                        // Read with textureLoad as a plain float, which GL backends support for depth
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
                         view_dimension: wgpu::TextureViewDimension::D2,
                         multisampled: false,
                     },
@@ -485,6 +661,10 @@ impl<'a> Renderer<'a> {
             label: Some("FloorShader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("floor.wgsl").into()),
         });
+        let line_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("LineShader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("line.wgsl").into()),
+        });
         let post_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("PostShader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("post.wgsl").into()),
@@ -730,10 +910,54 @@ impl<'a> Renderer<'a> {
             multiview: None,
         });
 
+        // Line pipeline (graph edges drawn on the floor plane)
+        let line_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("LinePipelineLayout"),
+            bind_group_layouts: &[&globals_layout],
+            push_constant_ranges: &[],
+        });
+        let line_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("LinePipeline"),
+            layout: Some(&line_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &line_shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: mem::size_of::<LineVertex>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4],
+                }],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &line_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: hdr_format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
         // Post pipelines
         let blur_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("BlurPipelineLayout"),
-            bind_group_layouts: &[&tex_samp_layout],
+            bind_group_layouts: &[&tex_samp_layout, &post_params_layout],
             push_constant_ranges: &[],
         });
         let blur_pipeline_h = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -796,7 +1020,7 @@ impl<'a> Renderer<'a> {
 
         let tonemap_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("TonemapPipelineLayout"),
-            bind_group_layouts: &[&tex_samp_double_layout],
+            bind_group_layouts: &[&tex_samp_double_layout, &post_params_layout],
             push_constant_ranges: &[],
         });
         let tonemap_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -828,25 +1052,20 @@ impl<'a> Renderer<'a> {
             multiview: None,
         });
 
-        // Egui setup
+        // Egui setup; window input is attached by `new`
         let egui_ctx = egui::Context::default();
-        let egui_state = egui_winit::State::new(
-            egui_ctx.clone(),
-            egui::ViewportId::ROOT,
-            &window,
-            Some(window.scale_factor() as f32),
-            None,
-        );
         let egui_renderer = egui_wgpu::Renderer::new(&device, surface_format, None, 1);
 
         Ok(Self {
             surface,
+            offscreen,
             device,
             queue,
             config,
             size,
             bar_pipeline,
             floor_pipeline,
+            line_pipeline,
             blur_pipeline_h,
             blur_pipeline_v,
             tonemap_pipeline,
@@ -857,8 +1076,12 @@ impl<'a> Renderer<'a> {
             platform_index_buffer,
             platform_index_count: platform_indices.len() as u32,
             instance_buffer,
+            line_vertex_buffer,
             globals_buffer,
             globals_bind,
+            theme_buffer,
+            post_params_buffer,
+            post_params_bind,
             fullscreen_buffer,
             floor_bind_group,
             scene_floor_tex,
@@ -879,16 +1102,71 @@ impl<'a> Renderer<'a> {
             tex_samp_layout,
             tex_samp_double_layout,
             egui_ctx,
-            egui_state,
+            egui_state: None,
             egui_renderer,
+            camera: Camera::new(),
+            post: PostSettings::default(),
+            themes: Theme::builtin(),
+            theme: 0,
             animated_heights: Vec::new(),
             animated_offsets: Vec::new(),
             flying_bar_progress: 0.0,
+            tree_positions: HashMap::new(),
+            show_stability: false,
+            sound: None,
+            pick_boxes: Vec::new(),
+            pointer_over_ui: false,
+            pinned: None,
+            heap_push_value: 500,
+            heap_key_idx: 0,
+            heap_key_value: 1,
+            tree_key: 50,
+            hash_key: 42,
         })
     }
 
+    /// Events go to egui first; the camera only gets the pointer while egui isn't using it
     pub fn handle_input(&mut self, window: &Window, event: &WindowEvent) {
-        let _ = self.egui_state.on_window_event(window, event);
+        let consumed = match &mut self.egui_state {
+            Some(state) => state.on_window_event(window, event).consumed,
+            None => false,
+        };
+        let over_ui = consumed || self.egui_ctx.wants_pointer_input() || self.egui_ctx.is_pointer_over_area();
+        self.pointer_over_ui = over_ui;
+        self.camera.handle_event(event, over_ui);
+    }
+
+    /// True while an egui text field has focus, so typed keys aren't shortcuts
+    pub fn wants_keyboard(&self) -> bool {
+        self.egui_ctx.wants_keyboard_input()
+    }
+
+    /// Stop tracing the pinned element, for a reset that deals new data
+    pub fn unpin(&mut self) {
+        self.pinned = None;
+    }
+
+    /// Show the mute toggle and volume, starting from `settings`
+    pub fn enable_sound(&mut self, settings: SoundSettings) {
+        self.sound = Some(settings);
+    }
+
+    /// Switch to `theme`, replacing a listed theme of the same name or adding it to the list
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = match self.themes.iter().position(|t| t.name == theme.name) {
+            Some(index) => {
+                self.themes[index] = theme;
+                index
+            }
+            None => {
+                self.themes.push(theme);
+                self.themes.len() - 1
+            }
+        };
+    }
+
+    pub fn set_camera_preset(&mut self, preset: CameraPreset) {
+        self.camera.set_preset(preset);
     }
 
     pub fn resize(&mut self, size: PhysicalSize<u32>) {
@@ -898,7 +1176,10 @@ impl<'a> Renderer<'a> {
         self.size = size;
         self.config.width = size.width;
         self.config.height = size.height;
-        self.surface.configure(&self.device, &self.config);
+        match &self.surface {
+            Some(surface) => surface.configure(&self.device, &self.config),
+            None => self.offscreen = Some(create_frame_target(&self.device, size, self.config.format)),
+        }
 
         // Recreate render targets and bind groups
         let hdr_format = wgpu::TextureFormat::Rgba16Float;
@@ -981,22 +1262,52 @@ impl<'a> Renderer<'a> {
         });
     }
 
-    pub fn render(&mut self, bars: &[Bar], max_value: u32, comparisons: usize, operations: usize, memory_ops: usize, time_elapsed: std::time::Duration, current_memory: usize, peak_memory: usize, animation: AnimationInfo, temp_array: &TempArrayState, multi_temp_arrays: &MultiTempArrayState, mode: SortMode, merge_level: usize, est_time_ns: u64, est_comparison_ns: u64, est_memory_ns: u64, memory_allocs: usize, memory_deallocs: usize, dt: std::time::Duration, window: &Window) -> Result<Option<SortMode>> {
+    /// Draw the engine's current state; `window` supplies egui input and is None for offscreen frames
+    pub fn render(&mut self, engine: &Engine, dt: std::time::Duration, window: Option<&Window>) -> Result<UiRequests> {
+        let (bars, max_value) = engine.bars();
         if bars.is_empty() {
-            return Ok(None);
+            return Ok(UiRequests::default());
         }
+        let (comparisons, operations, memory_ops) = (engine.comparisons, engine.operations, engine.memory_ops);
+        let (time_elapsed, current_memory, peak_memory) = (engine.time_elapsed, engine.current_memory, engine.peak_memory);
+        let animation = &engine.current_animation;
+        let (temp_array, multi_temp_arrays, search) = (&engine.temp_array, &engine.multi_temp_arrays, &engine.search);
+        let (select_k, last_popped) = (engine.select_k, engine.last_popped);
+        let (graph, graph_kind, dp, text, tree, hash) = (&engine.graph, engine.graph_kind, &engine.dp, &engine.text, &engine.tree, &engine.hash);
+        let manual = &engine.manual;
+        let (mode, algorithm, element_kind) = (engine.mode, engine.algorithm, engine.element_kind());
+        let (stability, finished) = (engine.stability(), engine.is_finished());
+        let (merge_level, network_layer) = (engine.merge_level, engine.network_layer);
+        let narration = engine.narration(5);
+        let heap = engine.heap();
+        let (code_line, calls, played, history) = (engine.current_line(), &engine.calls, engine.played(), engine.history());
+        let active_calls = engine.active_calls();
+        let (est_time_ns, memory_allocs, memory_deallocs) = (engine.est_time_ns, engine.memory_allocs, engine.memory_deallocs);
 
         let array_size = bars.len();
         let base_memory = array_size * 4; // 4 bytes per u32 element
         let dt_secs = dt.as_secs_f32();
         
-        let mut mode_changed: Option<SortMode> = None;
+        let mut requests = UiRequests::default();
+        let mut show_stability = self.show_stability;
+        let mut sound = self.sound;
+        let mut post = self.post;
+        let mut theme_index = self.theme;
+        let theme = self.themes[self.theme].clone();
+        let mut heap_push_value = self.heap_push_value;
+        let mut heap_key_idx = self.heap_key_idx.min(heap.len().saturating_sub(1));
+        let mut heap_key_value = self.heap_key_value;
+        let mut tree_key = self.tree_key;
+        let mut hash_key = self.hash_key;
 
         // Initialize or resize animated heights/offsets if needed
+        // Existing heights are kept so a growing or shrinking array (priority queue) doesn't snap
         if self.animated_heights.len() != bars.len() {
             let max_val = max_value.max(1) as f32;
-            self.animated_heights = bars.iter().map(|b| b.value as f32 / max_val).collect();
-            self.animated_offsets = vec![0.0; bars.len()];
+            let kept = self.animated_heights.len().min(bars.len());
+            self.animated_heights.truncate(kept);
+            self.animated_heights.extend(bars[kept..].iter().map(|b| b.value as f32 / max_val));
+            self.animated_offsets.resize(bars.len(), 0.0);
         }
 
         // Animate heights towards targets
@@ -1017,30 +1328,344 @@ impl<'a> Renderer<'a> {
             self.flying_bar_progress = 0.0;
         }
 
+        let network = &engine.network;
+
+        // Bar under the pointer, tested against the boxes of the last frame drawn; a click pins it, or unpins on empty space.
+        // While sorting by hand a click picks the bar for the next comparison instead
+        let viewport = Vec2::new(self.size.width as f32, self.size.height as f32);
+        let click = self.camera.take_click().filter(|_| !self.pointer_over_ui);
+        let pick_at = |cursor: Vec2| {
+            let (origin, direction) = self.camera.ray(cursor, viewport);
+            pick(&self.pick_boxes, origin, direction).filter(|&i| i < bars.len())
+        };
+        let hovered = self.camera.hover().filter(|_| !self.pointer_over_ui).and_then(pick_at);
+        if let Some(click) = click {
+            let picked = pick_at(click);
+            if algorithm == Algorithm::ManualSort {
+                requests.manual_op = picked.map(ManualOp::Pick);
+            } else {
+                self.pinned = picked.map(|i| bars[i].origin);
+            }
+        }
+        let pinned = self.pinned;
+
         // Egui update
-        let raw_input = self.egui_state.take_egui_input(window);
+        let raw_input = match (&mut self.egui_state, window) {
+            (Some(state), Some(window)) => state.take_egui_input(window),
+            _ => {
+                // Offscreen frames lay the panels out as in a 720p window and scale them with the frame
+                let pixels_per_point = self.size.height as f32 / 720.0;
+                let mut raw_input = egui::RawInput {
+                    screen_rect: Some(egui::Rect::from_min_size(
+                        egui::Pos2::ZERO,
+                        egui::vec2(self.size.width as f32, self.size.height as f32) / pixels_per_point,
+                    )),
+                    predicted_dt: dt_secs,
+                    ..Default::default()
+                };
+                raw_input.viewports.entry(egui::ViewportId::ROOT).or_default().native_pixels_per_point = Some(pixels_per_point);
+                raw_input
+            }
+        };
         let full_output = self.egui_ctx.run(raw_input, |ctx| {
+            if let Some(index) = hovered {
+                let bar = bars[index];
+                egui::show_tooltip_at_pointer(ctx, egui::LayerId::background(), egui::Id::new("bar_inspect"), |ui| {
+                    ui.strong(format!("a[{index}] = {}", bar.value));
+                    ui.label(format!("Original index: {}", bar.origin));
+                    ui.label(format!("State: {:?}", bar.state));
+                    match history.iter().enumerate().rev().find(|(_, action)| action.touches(index)) {
+                        Some((step, action)) => {
+                            ui.label(format!("Last touched by step {}: {:?}", step + 1, action.kind));
+                            if let Some(note) = &action.note {
+                                ui.weak(note);
+                            }
+                        }
+                        None => {
+                            ui.weak("Not touched yet");
+                        }
+                    }
+                    if algorithm == Algorithm::ManualSort {
+                        ui.weak("Click to pick it for a comparison");
+                    } else if pinned == Some(bar.origin) {
+                        ui.weak("Pinned: click empty space to unpin");
+                    } else {
+                        ui.weak("Click to pin and trace its moves");
+                    }
+                });
+            }
+
             egui::Window::new("Algorithm Stats")
                 .default_pos([10.0, 10.0])
                 .show(ctx, |ui| {
-                    ui.heading("Merge Sort");
+                    ui.heading(algorithm.label());
+                    egui::ComboBox::from_label("Algorithm")
+                        .selected_text(algorithm.label())
+                        .show_ui(ui, |ui| {
+                            for candidate in Algorithm::ALL {
+                                if ui.selectable_label(candidate == algorithm, candidate.label()).clicked() {
+                                    requests.algorithm = Some(candidate);
+                                }
+                            }
+                        });
                     ui.separator();
                     
                     // Mode toggle
                     ui.horizontal(|ui| {
                         ui.label("Mode:");
                         if ui.radio(matches!(mode, SortMode::Sequential), "Sequential").clicked() {
-                            mode_changed = Some(SortMode::Sequential);
+                            requests.mode = Some(SortMode::Sequential);
                         }
                         if ui.radio(matches!(mode, SortMode::Parallel), "Parallel (8 threads)").clicked() {
-                            mode_changed = Some(SortMode::Parallel);
+                            requests.mode = Some(SortMode::Parallel);
                         }
                     });
+                    egui::ComboBox::from_label("Elements")
+                        .selected_text(element_kind.label())
+                        .show_ui(ui, |ui| {
+                            for kind in ElementKind::ALL {
+                                if ui.selectable_label(kind == element_kind, kind.label()).clicked() {
+                                    requests.element_kind = Some(kind);
+                                }
+                            }
+                        });
                     ui.separator();
                     
                     ui.label(format!("Time Elapsed: {:.2} s", time_elapsed.as_secs_f32()));
                     ui.separator();
                     
+                    if algorithm.is_graph() {
+                        egui::ComboBox::from_label("Graph")
+                            .selected_text(graph_kind.label())
+                            .show_ui(ui, |ui| {
+                                for kind in GraphKind::ALL {
+                                    if ui.selectable_label(kind == graph_kind, kind.label()).clicked() {
+                                        requests.graph_kind = Some(kind);
+                                    }
+                                }
+                            });
+                        ui.label(format!("Nodes: {}  Edges: {}", graph.graph.node_count(), graph.graph.edges.len()));
+                        ui.label(format!("Visited: {}  Frontier: {}", graph.visited, graph.frontier));
+                        if algorithm.is_mst() {
+                            ui.label(format!("MST weight: {}", graph.tree_weight));
+                        } else {
+                            match graph.dist.get(graph.graph.goal).copied().flatten() {
+                                Some(d) if graph.nodes[graph.graph.goal] != NodeState::Unseen => {
+                                    ui.label(format!("Goal distance: {}", d))
+                                }
+                                _ => ui.label("Goal distance: ?"),
+                            };
+                        }
+                        if let Some(uf) = &graph.union_find {
+                            ui.label(format!("Union-find: {} find hops, {} compressions, {} unions", uf.find_hops, uf.compressions, uf.unions));
+                        }
+                        ui.label("Press R for a new graph");
+                        ui.separator();
+                    }
+                    
+                    if algorithm.is_dp() {
+                        ui.label(dp.problem.describe());
+                        ui.label(format!("Table: {} × {}", dp.table.rows, dp.table.cols));
+                        // Every DP write is also counted as an operation
+                        ui.label(format!("Cell reads: {}  Cell writes: {}", memory_ops - operations, operations));
+                        ui.label(format!("Input comparisons: {}", comparisons));
+                        match dp.answer {
+                            Some(answer) => ui.label(format!("Answer: {}", answer)),
+                            None => ui.label("Answer: filling table..."),
+                        };
+                        ui.label("Press R for new inputs");
+                        ui.separator();
+                    }
+                    
+                    if algorithm.is_text() {
+                        ui.label(format!("Text:    {}", String::from_utf8_lossy(&text.text)));
+                        ui.label(format!("Pattern: {} (shift {})", String::from_utf8_lossy(&text.pattern), text.shift));
+                        ui.label(format!("Auxiliary array: {}", text.aux_label));
+                        ui.label(format!("Character/hash comparisons: {}", comparisons));
+                        let found: Vec<String> = text.matches.iter().map(|s| s.to_string()).collect();
+                        ui.label(format!("Matches at: {}", if found.is_empty() { "-".to_string() } else { found.join(", ") }));
+                        if algorithm == Algorithm::RabinKarp {
+                            ui.label(format!("Spurious hash hits: {}", text.spurious_hits));
+                        }
+                        ui.label("Press R for a new text");
+                        ui.separator();
+                    }
+                    
+                    if algorithm.is_tree() {
+                        ui.label(format!("Nodes: {}  Height: {}", tree.shape.nodes.len(), tree.shape.height()));
+                        ui.label(format!("Rotations: {}", tree.rotations));
+                        if let Some(key) = tree.key {
+                            ui.label(format!("Current key: {key}"));
+                        }
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut tree_key).range(1..=99));
+                            if ui.button("Insert").clicked() {
+                                requests.tree_op = Some(TreeOp::Insert(tree_key));
+                            }
+                            if ui.button("Delete").clicked() {
+                                requests.tree_op = Some(TreeOp::Delete(tree_key));
+                            }
+                            if ui.button("Search").clicked() {
+                                requests.tree_op = Some(TreeOp::Search(tree_key));
+                            }
+                        });
+                        match tree.found {
+                            Some(Some(_)) => ui.label("Last lookup: found"),
+                            Some(None) => ui.label("Last lookup: not found"),
+                            None => ui.label("Last lookup: -"),
+                        };
+                        if algorithm == Algorithm::RedBlackTree {
+                            ui.horizontal(|ui| {
+                                ui.colored_label(theme.label_color(BarState::Thread0 as u32), "red");
+                                ui.colored_label(theme.label_color(OUTSIDE_WINDOW), "black");
+                            });
+                        }
+                        ui.label("Press R to rebuild from new keys");
+                        ui.separator();
+                    }
+                    
+                    if algorithm.is_hash() {
+                        ui.label(format!("Keys: {}  Buckets: {}  Resizes: {}", hash.len(), hash.buckets.len(), hash.resizes));
+                        ui.label(format!("Load factor: {:.2}", hash.load_factor()));
+                        if hash.probing() != Probing::Chaining {
+                            ui.label(format!("Tombstones: {}", hash.tombstones()));
+                        }
+                        ui.label(format!("Probes per operation: {:.2} avg, {} max", hash.mean_probes(), hash.max_probes));
+                        let (mean, max) = hash.probe_lengths();
+                        ui.label(format!("Probe length of stored keys: {:.2} avg, {} max", mean, max));
+                        if let Some(key) = hash.key {
+                            ui.label(format!("Current key: {key}"));
+                        }
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut hash_key).range(1..=99));
+                            if ui.button("Insert").clicked() {
+                                requests.hash_op = Some(HashOp::Insert(hash_key));
+                            }
+                            if ui.button("Lookup").clicked() {
+                                requests.hash_op = Some(HashOp::Lookup(hash_key));
+                            }
+                            if ui.button("Delete").clicked() {
+                                requests.hash_op = Some(HashOp::Delete(hash_key));
+                            }
+                        });
+                        match hash.found {
+                            Some(Some(_)) => ui.label("Last lookup: found"),
+                            Some(None) => ui.label("Last lookup: not found"),
+                            None => ui.label("Last lookup: -"),
+                        };
+                        ui.label("Key color: probes from home (blue = 1, red = 8+)");
+                        ui.label("Press R to rebuild from new keys");
+                        ui.separator();
+                    }
+                    
+                    if algorithm.is_search() {
+                        ui.label(format!("Target: {}", search.target));
+                        if let Some((lo, hi)) = search.window {
+                            ui.label(format!("Window: [{}, {}] ({} left)", lo, hi, hi + 1 - lo));
+                        }
+                        match search.result {
+                            Some(Some(idx)) => ui.label(format!("Found at index {}", idx)),
+                            Some(None) => ui.label("Not found"),
+                            None => ui.label("Searching..."),
+                        };
+                        ui.label(format!("Probes: {}", comparisons));
+                        ui.separator();
+                    }
+                    
+                    if algorithm == Algorithm::PriorityQueue {
+                        // Operations act on the heap after queued actions, which the bars may not show yet
+                        ui.label(format!("Heap size: {}", heap.len()));
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut heap_push_value).range(1..=1000));
+                            if ui.button("Push").clicked() {
+                                requests.heap_op = Some(HeapOp::Push(heap_push_value));
+                            }
+                            if ui.add_enabled(heap.len() > 1, egui::Button::new("Pop min")).clicked() {
+                                requests.heap_op = Some(HeapOp::Pop);
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("a[");
+                            ui.add(egui::DragValue::new(&mut heap_key_idx).range(0..=heap.len().saturating_sub(1)));
+                            ui.label("] →");
+                            ui.add(egui::DragValue::new(&mut heap_key_value).range(1..=1000));
+                            let current = heap.get(heap_key_idx).copied();
+                            let decreases = current.is_some_and(|current| heap_key_value < current);
+                            let button = ui.add_enabled(decreases, egui::Button::new("Decrease key"));
+                            if button.clicked() {
+                                requests.heap_op = Some(HeapOp::DecreaseKey { idx: heap_key_idx, value: heap_key_value });
+                            }
+                            if let Some(current) = current.filter(|_| !decreases) {
+                                button.on_disabled_hover_text(format!("New key must be below a[{heap_key_idx}] = {current}"));
+                            }
+                        });
+                        if let Some(value) = last_popped {
+                            ui.label(format!("Last popped: {}", value));
+                        }
+                        ui.label("Press R to heapify a new array");
+                        ui.separator();
+                    }
+                    
+                    if algorithm == Algorithm::ManualSort {
+                        ui.label("Click two bars to compare them; swap them if they are out of order");
+                        if let Some(i) = manual.selected {
+                            ui.label(format!("Picked a[{i}] = {}; click a second bar", bars[i].value));
+                        }
+                        if let Some((i, j)) = manual.compared {
+                            let verdict = if bars[i].value <= bars[j].value { "in order" } else { "out of order" };
+                            ui.horizontal(|ui| {
+                                ui.label(format!("a[{i}] vs a[{j}]: {verdict}"));
+                                if ui.button("Swap").clicked() {
+                                    requests.manual_op = Some(ManualOp::Swap);
+                                }
+                            });
+                        }
+                        ui.label(format!("You: {} comparisons, {} writes", comparisons, manual::writes(history)));
+                        ui.weak("A swap writes two slots, a merge one per element");
+                        if manual.sorted {
+                            ui.colored_label(theme.label_color(BarState::Sorted as u32), "Sorted!");
+                            for baseline in &manual.baselines {
+                                let best = manual.best() == Some(baseline);
+                                let text = format!(
+                                    "{}: {} comparisons, {} writes",
+                                    baseline.algorithm.label(),
+                                    baseline.comparisons,
+                                    baseline.writes
+                                );
+                                if best {
+                                    ui.strong(format!("{text} (fewest)"));
+                                } else {
+                                    ui.label(text);
+                                }
+                            }
+                            ui.label(format!(
+                                "No comparison sort can guarantee fewer than {} comparisons on {} elements",
+                                manual::lower_bound(array_size),
+                                array_size
+                            ));
+                            if let Some(best) = manual.best() {
+                                let extra = comparisons as i64 - best.comparisons as i64;
+                                ui.label(match extra {
+                                    e if e > 0 => format!("{e} more comparisons than {}", best.algorithm.label()),
+                                    0 => format!("As few comparisons as {}", best.algorithm.label()),
+                                    e => format!("{} fewer comparisons than {}", -e, best.algorithm.label()),
+                                });
+                            }
+                        }
+                        ui.label("Press R for a new array");
+                        ui.separator();
+                    }
+
+                    if algorithm.is_selection() {
+                        let mut k = select_k;
+                        ui.add(egui::Slider::new(&mut k, 0..=array_size - 1).text("k (0-based rank)"));
+                        if k != select_k {
+                            requests.select_k = Some(k);
+                        }
+                        ui.label(format!("Looking for the element of rank {} (position {})", k + 1, k));
+                        ui.separator();
+                    }
+                    
                     // Estimated CPU Time section with operation counts
                     ui.label("Estimated CPU Time:");
                     let format_time = |ns: u64| -> String {
@@ -1078,6 +1703,18 @@ impl<'a> Renderer<'a> {
                     ui.label(format!("  Deallocations: {} × {}ns = {}", 
                         memory_deallocs, FREE_NS, format_time(memory_deallocs as u64 * FREE_NS)));
                     
+                    // Union-find share of the above (Kruskal)
+                    if let Some(uf) = &graph.union_find {
+                        let find_cost = MEMORY_READ_NS + COMPARE_NS;
+                        ui.label(format!("  Find hops: {} × {}ns = {}", 
+                            uf.find_hops, find_cost, format_time(uf.find_hops as u64 * find_cost)));
+                        ui.label(format!("  Path compressions: {} × {}ns = {}", 
+                            uf.compressions, MEMORY_WRITE_NS, format_time(uf.compressions as u64 * MEMORY_WRITE_NS)));
+                        let union_cost = 2 * MEMORY_READ_NS + COMPARE_NS + MEMORY_WRITE_NS;
+                        ui.label(format!("  Unions: {} × ~{}ns = {}", 
+                            uf.unions, union_cost, format_time(uf.unions as u64 * union_cost)));
+                    }
+                    
                     // Total
                     ui.label(format!("  Total: {}", format_time(est_time_ns)));
                     
@@ -1095,74 +1732,535 @@ impl<'a> Renderer<'a> {
                     ui.label(format!("Peak Total: {} bytes", base_memory + peak_memory));
                     ui.separator();
                     ui.label("Complexity Analysis:");
-                    ui.label("Time: O(n log n)");
-                    ui.label("Space: O(n)");
+                    let (time_complexity, space_complexity) = algorithm.complexity();
+                    ui.label(format!("Time: {}", time_complexity));
+                    ui.label(format!("Space: {}", space_complexity));
+                    ui.separator();
                     
-                    if matches!(mode, SortMode::Parallel) {
-                        ui.separator();
-                        ui.label("Thread Colors:");
+                    // Stability of equal keys
+                    ui.checkbox(&mut show_stability, "Tint duplicates by original order");
+                    let stability_text = match (stability, finished) {
+                        (None, _) => "no duplicate values",
+                        (Some(_), false) => "pending (run in progress)",
+                        (Some(true), true) => "preserved",
+                        (Some(false), true) => "violated",
+                    };
+                    ui.label(format!("Stability: {}", stability_text));
+                    if show_stability {
                         ui.horizontal(|ui| {
-                            ui.colored_label(egui::Color32::from_rgb(255, 102, 102), "T0");
-                            ui.colored_label(egui::Color32::from_rgb(255, 178, 102), "T1");
-                            ui.colored_label(egui::Color32::from_rgb(255, 255, 102), "T2");
-                            ui.colored_label(egui::Color32::from_rgb(102, 255, 102), "T3");
+                            ui.colored_label(theme.label_color(STABILITY_TINTS), "first");
+                            ui.label("→");
+                            ui.colored_label(theme.label_color(STABILITY_TINTS + 7), "last");
                         });
+                    }
+                    
+                    if let Some(sound) = &mut sound {
+                        ui.separator();
+                        ui.label("Sound:");
                         ui.horizontal(|ui| {
-                            ui.colored_label(egui::Color32::from_rgb(102, 255, 255), "T4");
-                            ui.colored_label(egui::Color32::from_rgb(102, 178, 255), "T5");
-                            ui.colored_label(egui::Color32::from_rgb(178, 102, 255), "T6");
-                            ui.colored_label(egui::Color32::from_rgb(255, 102, 255), "T7");
+                            ui.checkbox(&mut sound.muted, "Mute");
+                            ui.add_enabled(!sound.muted, egui::Slider::new(&mut sound.volume, 0.0..=1.0).text("Volume"));
+                        });
+                    }
+
+                    egui::ComboBox::from_label("Theme")
+                        .selected_text(&theme.name)
+                        .show_ui(ui, |ui| {
+                            for (index, listed) in self.themes.iter().enumerate() {
+                                ui.selectable_value(&mut theme_index, index, &listed.name);
+                            }
+                        });
+
+                    if matches!(mode, SortMode::Parallel) {
+                        ui.separator();
+                        ui.label("Thread Colors:");
+                        for row in [0..4, 4..8] {
+                            ui.horizontal(|ui| {
+                                for thread_id in row {
+                                    ui.colored_label(theme.label_color(BarState::from_thread_id(thread_id) as u32), format!("T{thread_id}"));
+                                }
+                            });
+                        }
+                    }
+                });
+
+            egui::Window::new("Post-processing")
+                .default_pos([380.0, 10.0])
+                .default_open(false)
+                .show(ctx, |ui| {
+                    ui.checkbox(&mut post.enabled, "Enabled (bloom, blur, depth of field)");
+                    ui.add(egui::Slider::new(&mut post.exposure, 0.1..=4.0).logarithmic(true).text("Exposure"));
+                    egui::ComboBox::from_label("Tonemap")
+                        .selected_text(post.tonemap.label())
+                        .show_ui(ui, |ui| {
+                            for tonemap in Tonemap::ALL {
+                                ui.selectable_value(&mut post.tonemap, tonemap, tonemap.label());
+                            }
                         });
+                    ui.add_enabled_ui(post.enabled, |ui| {
+                        ui.add(egui::Slider::new(&mut post.bloom_intensity, 0.0..=2.0).text("Bloom intensity"));
+                        ui.add(egui::Slider::new(&mut post.bloom_threshold, 0.0..=2.0).text("Bloom threshold"));
+                        ui.add(egui::Slider::new(&mut post.blur_radius, 0.0..=4.0).text("Blur radius"));
+                        ui.add(egui::Slider::new(&mut post.focus_distance, 0.5..=6.0).text("Focus distance"));
+                        ui.add(egui::Slider::new(&mut post.focus_range, 0.1..=6.0).text("Focus range"));
+                    });
+                    if ui.button("Reset").clicked() {
+                        post = PostSettings::default();
                     }
                 });
+
+            if algorithm.plays_actions() {
+                egui::Window::new("Narration")
+                    .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -10.0])
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        match narration.first() {
+                            Some(latest) => ui.strong(*latest),
+                            None => ui.label("Each step is explained here as it plays"),
+                        };
+                        // Earlier steps fade out below the current one
+                        for note in narration.iter().skip(1) {
+                            ui.colored_label(egui::Color32::from_gray(140), *note);
+                        }
+                        if ui.button("Export trace").clicked() {
+                            requests.export_trace = true;
+                        }
+                    });
+            }
+
+            let listing = algorithm.pseudocode();
+            if !listing.is_empty() {
+                egui::Window::new("Pseudocode")
+                    .default_pos([900.0, 320.0])
+                    .show(ctx, |ui| {
+                        for (line, text) in listing.iter().enumerate() {
+                            let text = egui::RichText::new(*text).monospace();
+                            if code_line == Some(line) {
+                                ui.label(text.color(egui::Color32::BLACK).background_color(egui::Color32::from_rgb(255, 230, 140)));
+                            } else {
+                                ui.label(text);
+                            }
+                        }
+                    });
+            }
+
+            if !calls.is_empty() {
+                egui::Window::new("Call Tree")
+                    .default_pos([620.0, 10.0])
+                    .show(ctx, |ui| {
+                        let depth = active_calls.iter().map(|c| c.depth + 1).max().unwrap_or(0);
+                        let max_depth = calls.iter().map(|c| c.depth + 1).max().unwrap_or(0);
+                        ui.label(format!("{} calls, {} active, stack depth {} of {}", calls.len(), active_calls.len(), depth, max_depth));
+                        ui.separator();
+                        // Calls are listed thread by thread, each thread's tree in call order
+                        egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                            for call in calls {
+                                let prefix = if mode == SortMode::Parallel { format!("T{} ", call.thread_id) } else { String::new() };
+                                let text = format!("{}{}[{}, {})", "  ".repeat(call.depth), prefix, call.left, call.right);
+                                let text = egui::RichText::new(text).monospace();
+                                if call.is_active(played) {
+                                    let color = theme.label_color(call_state(mode, call.thread_id));
+                                    ui.label(text.color(egui::Color32::BLACK).background_color(color));
+                                } else if call.is_done(played) {
+                                    ui.label(text.weak());
+                                } else {
+                                    ui.label(text);
+                                }
+                            }
+                        });
+                    });
+            }
+
+            if algorithm.is_network() {
+                egui::Window::new("Sorting Network")
+                    .default_pos([900.0, 10.0])
+                    .show(ctx, |ui| {
+                        let comparators: usize = network.iter().map(Vec::len).sum();
+                        ui.label(format!("{} comparators in {} layers", comparators, network.len()));
+                        match network_layer {
+                            Some(layer) => ui.label(format!("Layer {} of {}", layer + 1, network.len())),
+                            None if finished => ui.label("Network complete"),
+                            None => ui.label("Waiting for the first layer"),
+                        };
+                        draw_network(ui, network, bars.len(), network_layer, finished);
+                    });
+            }
         });
 
+        self.show_stability = show_stability;
+        // Another algorithm or element kind deals new data, where the pinned origin is a different element
+        if requests.algorithm.is_some() || requests.element_kind.is_some() {
+            self.pinned = None;
+        }
+        self.post = post;
+        self.theme = theme_index;
+        if sound != self.sound {
+            requests.sound = sound;
+            self.sound = sound;
+        }
+        self.heap_push_value = heap_push_value;
+        self.heap_key_idx = heap_key_idx;
+        self.heap_key_value = heap_key_value;
+        self.tree_key = tree_key;
+        self.hash_key = hash_key;
+
         let count = bars.len() as f32;
-        let bar_width = 2.0 / count;
+        // Graph nodes use a fixed pillar footprint instead of splitting the array width
+        let bar_width = if algorithm.is_graph() {
+            0.12
+        } else if algorithm.is_hash() {
+            (3.6 / hash.buckets.len().max(1) as f32).min(0.2)
+        } else if algorithm.is_tree() {
+            (3.6 / tree.shape.nodes.len().max(1) as f32).min(0.2)
+        } else if algorithm.is_text() {
+            3.6 / text.text.len().max(text.aux.len()).max(1) as f32
+        } else if algorithm.is_dp() {
+            // Square cells that fit the table into the floor area
+            (3.6 / dp.table.cols.max(1) as f32).min(1.8 / dp.table.rows.max(1) as f32)
+        } else {
+            2.0 / count
+        };
+
+        // Stability tint: rank of each bar's original index among bars with the same value
+        let stability_tints: Vec<Option<u32>> = if self.show_stability {
+            engine
+                .duplicate_ranks()
+                .into_iter()
+                // The eight tints run from earliest to latest original position
+                .map(|rank| rank.map(|(rank, count)| STABILITY_TINTS + (rank * 7 / (count - 1)) as u32))
+                .collect()
+        } else {
+            vec![None; bars.len()]
+        };
         
         // Shift main array to the left to make room for temp array
         let main_array_offset = -1.2; // Shift everything left
         let z_span = 0.6;
+        // Resting (x, z) of main-array slot i
+        let slot = |i: usize| {
+            let t = if count > 1.0 { i as f32 / (count - 1.0) } else { 0.5 };
+            (main_array_offset + (-1.0 + bar_width * (i as f32 + 0.5)), (t - 0.5) * z_span)
+        };
 
         let mut instances: Vec<Instance> = bars
             .iter()
             .enumerate()
             .map(|(i, bar)| {
-                let t = if count > 1.0 { i as f32 / (count - 1.0) } else { 0.5 };
-                let offset = main_array_offset + (-1.0 + bar_width * (i as f32 + 0.5));
+                let (offset, slot_z) = slot(i);
                 
                 // In parallel mode, spread thread-active bars horizontally by thread
                 let z = match mode {
-                    SortMode::Sequential => (t - 0.5) * z_span,
+                    SortMode::Sequential => slot_z,
                     SortMode::Parallel => {
                         let state = bar.state as u32;
-                        // States 6-13 are ThreadActive0-7, spread them by thread
-                        if state >= 6 && state <= 13 {
-                            let thread_id = state - 6;
+                        // Thread-active bars are spread by thread
+                        if (BarState::Thread0 as u32..=BarState::Thread7 as u32).contains(&state) {
+                            let thread_id = state - BarState::Thread0 as u32;
                             let thread_t = thread_id as f32 / 7.0;
                             (thread_t - 0.5) * z_span
                         } else {
                             // Normal bars keep their index-based position
-                            (t - 0.5) * z_span
+                            slot_z
                         }
                     }
                 };
                 
                 // Use animated height for smooth transitions
                 let h = self.animated_heights[i].clamp(0.0, 1.0);
+                // Stability tint replaces resting colors only, so compare/swap stay visible
+                let mut state = match (bar.state, stability_tints[i]) {
+                    (BarState::Idle | BarState::Sorted, Some(tint)) => tint,
+                    _ => bar.state as u32,
+                };
+                // Dim idle bars outside the active search window (state 39)
+                if let Some((lo, hi)) = search.window {
+                    if bar.state == BarState::Idle && (i < lo || i > hi) {
+                        state = 39;
+                    }
+                }
                 Instance {
                     offset,
                     height: h,
                     z,
-                    state: bar.state as u32,
+                    state,
                 }
             })
             .collect();
 
+        // Graph algorithms replace the array with node pillars and edge lines on the floor
+        let mut line_vertices: Vec<LineVertex> = Vec::new();
+        if algorithm.is_graph() {
+            instances.clear();
+            let to_world = |p: [f32; 2]| (-1.8 + 3.6 * p[0], -1.2 + 1.6 * p[1]);
+            // Pillar height grows with the node's known distance from the start
+            let max_dist = graph.dist.iter().flatten().copied().max().unwrap_or(0).max(1) as f32;
+            for (node, &position) in graph.graph.positions.iter().enumerate() {
+                let (x, z) = to_world(position);
+                let height = match graph.dist[node] {
+                    Some(d) if graph.nodes[node] != NodeState::Unseen => 0.08 + 0.6 * d as f32 / max_dist,
+                    _ => 0.08,
+                };
+                let is_endpoint = match algorithm {
+                    Algorithm::Kruskal => false,
+                    Algorithm::Prim => node == graph.graph.start,
+                    _ => node == graph.graph.start || node == graph.graph.goal,
+                };
+                let state = match graph.nodes[node] {
+                    NodeState::Path => SEARCH_WINDOW,
+                    NodeState::Current => BarState::Swap as u32,
+                    NodeState::Frontier => BarState::Compare as u32,
+                    _ if is_endpoint => BarState::Source as u32,
+                    NodeState::Visited => BarState::Sorted as u32,
+                    NodeState::Unseen => BarState::Idle as u32,
+                };
+                instances.push(Instance { offset: x, height, z, state });
+            }
+            for (edge, &edge_state) in graph.graph.edges.iter().zip(&graph.edges) {
+                let color = match edge_state {
+                    EdgeState::Idle => [0.35, 0.40, 0.55, 0.45],
+                    EdgeState::Active => [1.00, 0.75, 0.35, 1.00],
+                    EdgeState::Tree => [0.65, 1.00, 0.75, 0.85],
+                    EdgeState::Path => [1.00, 0.90, 0.55, 1.00],
+                };
+                for node in [edge.a, edge.b] {
+                    let (x, z) = to_world(graph.graph.positions[node]);
+                    line_vertices.push(LineVertex { position: [x, 0.01, z], color });
+                }
+            }
+
+            // Union-find parent array as an auxiliary bar row in front of the graph:
+            // height is the parent index, roots glow as settled
+            if let Some(uf) = &graph.union_find {
+                let n = uf.parent.len().max(1) as f32;
+                for (node, &parent) in uf.parent.iter().enumerate() {
+                    let state = if uf.changed == Some(node) {
+                        BarState::Swap as u32
+                    } else if uf.active == Some(node) {
+                        BarState::Compare as u32
+                    } else if parent == node {
+                        BarState::Sorted as u32
+                    } else {
+                        BarState::TempArray as u32
+                    };
+                    instances.push(Instance {
+                        offset: -1.8 + 3.6 * (node as f32 + 0.5) / n,
+                        height: 0.05 + 0.45 * (parent as f32 + 1.0) / n,
+                        z: 0.75,
+                        state,
+                    });
+                }
+            }
+        }
+
+        // DP tables replace the array with a grid of cells on the floor, row 0 furthest back
+        if algorithm.is_dp() {
+            instances.clear();
+            let table = &dp.table;
+            let max_cell = table.cells.iter().flatten().copied().max().unwrap_or(0).max(1) as f32;
+            let left = -bar_width * table.cols as f32 / 2.0;
+            let back = -1.2;
+            for row in 0..table.rows {
+                for col in 0..table.cols {
+                    let idx = row * table.cols + col;
+                    let cell = table.get(row, col);
+                    let state = if dp.written == Some((row, col)) {
+                        BarState::Swap as u32
+                    } else if dp.reads.contains(&(row, col)) {
+                        BarState::Compare as u32
+                    } else if dp.trace[idx] {
+                        38
+                    } else if cell.is_some() {
+                        BarState::Idle as u32
+                    } else {
+                        39
+                    };
+                    instances.push(Instance {
+                        offset: left + bar_width * (col as f32 + 0.5),
+                        height: 0.03 + 0.8 * cell.unwrap_or(0) as f32 / max_cell,
+                        z: back + bar_width * (row as f32 + 0.5),
+                        state,
+                    });
+                }
+            }
+        }
+
+        // String matching: text row at the back, the pattern sliding in front of it,
+        // and the auxiliary array (failure function, Z-array, hashes, suffix array) nearest the camera
+        if algorithm.is_text() {
+            instances.clear();
+            let n = text.text.len().max(1);
+            let m = text.pattern.len();
+            let spacing = 3.6 / n as f32;
+            let char_height = |c: u8| 0.15 + 0.2 * (c - b'A') as f32;
+            let compared_state = |cell: Cell| match text.compared {
+                Some((a, b, equal)) if a == cell || b == cell => {
+                    Some(if equal { BarState::Compare as u32 } else { BarState::Swap as u32 })
+                }
+                _ => None,
+            };
+            for (i, &c) in text.text.iter().enumerate() {
+                let matched = text.matches.iter().any(|&s| i >= s && i < s + m);
+                let in_window = i >= text.shift && i < text.shift + m;
+                let state = compared_state(Cell::Text(i)).unwrap_or(if matched {
+                    BarState::Sorted as u32
+                } else if in_window {
+                    BarState::Idle as u32
+                } else {
+                    39
+                });
+                instances.push(Instance {
+                    offset: -1.8 + spacing * (i as f32 + 0.5),
+                    height: char_height(c),
+                    z: -0.5,
+                    state,
+                });
+            }
+            for (j, &c) in text.pattern.iter().enumerate() {
+                let state = compared_state(Cell::Pattern(j)).unwrap_or(BarState::Source as u32);
+                instances.push(Instance {
+                    offset: -1.8 + spacing * ((text.shift + j) as f32 + 0.5),
+                    height: char_height(c),
+                    z: -0.1,
+                    state,
+                });
+            }
+            if !text.aux.is_empty() {
+                let aux_spacing = 3.6 / text.aux.len() as f32;
+                let max_aux = text.aux.iter().flatten().copied().max().unwrap_or(0).max(1) as f32;
+                for (k, entry) in text.aux.iter().enumerate() {
+                    let state = if text.aux_active == Some(k) {
+                        BarState::Compare as u32
+                    } else if entry.is_some() {
+                        BarState::TempArray as u32
+                    } else {
+                        39
+                    };
+                    instances.push(Instance {
+                        offset: -1.8 + aux_spacing * (k as f32 + 0.5),
+                        height: 0.03 + 0.5 * entry.unwrap_or(0) as f32 / max_aux,
+                        z: 0.35,
+                        state,
+                    });
+                }
+            }
+        }
+
+        // Search tree: in-order position along x, depth towards the camera.
+        // Node positions glide to their new slots so rotations are visible as movement.
+        if algorithm.is_tree() {
+            instances.clear();
+            let nodes = &tree.shape.nodes;
+            let n = nodes.len().max(1) as f32;
+            let depth_step = (1.6 / tree.shape.height().max(1) as f32).min(0.3);
+            let blend = 1.0 - (-animation_speed * dt_secs).exp();
+            self.tree_positions.retain(|id, _| nodes.iter().any(|node| node.id == *id));
+            for (rank, node) in nodes.iter().enumerate() {
+                let target = [-1.8 + 3.6 * (rank as f32 + 0.5) / n, -1.0 + node.depth as f32 * depth_step];
+                // New nodes grow out of their parent's position
+                let start = node
+                    .parent
+                    .and_then(|p| self.tree_positions.get(&p).copied())
+                    .unwrap_or(target);
+                let position = self.tree_positions.entry(node.id).or_insert(start);
+                position[0] += (target[0] - position[0]) * blend;
+                position[1] += (target[1] - position[1]) * blend;
+            }
+            for node in nodes {
+                let [x, z] = self.tree_positions[&node.id];
+                let state = if tree.active == Some(node.id) {
+                    BarState::Compare as u32
+                } else if tree.rotated == Some(node.id) {
+                    BarState::Swap as u32
+                } else if tree.changed == Some(node.id) {
+                    BarState::Source as u32
+                } else if tree.found == Some(Some(node.id)) {
+                    BarState::Sorted as u32
+                } else if algorithm == Algorithm::RedBlackTree {
+                    if node.red { BarState::Thread0 as u32 } else { 39 }
+                } else {
+                    BarState::Idle as u32
+                };
+                instances.push(Instance {
+                    offset: x,
+                    height: 0.1 + 0.7 * node.key as f32 / 99.0,
+                    z,
+                    state,
+                });
+                if let Some(parent) = node.parent.and_then(|p| self.tree_positions.get(&p)) {
+                    let color = [0.35, 0.40, 0.55, 0.6];
+                    line_vertices.push(LineVertex { position: [x, 0.01, z], color });
+                    line_vertices.push(LineVertex { position: [parent[0], 0.01, parent[1]], color });
+                }
+            }
+        }
+
+        // Hash table: buckets along x, chains grow towards the camera.
+        // A table being rehashed stays visible behind the new one until it is freed.
+        if algorithm.is_hash() {
+            instances.clear();
+            let slot_instance = |chain: &[Slot], bucket: usize, capacity: usize, z: f32, instances: &mut Vec<Instance>, state_of: &dyn Fn(usize, Slot) -> u32| {
+                let x = -1.8 + 3.6 * (bucket as f32 + 0.5) / capacity as f32;
+                // Empty buckets still get a floor marker so the table size is visible
+                if chain.is_empty() {
+                    instances.push(Instance { offset: x, height: 0.02, z, state: OUTSIDE_WINDOW });
+                }
+                for (pos, &slot) in chain.iter().enumerate() {
+                    let height = match slot {
+                        Slot::Empty => 0.02,
+                        Slot::Tombstone => 0.05,
+                        Slot::Key(key) => 0.1 + 0.7 * key as f32 / 99.0,
+                    };
+                    instances.push(Instance { offset: x, height, z: z + pos as f32 * 0.3, state: state_of(pos, slot) });
+                }
+            };
+
+            if let Some(old) = &hash.old_buckets {
+                for (bucket, chain) in old.iter().enumerate() {
+                    slot_instance(chain, bucket, old.len(), -1.5, &mut instances, &|_, slot| match slot {
+                        Slot::Key(_) => BarState::TempArray as u32,
+                        _ => OUTSIDE_WINDOW,
+                    });
+                }
+            }
+
+            let capacity = hash.buckets.len();
+            for (bucket, chain) in hash.buckets.iter().enumerate() {
+                slot_instance(chain, bucket, capacity, -0.8, &mut instances, &|pos, slot| {
+                    if hash.probed == Some((bucket, pos)) {
+                        BarState::Compare as u32
+                    } else if hash.written == Some((bucket, pos)) {
+                        BarState::Swap as u32
+                    } else if hash.found == Some(Some((bucket, pos))) {
+                        BarState::Sorted as u32
+                    } else {
+                        match slot {
+                            Slot::Empty => OUTSIDE_WINDOW,
+                            // Bright red marker for deleted slots
+                            Slot::Tombstone => THREAD_UNDERLINES,
+                            // Stability tint ramp: blue at home, red after 8+ probes
+                            Slot::Key(key) => STABILITY_TINTS + (hash.probe_length(key, bucket, pos) - 1).min(7) as u32,
+                        }
+                    }
+                });
+            }
+
+            // Home bucket of the current key, drawn in front of the table with a line to the probe
+            if let Some(home) = hash.home {
+                let x = -1.8 + 3.6 * (home as f32 + 0.5) / capacity as f32;
+                instances.push(Instance { offset: x, height: 0.03, z: -1.1, state: BarState::Source as u32 });
+                if let Some((bucket, pos)) = hash.probed {
+                    let color = [0.95, 0.85, 0.4, 0.8];
+                    let probe_x = -1.8 + 3.6 * (bucket as f32 + 0.5) / capacity as f32;
+                    line_vertices.push(LineVertex { position: [x, 0.01, -1.1], color });
+                    line_vertices.push(LineVertex { position: [probe_x, 0.01, -0.8 + pos as f32 * 0.3], color });
+                }
+            }
+        }
+
         // In parallel mode, add underline bars to show thread ownership
-        if matches!(mode, SortMode::Parallel) {
+        if matches!(mode, SortMode::Parallel) && !algorithm.is_graph() && !algorithm.is_dp() && !algorithm.is_text() && !algorithm.is_tree() && !algorithm.is_hash() && !algorithm.is_network() {
             let n = bars.len();
-            let base_chunk_size = (n + 7) / 8; // Initial segment size for 8 threads
+            let base_chunk_size = n.div_ceil(8); // Initial segment size for 8 threads
             
             // Segment size doubles with each merge level
             // Level 0: base_chunk_size (8 threads each with 1 chunk)
@@ -1170,7 +2268,6 @@ impl<'a> Renderer<'a> {
             // Level 2: base_chunk_size * 4 (2 threads each with 4 chunks merged)
             // Level 3: base_chunk_size * 8 (1 thread with all chunks merged)
             let segment_size = base_chunk_size * (1 << merge_level); // 2^merge_level
-            let num_segments = (n + segment_size - 1) / segment_size; // Ceiling division
             
             for i in 0..n {
                 // Calculate which segment this element belongs to
@@ -1188,11 +2285,66 @@ impl<'a> Renderer<'a> {
                     offset,
                     height: 0.03, // Slightly thicker for visibility
                     z,
-                    state: 22 + thread_id as u32, // Saturated underline color
+                    state: THREAD_UNDERLINES + thread_id as u32, // Saturated underline color
+                });
+            }
+        }
+
+        // Priority queue: mirror the flat array as a binary tree to the right,
+        // one row per level with the root furthest back
+        if algorithm == Algorithm::PriorityQueue {
+            let tree_left = 0.1;
+            let tree_width = 2.0;
+            for (i, bar) in bars.iter().enumerate() {
+                let depth = (usize::BITS - 1 - (i + 1).leading_zeros()) as i32;
+                let level_count = 1usize << depth;
+                let pos = i + 1 - level_count;
+                let offset = tree_left + tree_width * (pos as f32 + 0.5) / level_count as f32;
+                let z = -0.6 + depth as f32 * 0.3;
+                instances.push(Instance {
+                    offset,
+                    height: self.animated_heights[i].clamp(0.0, 1.0),
+                    z,
+                    state: bar.state as u32,
+                });
+            }
+        }
+
+        // Underline the active search window [lo, hi]
+        if let Some((lo, hi)) = search.window {
+            for i in lo..=hi.min(bars.len() - 1) {
+                let t = if count > 1.0 { i as f32 / (count - 1.0) } else { 0.5 };
+                let offset = main_array_offset + (-1.0 + bar_width * (i as f32 + 0.5));
+                let z = (t - 0.5) * z_span + 0.15;
+                instances.push(Instance {
+                    offset,
+                    height: 0.03,
+                    z,
+                    state: SEARCH_WINDOW,
                 });
             }
         }
 
+        // Recursion stack as brackets above the bars, one row per depth;
+        // each thread's innermost call, the last of its thread in the stack, is drawn solid, its callers faded
+        for (k, call) in active_calls.iter().enumerate().filter(|(_, c)| c.right <= bars.len()) {
+            let innermost = active_calls.get(k + 1).is_none_or(|next| next.thread_id != call.thread_id);
+            let [r, g, b, _] = self.themes[self.theme].state_color(call_state(mode, call.thread_id));
+            let color = [r, g, b, if innermost { 1.0 } else { 0.45 }];
+            let end = |i: usize, side: f32| {
+                let t = if count > 1.0 { i as f32 / (count - 1.0) } else { 0.5 };
+                let x = main_array_offset + (-1.0 + bar_width * (i as f32 + 0.5)) + side * bar_width * 0.45;
+                (x, (t - 0.5) * z_span)
+            };
+            let (x0, z0) = end(call.left, -1.0);
+            let (x1, z1) = end(call.right - 1, 1.0);
+            let y = 1.35 + call.depth as f32 * 0.08;
+            for (a, b) in [([x0, y - 0.05, z0], [x0, y, z0]), ([x0, y, z0], [x1, y, z1]), ([x1, y, z1], [x1, y - 0.05, z1])] {
+                line_vertices.push(LineVertex { position: a, color });
+                line_vertices.push(LineVertex { position: b, color });
+            }
+        }
+
         // Add temp array visualization (bars to the right, same height as main)
         // Position temp array on the right side with gap from main array
         let temp_x_offset = main_array_offset + 1.8; // To the right of shifted main array
@@ -1331,6 +2483,48 @@ impl<'a> Renderer<'a> {
             }
         }
 
+        // Main-array bars are the first instances of array algorithms; others can't be inspected
+        let half_width = bar_width * 0.35;
+        self.pick_boxes.clear();
+        if algorithm.plays_actions() {
+            self.pick_boxes.extend(instances[..bars.len()].iter().map(|inst| {
+                let top = (inst.height * 1.25).max(0.02);
+                (Vec3::new(inst.offset - half_width, 0.0, inst.z - half_width), Vec3::new(inst.offset + half_width, top, inst.z + half_width))
+            }));
+        }
+
+        // Pinned element: every move so far as an arc on the floor in front of the array, fading with age,
+        // and a marker above the bar it occupies now
+        if let Some(origin) = self.pinned.filter(|_| algorithm.plays_actions()) {
+            let [r, g, b, _] = theme.state_color(PINNED);
+            let path: Vec<usize> = element_path(history, origin).into_iter().filter(|&i| i < bars.len()).collect();
+            let front = half_width + 0.03;
+            let hops = path.len().saturating_sub(1);
+            for (hop, pair) in path.windows(2).enumerate() {
+                let alpha = 0.25 + 0.75 * (hop + 1) as f32 / hops as f32;
+                let ((x0, z0), (x1, z1)) = (slot(pair[0]), slot(pair[1]));
+                let bulge = 0.5 * (x1 - x0).abs().min(1.2);
+                let point = |k: usize| {
+                    let t = k as f32 / 16.0;
+                    let lift = bulge * (std::f32::consts::PI * t).sin();
+                    [x0 + (x1 - x0) * t, 0.012, z0 + (z1 - z0) * t + front + lift]
+                };
+                for k in 0..16 {
+                    line_vertices.push(LineVertex { position: point(k), color: [r, g, b, alpha] });
+                    line_vertices.push(LineVertex { position: point(k + 1), color: [r, g, b, alpha] });
+                }
+            }
+            if let Some(inst) = path.last().and_then(|&i| instances.get(i)) {
+                let top = inst.height * 1.25 + 0.04;
+                let color = [r, g, b, 1.0];
+                let tip = [inst.offset, top, inst.z];
+                for [dx, dy] in [[0.0, 0.2], [-0.04, 0.05], [0.04, 0.05]] {
+                    line_vertices.push(LineVertex { position: tip, color });
+                    line_vertices.push(LineVertex { position: [inst.offset + dx, top + dy, inst.z], color });
+                }
+            }
+        }
+
         let required_bytes = instances.len() as u64 * mem::size_of::<Instance>() as u64;
         if required_bytes > self.instance_buffer.size() {
             self.instance_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
@@ -1342,27 +2536,49 @@ impl<'a> Renderer<'a> {
         }
         self.queue
             .write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
+
+        let required_bytes = line_vertices.len() as u64 * mem::size_of::<LineVertex>() as u64;
+        if required_bytes > self.line_vertex_buffer.size() {
+            self.line_vertex_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("LineVertexBufferDynamic"),
+                size: required_bytes.next_power_of_two(),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+        }
+        self.queue
+            .write_buffer(&self.line_vertex_buffer, 0, bytemuck::cast_slice(&line_vertices));
+        // Follow target: centre of the bars in an action state (compare, swap, source, thread colors)
+        let threads = BarState::Thread0 as u32..=BarState::Thread7 as u32;
+        let is_active = |state: u32| {
+            [BarState::Compare, BarState::Swap, BarState::Source].iter().any(|s| *s as u32 == state) || threads.contains(&state)
+        };
+        let active: Vec<Vec3> = instances
+            .iter()
+            .filter(|inst| is_active(inst.state))
+            .map(|inst| Vec3::new(inst.offset, inst.height * 1.25 * 0.5, inst.z))
+            .collect();
+        let active_centre = (!active.is_empty()).then(|| active.iter().sum::<Vec3>() / active.len() as f32);
+        self.camera.update(dt_secs, active_centre);
         let aspect = self.size.width as f32 / self.size.height as f32;
-        let eye = Vec3::new(0.0, 1.2, 2.6);
-        let target = Vec3::new(0.0, 0.6, 0.0);
-        let view = Mat4::look_at_rh(eye, target, Vec3::Y);
-        let fov_y = 50f32.to_radians();
-        let near = 0.1;
-        let far = 10.0;
-        let proj = Mat4::perspective_rh(fov_y, aspect, near, far);
-        let view_proj = proj * view;
+        let view_proj = self.camera.view_proj(aspect);
 
         let globals = Globals {
             view_proj: view_proj.to_cols_array_2d(),
             bar_width,
             max_value: max_val,
-            focus_distance: 2.3,
-            focus_range: 2.5,
+            _padding: [0.0; 2],
         };
         self.queue
             .write_buffer(&self.globals_buffer, 0, bytemuck::bytes_of(&globals));
+        self.queue
+            .write_buffer(&self.theme_buffer, 0, bytemuck::bytes_of(&self.themes[self.theme].uniform()));
+        self.queue
+            .write_buffer(&self.post_params_buffer, 0, bytemuck::bytes_of(&PostParams::from(&self.post)));
 
         // Scene pass: floor into floor texture, then bars into full texture
+        let [r, g, b] = self.themes[self.theme].background;
+        let background = wgpu::Color { r: r as f64, g: g as f64, b: b as f64, a: 1.0 };
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("SceneEncoder") });
@@ -1375,7 +2591,7 @@ impl<'a> Renderer<'a> {
                     view: &self.scene_floor_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.02, g: 0.02, b: 0.04, a: 1.0 }),
+                        load: wgpu::LoadOp::Clear(background),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -1433,6 +2649,14 @@ impl<'a> Renderer<'a> {
                 timestamp_writes: None,
             });
 
+            // Edges first so the glass pillars blend over them
+            if !line_vertices.is_empty() {
+                render_pass.set_pipeline(&self.line_pipeline);
+                render_pass.set_bind_group(0, &self.globals_bind, &[]);
+                render_pass.set_vertex_buffer(0, self.line_vertex_buffer.slice(..));
+                render_pass.draw(0..line_vertices.len() as u32, 0..1);
+            }
+
             render_pass.set_pipeline(&self.bar_pipeline);
             render_pass.set_bind_group(0, &self.globals_bind, &[]);
             render_pass.set_bind_group(1, &self.bar_floor_bind, &[]);
@@ -1444,27 +2668,34 @@ impl<'a> Renderer<'a> {
 
         self.queue.submit(std::iter::once(encoder.finish()));
 
-        let output = match self.surface.get_current_texture() {
-            Ok(frame) => frame,
-            Err(wgpu::SurfaceError::Lost) => {
-                self.resize(self.size);
-                return Ok(None);
-            }
-            Err(wgpu::SurfaceError::OutOfMemory) => {
-                return Err(anyhow::anyhow!("Surface out of memory"));
-            }
-            Err(err) => {
-                eprintln!("Surface error: {err:?}");
-                return Ok(None);
-            }
+        let output = match &self.surface {
+            Some(surface) => match surface.get_current_texture() {
+                Ok(frame) => Some(frame),
+                Err(wgpu::SurfaceError::Lost) => {
+                    self.resize(self.size);
+                    return Ok(requests);
+                }
+                Err(wgpu::SurfaceError::OutOfMemory) => {
+                    return Err(anyhow::anyhow!("Surface out of memory"));
+                }
+                Err(err) => {
+                    eprintln!("Surface error: {err:?}");
+                    return Ok(requests);
+                }
+            },
+            None => None,
+        };
+        let swap_view = match (&output, &self.offscreen) {
+            (Some(frame), _) => frame.texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            (None, Some(texture)) => texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            (None, None) => return Err(anyhow::anyhow!("Renderer has neither a surface nor an offscreen target")),
         };
-        let swap_view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("PostEncoder") });
 
-        // Blur passes (downsampled)
-        {
+        // Blur passes (downsampled); skipped entirely with post-processing off
+        if self.post.enabled {
             let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("BlurH"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -1481,11 +2712,12 @@ impl<'a> Renderer<'a> {
             });
             pass.set_pipeline(&self.blur_pipeline_h);
             pass.set_bind_group(0, &self.blur_from_scene_bind, &[]);
+            pass.set_bind_group(1, &self.post_params_bind, &[]);
             pass.set_vertex_buffer(0, self.fullscreen_buffer.slice(..));
             pass.draw(0..6, 0..1);
         }
 
-        {
+        if self.post.enabled {
             let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("BlurV"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -1502,6 +2734,7 @@ impl<'a> Renderer<'a> {
             });
             pass.set_pipeline(&self.blur_pipeline_v);
             pass.set_bind_group(0, &self.blur_from_a_bind, &[]);
+            pass.set_bind_group(1, &self.post_params_bind, &[]);
             pass.set_vertex_buffer(0, self.fullscreen_buffer.slice(..));
             pass.draw(0..6, 0..1);
         }
@@ -1524,6 +2757,7 @@ impl<'a> Renderer<'a> {
             });
             pass.set_pipeline(&self.tonemap_pipeline);
             pass.set_bind_group(0, &self.tonemap_bind, &[]);
+            pass.set_bind_group(1, &self.post_params_bind, &[]);
             pass.set_vertex_buffer(0, self.fullscreen_buffer.slice(..));
             pass.draw(0..6, 0..1);
         }
@@ -1531,7 +2765,7 @@ impl<'a> Renderer<'a> {
         // Egui render
         let screen_descriptor = egui_wgpu::ScreenDescriptor {
             size_in_pixels: [self.config.width, self.config.height],
-            pixels_per_point: window.scale_factor() as f32,
+            pixels_per_point: window.map_or(full_output.pixels_per_point, |window| window.scale_factor() as f32),
         };
 
         let paint_jobs = self.egui_ctx.tessellate(full_output.shapes, full_output.pixels_per_point);
@@ -1570,11 +2804,168 @@ impl<'a> Renderer<'a> {
         }
 
         self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
-        Ok(mode_changed)
+        if let Some(output) = output {
+            output.present();
+        }
+        Ok(requests)
+    }
+
+    /// Copy the last offscreen frame back from the GPU
+    pub fn read_frame(&self) -> Result<image::RgbaImage> {
+        let texture = self
+            .offscreen
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Only offscreen renderers can read frames back"))?;
+        let (width, height) = (self.size.width, self.size.height);
+        // Buffer rows must be padded to the copy alignment; the padding is dropped below
+        let row_bytes = width * 4;
+        let padded_row_bytes = row_bytes.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("FrameReadback"),
+            size: padded_row_bytes as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("ReadbackEncoder") });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+
+        let mut pixels = Vec::with_capacity((row_bytes * height) as usize);
+        for row in slice.get_mapped_range().chunks(padded_row_bytes as usize) {
+            pixels.extend_from_slice(&row[..row_bytes as usize]);
+        }
+        buffer.unmap();
+        image::RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| anyhow::anyhow!("Frame readback has the wrong size"))
+    }
+}
+
+/// Index of the nearest box the ray hits (slab test), if any
+fn pick(boxes: &[(Vec3, Vec3)], origin: Vec3, direction: Vec3) -> Option<usize> {
+    let inverse = direction.recip();
+    boxes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &(min, max))| {
+            let (a, b) = ((min - origin) * inverse, (max - origin) * inverse);
+            let near = a.min(b).max_element();
+            let far = a.max(b).min_element();
+            (near <= far && far >= 0.0).then_some((index, near))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index)
+}
+
+/// State whose theme color marks an active call: its thread's color in parallel mode, the highlight accent otherwise
+fn call_state(mode: SortMode, thread_id: usize) -> u32 {
+    match mode {
+        SortMode::Parallel => BarState::from_thread_id(thread_id % 8) as u32,
+        SortMode::Sequential => SEARCH_WINDOW,
+    }
+}
+
+/// Wires run left to right, one per array index; each layer is a column of vertical comparators.
+/// Comparators of a layer whose spans overlap are spread over adjacent lanes so none hide each other.
+fn draw_network(ui: &mut egui::Ui, layers: &[Vec<Comparator>], wires: usize, current: Option<usize>, finished: bool) {
+    const WIRE_GAP: f32 = 14.0;
+    const LANE_GAP: f32 = 5.0;
+    const LAYER_GAP: f32 = 10.0;
+
+    // Greedy lane assignment: (layer, lane, i, j), plus the lane count of every layer
+    let mut placed = Vec::new();
+    let mut lane_counts = Vec::new();
+    for (layer, comparators) in layers.iter().enumerate() {
+        let mut lanes: Vec<Vec<Comparator>> = Vec::new();
+        for &(i, j) in comparators {
+            let lane = lanes
+                .iter()
+                .position(|lane| lane.iter().all(|&(a, b)| b < i || a > j))
+                .unwrap_or_else(|| {
+                    lanes.push(Vec::new());
+                    lanes.len() - 1
+                });
+            lanes[lane].push((i, j));
+            placed.push((layer, lane, i, j));
+        }
+        lane_counts.push(lanes.len());
+    }
+    let mut layer_x = Vec::with_capacity(layers.len());
+    let mut width = LAYER_GAP;
+    for &lanes in &lane_counts {
+        layer_x.push(width);
+        width += lanes as f32 * LANE_GAP + LAYER_GAP;
+    }
+
+    let size = egui::vec2(width, WIRE_GAP * wires as f32);
+    let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+    let rect = response.rect;
+    let wire_y = |wire: usize| rect.top() + WIRE_GAP * (wire as f32 + 0.5);
+
+    if let Some(layer) = current {
+        let column = egui::Rect::from_min_size(
+            egui::pos2(rect.left() + layer_x[layer] - LANE_GAP / 2.0, rect.top()),
+            egui::vec2(lane_counts[layer] as f32 * LANE_GAP, rect.height()),
+        );
+        painter.rect_filled(column, 2.0, egui::Color32::from_rgba_unmultiplied(255, 230, 140, 40));
+    }
+    for wire in 0..wires {
+        let y = wire_y(wire);
+        painter.line_segment([egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)], egui::Stroke::new(1.0, egui::Color32::from_gray(90)));
+    }
+    for (layer, lane, i, j) in placed {
+        let color = match current {
+            Some(c) if layer == c => egui::Color32::from_rgb(255, 230, 140),
+            Some(c) if layer < c => egui::Color32::from_rgb(110, 160, 230),
+            None if finished => egui::Color32::from_rgb(110, 160, 230),
+            _ => egui::Color32::from_gray(150),
+        };
+        let x = rect.left() + layer_x[layer] + lane as f32 * LANE_GAP;
+        painter.line_segment([egui::pos2(x, wire_y(i)), egui::pos2(x, wire_y(j))], egui::Stroke::new(1.5, color));
+        painter.circle_filled(egui::pos2(x, wire_y(i)), 2.5, color);
+        painter.circle_filled(egui::pos2(x, wire_y(j)), 2.5, color);
     }
 }
 
+/// Final color target of an offscreen renderer, copied out by `read_frame`
+fn create_frame_target(device: &wgpu::Device, size: PhysicalSize<u32>, format: wgpu::TextureFormat) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("OffscreenFrame"),
+        size: wgpu::Extent3d {
+            width: size.width.max(1),
+            height: size.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
 fn create_color_target(
     device: &wgpu::Device,
     size: PhysicalSize<u32>,
@@ -0,0 +1,272 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::engine::{Bar, BarState, Engine, MultiTempArrayState, SortMode, TempArrayState};
+use crate::theme::{channel, Theme};
+
+const WIDTH: f32 = 960.0;
+const HEIGHT: f32 = 480.0;
+const BASELINE: f32 = 440.0;
+const BAR_AREA: f32 = 340.0; // Height of the tallest bar
+const MAIN_RIGHT: f32 = 620.0;
+const TEMP_LEFT: f32 = 660.0;
+const MARGIN: f32 = 20.0;
+
+/// Everything one SVG frame shows, taken from the engine after some number of actions
+pub struct Snapshot<'a> {
+    pub bars: &'a [Bar],
+    pub max_value: u32,
+    pub mode: SortMode,
+    pub temp_array: &'a TempArrayState,
+    pub multi_temp_arrays: &'a MultiTempArrayState,
+    pub stats: Vec<String>, // Text lines above the bars
+    pub theme: &'a Theme,
+}
+
+impl<'a> Snapshot<'a> {
+    pub fn from_engine(engine: &'a Engine, theme: &'a Theme) -> Self {
+        let (bars, max_value) = engine.bars();
+        let mut stats = vec![
+            format!("{} · action {}", engine.algorithm.label(), engine.played()),
+            format!(
+                "comparisons {} · operations {} · memory ops {} · memory {} B (peak {} B)",
+                engine.comparisons, engine.operations, engine.memory_ops, engine.current_memory, engine.peak_memory
+            ),
+        ];
+        if let Some(note) = engine.narration(1).first() {
+            stats.push(note.to_string());
+        }
+        Self {
+            bars,
+            max_value,
+            mode: engine.mode,
+            temp_array: &engine.temp_array,
+            multi_temp_arrays: &engine.multi_temp_arrays,
+            stats,
+            theme,
+        }
+    }
+
+    pub fn to_svg(&self) -> String {
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="monospace">"#
+        );
+        let [r, g, b] = self.theme.background;
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="rgb({},{},{})"/>"#, channel(r), channel(g), channel(b));
+        for (line, text) in self.stats.iter().enumerate() {
+            let _ = writeln!(
+                svg,
+                r##"<text x="{MARGIN}" y="{}" font-size="13" fill="#d8dce8">{}</text>"##,
+                24.0 + line as f32 * 18.0,
+                escape(text)
+            );
+        }
+
+        let max_value = self.max_value.max(1) as f32;
+        let states: Vec<u32> = self.bars.iter().map(|bar| bar.state as u32).collect();
+        let values: Vec<u32> = self.bars.iter().map(|bar| bar.value).collect();
+        BarRow { values: &values, states: &states, left: MARGIN, right: MAIN_RIGHT, baseline: BASELINE, scale: BAR_AREA / max_value }
+            .draw(&mut svg, self.theme);
+
+        // Temp arrays sit right of the main array, scaled like it; parallel threads get one row each
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{0}" y1="{1}" x2="{0}" y2="{BASELINE}" stroke="#303040" stroke-dasharray="4 4"/>"##,
+            (MAIN_RIGHT + TEMP_LEFT) / 2.0,
+            BASELINE - BAR_AREA
+        );
+        let slot = (MAIN_RIGHT - MARGIN) / self.bars.len().max(1) as f32;
+        match self.mode {
+            SortMode::Sequential => {
+                let temp = &self.temp_array.values;
+                let states = vec![BarState::TempArray as u32; temp.len()];
+                let right = TEMP_LEFT + slot * temp.len() as f32;
+                BarRow { values: temp, states: &states, left: TEMP_LEFT, right: right.min(WIDTH - MARGIN), baseline: BASELINE, scale: BAR_AREA / max_value }
+                    .draw(&mut svg, self.theme);
+            }
+            SortMode::Parallel => {
+                let rows = self.multi_temp_arrays.arrays.len().max(1);
+                let row_height = BAR_AREA / rows as f32;
+                for (thread_id, temp) in self.multi_temp_arrays.arrays.iter().enumerate() {
+                    let baseline = BASELINE - row_height * (rows - 1 - thread_id) as f32;
+                    let states = vec![BarState::temp_array_for_thread(thread_id); temp.values.len()];
+                    let right = TEMP_LEFT + slot * temp.values.len() as f32;
+                    let scale = (row_height - 4.0) / max_value;
+                    BarRow { values: &temp.values, states: &states, left: TEMP_LEFT, right: right.min(WIDTH - MARGIN), baseline, scale }
+                        .draw(&mut svg, self.theme);
+                }
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Bars spread evenly over [left, right], standing on `baseline`, `scale` pixels per unit value
+struct BarRow<'a> {
+    values: &'a [u32],
+    states: &'a [u32], // Bar state code per value
+    left: f32,
+    right: f32,
+    baseline: f32,
+    scale: f32,
+}
+
+impl BarRow<'_> {
+    fn draw(&self, svg: &mut String, theme: &Theme) {
+        if self.values.is_empty() {
+            return;
+        }
+        let slot = (self.right - self.left) / self.values.len() as f32;
+        // Same 0.7 footprint as the 3D bars, leaving visible gaps
+        let width = slot * 0.7;
+        for (i, (&value, &state)) in self.values.iter().zip(self.states).enumerate() {
+            let [r, g, b, a] = theme.state_color(state);
+            let height = (value as f32 * self.scale).max(1.0);
+            let x = self.left + slot * i as f32 + (slot - width) / 2.0;
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{x:.1}" y="{:.1}" width="{width:.1}" height="{height:.1}" rx="2" fill="rgb({},{},{})" fill-opacity="{:.2}" stroke="rgb({},{},{})"><title>{value}</title></rect>"#,
+                self.baseline - height,
+                channel(r),
+                channel(g),
+                channel(b),
+                // Glass bars read too faint as flat fills; keep the palette's ordering of emphasis
+                0.35 + a * 0.65,
+                channel(r),
+                channel(g),
+                channel(b),
+            );
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Write `action_<n>.svg` for the state after `action` actions
+pub fn export_action(engine: &mut Engine, theme: &Theme, dir: &Path, action: usize) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    engine.seek(action);
+    let path = dir.join(format!("action_{action:05}.svg"));
+    fs::write(&path, Snapshot::from_engine(engine, theme).to_svg()).with_context(|| format!("cannot write {}", path.display()))?;
+    eprintln!("Wrote {}", path.display());
+    Ok(())
+}
+
+/// Write one SVG per step of the whole run, plus flipbook.html that pages through them
+pub fn export_flipbook(engine: &mut Engine, theme: &Theme, dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    engine.replay();
+    let mut frames = Vec::new();
+    loop {
+        let svg = Snapshot::from_engine(engine, theme).to_svg();
+        let path = dir.join(format!("step_{:05}.svg", frames.len()));
+        fs::write(&path, &svg).with_context(|| format!("cannot write {}", path.display()))?;
+        frames.push(svg);
+        if engine.is_finished() {
+            break;
+        }
+        engine.step(engine.step_delay());
+    }
+
+    let path = dir.join("flipbook.html");
+    fs::write(&path, flipbook(engine.algorithm.label(), &frames)).with_context(|| format!("cannot write {}", path.display()))?;
+    eprintln!("Wrote {} steps and {}", frames.len(), path.display());
+    Ok(())
+}
+
+/// Self-contained page with every frame inlined: arrow keys or the slider step, space plays
+fn flipbook(title: &str, frames: &[String]) -> String {
+    let mut html = String::new();
+    let _ = writeln!(html, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>", escape(title));
+    html.push_str(
+        "<style>\n\
+         body { background: #05050a; color: #d8dce8; font-family: monospace; margin: 20px; }\n\
+         .frame { display: none; }\n\
+         .frame.shown { display: block; }\n\
+         input[type=range] { width: 640px; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+    let _ = writeln!(
+        html,
+        "<div><button id=\"prev\">&#9664;</button> <button id=\"play\">play</button> <button id=\"next\">&#9654;</button> \
+         <input id=\"slider\" type=\"range\" min=\"0\" max=\"{}\" value=\"0\"> <span id=\"label\"></span></div>",
+        frames.len().saturating_sub(1)
+    );
+    for (i, frame) in frames.iter().enumerate() {
+        let _ = write!(html, "<div class=\"frame{}\">\n{}</div>\n", if i == 0 { " shown" } else { "" }, frame);
+    }
+    html.push_str(
+        "<script>\n\
+         const frames = document.querySelectorAll('.frame');\n\
+         const slider = document.getElementById('slider');\n\
+         const label = document.getElementById('label');\n\
+         let current = 0, timer = null;\n\
+         function show(i) {\n\
+         \x20 current = Math.max(0, Math.min(frames.length - 1, i));\n\
+         \x20 frames.forEach((f, j) => f.classList.toggle('shown', j === current));\n\
+         \x20 slider.value = current;\n\
+         \x20 label.textContent = `step ${current} / ${frames.length - 1}`;\n\
+         }\n\
+         function toggle() {\n\
+         \x20 if (timer) { clearInterval(timer); timer = null; return; }\n\
+         \x20 if (current === frames.length - 1) show(0);\n\
+         \x20 timer = setInterval(() => { show(current + 1); if (current === frames.length - 1) toggle(); }, 400);\n\
+         }\n\
+         document.getElementById('prev').onclick = () => show(current - 1);\n\
+         document.getElementById('next').onclick = () => show(current + 1);\n\
+         document.getElementById('play').onclick = toggle;\n\
+         slider.oninput = () => show(Number(slider.value));\n\
+         document.addEventListener('keydown', e => {\n\
+         \x20 if (e.key === 'ArrowLeft') show(current - 1);\n\
+         \x20 if (e.key === 'ArrowRight') show(current + 1);\n\
+         \x20 if (e.key === ' ') { e.preventDefault(); toggle(); }\n\
+         });\n\
+         show(0);\n\
+         </script>\n</body>\n</html>\n",
+    );
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Algorithm;
+
+    /// Values in the tooltips of the drawn bars, left to right
+    fn drawn_values(svg: &str) -> Vec<u32> {
+        svg.lines()
+            .filter(|line| line.starts_with("<rect x="))
+            .map(|line| line.split("<title>").nth(1).and_then(|rest| rest.split("</title>").next()).unwrap().parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn snapshot_draws_one_rect_per_bar_and_temp_entry() {
+        let theme = Theme::default();
+        for algorithm in [Algorithm::BitonicSort, Algorithm::MergeSort] {
+            let mut engine = Engine::new(12);
+            engine.set_algorithm(algorithm);
+            engine.seek(20);
+            if algorithm == Algorithm::MergeSort {
+                // Stop mid-merge so the temp array is drawn too
+                while engine.temp_array.values.is_empty() {
+                    engine.step(engine.step_delay());
+                }
+            }
+            let svg = Snapshot::from_engine(&engine, &theme).to_svg();
+            let mut expected: Vec<u32> = engine.bars().0.iter().map(|bar| bar.value).collect();
+            expected.extend(&engine.temp_array.values);
+            assert_eq!(drawn_values(&svg), expected, "{algorithm:?}");
+            assert!(svg.contains(&format!("action {}", engine.played())));
+            assert!(svg.ends_with("</svg>\n"));
+        }
+    }
+}
@@ -0,0 +1,482 @@
+use std::cmp::Ordering;
+
+use crate::algorithms::{annotate, Algorithm};
+use crate::engine::Step;
+
+/// A character position in the text or the pattern
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Text(usize),
+    Pattern(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextEventKind {
+    Align,                                     // Pattern placed at text offset i
+    Compare { a: Cell, b: Cell, equal: bool }, // Character comparison
+    HashCompare { equal: bool },               // Hash of the window at shift i compared with the pattern's
+    AuxRead,                                   // Auxiliary entry i read
+    AuxWrite,                                  // Auxiliary entry i set to value
+    Match,                                     // Pattern occurs at shift i
+    SpuriousHit,                               // Hash matched at shift i but the characters did not
+    Done,
+}
+
+#[derive(Clone, Debug)]
+pub struct TextEvent {
+    pub kind: TextEventKind,
+    pub i: usize,
+    pub value: u32,
+    pub note: Option<String>,
+    pub line: Option<usize>,
+}
+
+impl Step for TextEvent {
+    fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    fn note_mut(&mut self) -> &mut Option<String> {
+        &mut self.note
+    }
+
+    fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    fn columns(&self) -> String {
+        // Character comparisons name their cells in the kind column
+        format!("{:?}\t{}\t-\t{}", self.kind, self.i, self.value)
+    }
+}
+
+impl Cell {
+    fn label(self) -> String {
+        match self {
+            Cell::Text(i) => format!("text[{i}]"),
+            Cell::Pattern(j) => format!("pattern[{j}]"),
+        }
+    }
+}
+
+/// Listing the events' lines index into
+pub fn pseudocode(algorithm: Algorithm) -> &'static [&'static str] {
+    match algorithm {
+        Algorithm::NaiveMatch => &[
+            "for shift in 0..=n - m:",
+            "    j = 0; while j < m and text[shift + j] == pattern[j]: j += 1",
+            "    if j == m: report shift",
+        ],
+        Algorithm::Kmp => &[
+            "fail[0] = 0; k = 0",
+            "for i in 1..m:",
+            "    while pattern[i] != pattern[k]:",
+            "        if k == 0: break; k = fail[k - 1]",
+            "    fail[i] = k + 1 if pattern[i] == pattern[k] else 0",
+            "j = 0; for i in 0..n:",
+            "    while text[i] != pattern[j]:",
+            "        if j == 0: break; j = fail[j - 1]",
+            "    if they matched: j += 1; if j == m: report i + 1 - m, j = fail[m - 1]",
+        ],
+        Algorithm::ZFunction => &[
+            "s = pattern + '$' + text; Z[0] = 0; l = r = 0",
+            "for i in 1..len(s):",
+            "    z = min(r - i, Z[i - l]) if i < r else 0",
+            "    while s[z] == s[i + z]: z += 1",
+            "    Z[i] = z; if i + z > r: l, r = i, i + z",
+            "    if i > m and z == m: report i - m - 1",
+        ],
+        Algorithm::RabinKarp => &[
+            "target = hash(pattern); h = hash(text[..m])",
+            "for shift in 0..=n - m:",
+            "    if shift > 0: roll h, dropping text[shift - 1], adding text[shift + m - 1]",
+            "    if h == target:",
+            "        compare text[shift..shift + m] with pattern",
+            "        report shift if they are equal, else it is a spurious hit",
+        ],
+        Algorithm::SuffixArray => &[
+            "sa = [0, 1, ..., n - 1]",
+            "for i in 1..n, j from i down while text[sa[j]..] < text[sa[j - 1]..]:",
+            "    swap sa[j - 1], sa[j]",
+            "lo, hi = 0, n; while lo < hi:",
+            "    mid = (lo + hi) / 2; if pattern > text[sa[mid]..]: lo = mid + 1 else hi = mid",
+            "for idx in lo..n while text[sa[idx]..] starts with pattern:",
+            "    report sa[idx]",
+        ],
+        _ => &[],
+    }
+}
+
+// Rabin–Karp rolling hash; a small modulus makes spurious hits visible
+const HASH_BASE: u32 = 3;
+const HASH_MOD: u32 = 13;
+
+struct Recorder<'a> {
+    text: &'a [u8],
+    pattern: &'a [u8],
+    aux: Vec<u32>,
+    aux_label: &'static str,
+    events: Vec<TextEvent>,
+    line: Option<usize>, // Pseudocode line the next events execute
+}
+
+impl Recorder<'_> {
+    fn push(&mut self, kind: TextEventKind, i: usize, value: u32) {
+        self.events.push(TextEvent { kind, i, value, note: None, line: self.line });
+    }
+
+    /// Attribute the following events to pseudocode line `line`
+    fn at(&mut self, line: usize) {
+        self.line = Some(line);
+    }
+
+    /// Caption the event just recorded
+    fn note(&mut self, note: String) {
+        annotate(&mut self.events, note);
+    }
+
+    fn char_at(&self, cell: Cell) -> u8 {
+        match cell {
+            Cell::Text(i) => self.text[i],
+            Cell::Pattern(j) => self.pattern[j],
+        }
+    }
+
+    fn compare(&mut self, a: Cell, b: Cell) -> bool {
+        let (x, y) = (self.char_at(a) as char, self.char_at(b) as char);
+        let equal = x == y;
+        self.push(TextEventKind::Compare { a, b, equal }, 0, 0);
+        let verdict = if equal { "match" } else { "mismatch" };
+        self.note(format!("compare {}={x} with {}={y}: {verdict}", a.label(), b.label()));
+        equal
+    }
+
+    fn read(&mut self, i: usize) -> u32 {
+        self.push(TextEventKind::AuxRead, i, self.aux[i]);
+        self.note(format!("read {} entry {i} = {}", self.aux_label, self.aux[i]));
+        self.aux[i]
+    }
+
+    fn write(&mut self, i: usize, value: u32) {
+        self.aux[i] = value;
+        self.push(TextEventKind::AuxWrite, i, value);
+        self.note(format!("set {} entry {i} = {value}", self.aux_label));
+    }
+
+    fn found(&mut self, shift: usize) {
+        self.push(TextEventKind::Match, shift, 0);
+        self.note(format!("pattern occurs at shift {shift}"));
+    }
+}
+
+/// Generate the auxiliary array length, its label and the matching events
+pub fn match_events(algorithm: Algorithm, text: &[u8], pattern: &[u8]) -> (usize, &'static str, Vec<TextEvent>) {
+    let (n, m) = (text.len(), pattern.len());
+    let (aux_len, label) = match algorithm {
+        Algorithm::Kmp => (m, "failure function"),
+        Algorithm::ZFunction => (m + 1 + n, "Z-array of pattern$text"),
+        Algorithm::RabinKarp => ((n + 1).saturating_sub(m), "window hashes mod 13"),
+        Algorithm::SuffixArray => (n, "suffix array"),
+        _ => (0, "none"),
+    };
+    let mut rec = Recorder {
+        text,
+        pattern,
+        aux: vec![0; aux_len],
+        aux_label: label,
+        events: Vec::new(),
+        line: None,
+    };
+    if m > 0 && m <= n {
+        match algorithm {
+            Algorithm::Kmp => kmp(&mut rec),
+            Algorithm::ZFunction => z_algorithm(&mut rec),
+            Algorithm::RabinKarp => rabin_karp(&mut rec),
+            Algorithm::SuffixArray => suffix_array(&mut rec),
+            _ => naive(&mut rec),
+        }
+    }
+    rec.line = None;
+    rec.push(TextEventKind::Done, 0, 0);
+    let found = rec.events.iter().filter(|e| e.kind == TextEventKind::Match).count();
+    rec.note(format!("done: {found} occurrences"));
+    (aux_len, label, rec.events)
+}
+
+fn naive(rec: &mut Recorder) {
+    let (n, m) = (rec.text.len(), rec.pattern.len());
+    for shift in 0..=n - m {
+        rec.at(0);
+        rec.push(TextEventKind::Align, shift, 0);
+        rec.note(format!("align the pattern at shift {shift}"));
+        rec.at(1);
+        let mut j = 0;
+        while j < m && rec.compare(Cell::Text(shift + j), Cell::Pattern(j)) {
+            j += 1;
+        }
+        if j == m {
+            rec.at(2);
+            rec.found(shift);
+        }
+    }
+}
+
+/// Knuth–Morris–Pratt: on a mismatch the failure function says how much of the
+/// pattern is still matched, so text characters are never re-read
+fn kmp(rec: &mut Recorder) {
+    let (n, m) = (rec.text.len(), rec.pattern.len());
+
+    // fail[i] = length of the longest proper border of pattern[..=i]
+    rec.at(0);
+    rec.write(0, 0);
+    let mut k = 0;
+    for i in 1..m {
+        loop {
+            rec.at(2);
+            if rec.compare(Cell::Pattern(i), Cell::Pattern(k)) {
+                k += 1;
+                break;
+            }
+            if k == 0 {
+                break;
+            }
+            rec.at(3);
+            k = rec.read(k - 1) as usize;
+            rec.note(format!("mismatch: fall back to the border of length {k}"));
+        }
+        rec.at(4);
+        rec.write(i, k as u32);
+        rec.note(format!("fail[{i}] = {k}: longest proper border of pattern[..={i}]"));
+    }
+
+    let mut j = 0;
+    for i in 0..n {
+        loop {
+            rec.at(6);
+            if rec.compare(Cell::Text(i), Cell::Pattern(j)) {
+                j += 1;
+                break;
+            }
+            if j == 0 {
+                break;
+            }
+            rec.at(7);
+            j = rec.read(j - 1) as usize;
+            rec.note(format!("mismatch: keep the {j} characters still matched, text is not re-read"));
+        }
+        if j == m {
+            rec.at(8);
+            rec.found(i + 1 - m);
+            j = rec.read(m - 1) as usize;
+            rec.note(format!("continue with the border of length {j}"));
+        }
+    }
+}
+
+/// Z-algorithm over pattern + '$' + text; Z[i] == m marks an occurrence
+fn z_algorithm(rec: &mut Recorder) {
+    let (n, m) = (rec.text.len(), rec.pattern.len());
+    let len = m + 1 + n;
+    // Map a concatenation index to its cell; the separator matches nothing
+    let cell = |idx: usize| match idx.cmp(&m) {
+        Ordering::Less => Some(Cell::Pattern(idx)),
+        Ordering::Equal => None,
+        Ordering::Greater => Some(Cell::Text(idx - m - 1)),
+    };
+
+    rec.at(0);
+    rec.write(0, 0);
+    let (mut l, mut r) = (0, 0);
+    for i in 1..len {
+        // Reuse the Z-box [l, r) when i falls inside it
+        rec.at(2);
+        let mut z = if i < r { (r - i).min(rec.read(i - l) as usize) } else { 0 };
+        rec.at(3);
+        while i + z < len {
+            match (cell(z), cell(i + z)) {
+                (Some(a), Some(b)) if rec.compare(b, a) => z += 1,
+                _ => break,
+            }
+        }
+        rec.at(4);
+        rec.write(i, z as u32);
+        rec.note(format!("Z[{i}] = {z}: the string at {i} shares a prefix of length {z} with the pattern"));
+        if i + z > r {
+            l = i;
+            r = i + z;
+        }
+        if i > m && z == m {
+            rec.at(5);
+            rec.found(i - m - 1);
+        }
+    }
+}
+
+fn rabin_karp(rec: &mut Recorder) {
+    let (n, m) = (rec.text.len(), rec.pattern.len());
+    // Any byte is a digit, so the hash needs no assumption about the alphabet
+    let digit = |c: u8| u32::from(c) % HASH_MOD;
+    let hash = |chars: &[u8]| chars.iter().fold(0, |h, &c| (h * HASH_BASE + digit(c)) % HASH_MOD);
+    // Weight of the outgoing character: BASE^(m-1) mod MOD
+    let high = (1..m).fold(1, |p, _| p * HASH_BASE % HASH_MOD);
+
+    let target = hash(rec.pattern);
+    let mut h = hash(&rec.text[..m]);
+    for shift in 0..=n - m {
+        if shift > 0 {
+            let out = digit(rec.text[shift - 1]) * high % HASH_MOD;
+            h = ((h + HASH_MOD - out) * HASH_BASE + digit(rec.text[shift + m - 1])) % HASH_MOD;
+        }
+        rec.at(if shift > 0 { 2 } else { 0 });
+        rec.write(shift, h);
+        let rolled = if shift > 0 { "roll the window hash" } else { "hash the first window" };
+        rec.note(format!("{rolled}: text[{shift}..{}] hashes to {h}", shift + m));
+        let equal = h == target;
+        rec.at(3);
+        rec.push(TextEventKind::HashCompare { equal }, shift, h);
+        let verdict = if equal { "equal, verify the characters" } else { "differ, slide on" };
+        rec.note(format!("window hash {h} vs pattern hash {target}: {verdict}"));
+        if !equal {
+            continue;
+        }
+        // Hashes can collide: verify character by character
+        rec.at(4);
+        let mut j = 0;
+        while j < m && rec.compare(Cell::Text(shift + j), Cell::Pattern(j)) {
+            j += 1;
+        }
+        rec.at(5);
+        if j == m {
+            rec.found(shift);
+        } else {
+            rec.push(TextEventKind::SpuriousHit, shift, 0);
+            rec.note(format!("spurious hit at shift {shift}: the hashes collide but the characters differ"));
+        }
+    }
+}
+
+/// Build the suffix array by insertion-sorting suffixes, then binary-search it for the pattern
+fn suffix_array(rec: &mut Recorder) {
+    let n = rec.text.len();
+    rec.at(0);
+    for i in 0..n {
+        rec.write(i, i as u32);
+    }
+    for i in 1..n {
+        let mut j = i;
+        rec.at(1);
+        while j > 0 && suffix_less(rec, rec.aux[j] as usize, rec.aux[j - 1] as usize) {
+            let (a, b) = (rec.aux[j], rec.aux[j - 1]);
+            rec.at(2);
+            rec.write(j - 1, a);
+            rec.write(j, b);
+            rec.note(format!("suffix {a} sorts before suffix {b}: swap them"));
+            j -= 1;
+            rec.at(1);
+        }
+    }
+
+    // Lower bound: first suffix not smaller than the pattern
+    rec.at(4);
+    let (mut lo, mut hi) = (0, n);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let start = rec.read(mid) as usize;
+        rec.push(TextEventKind::Align, start, 0);
+        rec.note(format!("binary search: compare the pattern with suffix {start}, entry {mid} of [{lo}, {hi})"));
+        if pattern_cmp(rec, start) == Ordering::Greater {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    // All occurrences are adjacent in suffix order
+    for idx in lo..n {
+        rec.at(5);
+        let start = rec.read(idx) as usize;
+        rec.push(TextEventKind::Align, start, 0);
+        rec.note(format!("occurrences are adjacent: check suffix {start}"));
+        if pattern_cmp(rec, start) != Ordering::Equal {
+            break;
+        }
+        rec.at(6);
+        rec.found(start);
+    }
+}
+
+/// Whether text[a..] sorts before text[b..]
+fn suffix_less(rec: &mut Recorder, a: usize, b: usize) -> bool {
+    let n = rec.text.len();
+    let mut k = 0;
+    loop {
+        if b + k == n {
+            return false;
+        }
+        if a + k == n {
+            return true;
+        }
+        if !rec.compare(Cell::Text(a + k), Cell::Text(b + k)) {
+            return rec.text[a + k] < rec.text[b + k];
+        }
+        k += 1;
+    }
+}
+
+/// Order of the pattern relative to the first m characters of text[start..]
+fn pattern_cmp(rec: &mut Recorder, start: usize) -> Ordering {
+    let n = rec.text.len();
+    for k in 0..rec.pattern.len() {
+        if start + k == n {
+            // Suffix is a proper prefix of the pattern, so it sorts first
+            return Ordering::Greater;
+        }
+        if !rec.compare(Cell::Text(start + k), Cell::Pattern(k)) {
+            return rec.pattern[k].cmp(&rec.text[start + k]);
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MATCHERS: [Algorithm; 5] =
+        [Algorithm::NaiveMatch, Algorithm::Kmp, Algorithm::ZFunction, Algorithm::RabinKarp, Algorithm::SuffixArray];
+
+    /// Shifts reported as matches, in ascending order (the suffix array finds them in suffix order)
+    fn matches(algorithm: Algorithm, text: &[u8], pattern: &[u8]) -> Vec<usize> {
+        let (_, _, events) = match_events(algorithm, text, pattern);
+        let mut shifts: Vec<usize> = events.iter().filter(|e| e.kind == TextEventKind::Match).map(|e| e.i).collect();
+        shifts.sort_unstable();
+        shifts
+    }
+
+    #[test]
+    fn every_matcher_reports_the_same_shifts() {
+        let cases: [(&[u8], &[u8], &[usize]); 7] = [
+            (b"AAAAAA", b"AA", &[0, 1, 2, 3, 4]),
+            (b"ABABABAB", b"ABAB", &[0, 2, 4]),
+            (b"AABAACAADAABAABA", b"AABA", &[0, 9, 12]),
+            (b"ABBABBAB", b"BAA", &[]),
+            (b"ABC", b"ABCD", &[]),
+            (b"banana", b"ana", &[1, 3]),
+            (b"x1 x1x1!", b"x1", &[0, 3, 5]),
+        ];
+        for (text, pattern, expected) in cases {
+            for algorithm in MATCHERS {
+                assert_eq!(matches(algorithm, text, pattern), expected, "{algorithm:?} on {text:?} / {pattern:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn rabin_karp_verifies_every_hash_hit() {
+        let (text, pattern) = (b"ABBABBBABABBBAABBABA", b"BBAB");
+        let (_, _, events) = match_events(Algorithm::RabinKarp, text, pattern);
+        let hits = events.iter().filter(|e| e.kind == TextEventKind::HashCompare { equal: true }).count();
+        let found = events.iter().filter(|e| matches!(e.kind, TextEventKind::Match | TextEventKind::SpuriousHit)).count();
+        assert_eq!(hits, found);
+        assert_eq!(matches(Algorithm::RabinKarp, text, pattern), matches(Algorithm::NaiveMatch, text, pattern));
+    }
+}
@@ -0,0 +1,107 @@
+pub mod matching;
+
+use rand::Rng;
+
+use crate::algorithms::Algorithm;
+use matching::{Cell, TextEventKind};
+
+/// Pattern matching playback: text and pattern rows plus the algorithm's auxiliary array
+#[derive(Clone, Debug, Default)]
+pub struct TextRun {
+    pub text: Vec<u8>,
+    pub pattern: Vec<u8>,
+    pub aux: Vec<Option<u32>>, // Failure function, Z-array, window hashes or suffix array
+    pub aux_label: &'static str,
+    events: Vec<matching::TextEvent>,
+    cursor: usize,
+    pub shift: usize,                         // Text offset the pattern is aligned at
+    pub compared: Option<(Cell, Cell, bool)>, // Last character comparison and whether it matched
+    pub aux_active: Option<usize>,            // Auxiliary entry just read or written
+    pub matches: Vec<usize>,                  // Shifts where the pattern occurs
+    pub spurious_hits: usize,                 // Rabin–Karp hash matches that failed verification
+}
+
+impl TextRun {
+    /// Random binary text with the pattern planted twice, so naive matching hits many partial matches
+    pub fn random<R: Rng>(algorithm: Algorithm, rng: &mut R) -> Self {
+        let n = 24;
+        let m = 4;
+        let mut text: Vec<u8> = (0..n).map(|_| b"AB"[rng.gen_range(0..2)]).collect();
+        let pattern: Vec<u8> = (0..m).map(|_| b"AB"[rng.gen_range(0..2)]).collect();
+        for _ in 0..2 {
+            let at = rng.gen_range(0..=n - m);
+            text[at..at + m].copy_from_slice(&pattern);
+        }
+        Self::new(algorithm, text, pattern)
+    }
+
+    pub fn new(algorithm: Algorithm, text: Vec<u8>, pattern: Vec<u8>) -> Self {
+        let (aux_len, aux_label, events) = matching::match_events(algorithm, &text, &pattern);
+        Self {
+            text,
+            pattern,
+            aux: vec![None; aux_len],
+            aux_label,
+            events,
+            cursor: 0,
+            shift: 0,
+            compared: None,
+            aux_active: None,
+            matches: Vec::new(),
+            spurious_hits: 0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.events.len()
+    }
+
+    pub fn events(&self) -> &Vec<matching::TextEvent> {
+        &self.events
+    }
+
+    /// Number of events played so far
+    pub fn played(&self) -> usize {
+        self.cursor
+    }
+
+    /// Bytes held by the auxiliary array entries written so far
+    pub fn aux_bytes(&self) -> usize {
+        self.aux.iter().flatten().count() * 4
+    }
+
+    /// Apply the next event, returning it so the engine can count its cost
+    pub fn advance(&mut self) -> Option<matching::TextEvent> {
+        let event = self.events.get(self.cursor)?.clone();
+        self.cursor += 1;
+        self.compared = None;
+        self.aux_active = None;
+
+        match event.kind {
+            TextEventKind::Align => self.shift = event.i,
+            TextEventKind::Compare { a, b, equal } => {
+                // The pattern follows whatever text position it is compared against
+                if let (Cell::Text(i), Cell::Pattern(j)) = (a, b) {
+                    self.shift = i.saturating_sub(j);
+                }
+                self.compared = Some((a, b, equal));
+            }
+            TextEventKind::HashCompare { .. } => {
+                self.shift = event.i;
+                self.aux_active = Some(event.i);
+            }
+            TextEventKind::AuxRead => self.aux_active = Some(event.i),
+            TextEventKind::AuxWrite => {
+                self.aux[event.i] = Some(event.value);
+                self.aux_active = Some(event.i);
+            }
+            TextEventKind::Match => {
+                self.shift = event.i;
+                self.matches.push(event.i);
+            }
+            TextEventKind::SpuriousHit => self.spurious_hits += 1,
+            TextEventKind::Done => {}
+        }
+        Some(event)
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::engine::BarState;
+
+/// Bar state codes with a color of their own; codes beyond fall back to `Theme::fallback`
+pub const STATE_COUNT: usize = 40;
+
+/// First of the eight thread temp array codes, after the BarState codes
+pub const THREAD_TEMPS: u32 = 14;
+/// First of the eight thread underline codes
+pub const THREAD_UNDERLINES: u32 = 22;
+/// First of the eight stability tints, earliest original position first
+pub const STABILITY_TINTS: u32 = 30;
+/// Search window underline, also the highlight accent (graph paths, call brackets)
+pub const SEARCH_WINDOW: u32 = 38;
+/// Bars outside the search window, also empty slots and black tree nodes
+pub const OUTSIDE_WINDOW: u32 = 39;
+/// Trail and marker of the pinned element
+pub const PINNED: u32 = SEARCH_WINDOW;
+
+/// Named single states in the `[states]` table, with their codes
+const STATE_KEYS: [(&str, usize); 8] = [
+    ("idle", BarState::Idle as usize),
+    ("compare", BarState::Compare as usize),
+    ("swap", BarState::Swap as usize),
+    ("sorted", BarState::Sorted as usize),
+    ("source", BarState::Source as usize),
+    ("temp_array", BarState::TempArray as usize),
+    ("search_window", SEARCH_WINDOW as usize),
+    ("outside_window", OUTSIDE_WINDOW as usize),
+];
+
+/// Colors of the bars, their state codes and the scene around them, shared by the wgpu shaders,
+/// egui legends and the CPU renderers (SVG, terminal)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub states: [[f32; 4]; STATE_COUNT], // RGBA per bar state code, see BarState and the codes above 13
+    pub fallback: [f32; 4],
+    pub background: [f32; 3],
+    pub floor_tint: [f32; 3], // Multiplies the floor texture
+    pub grid_glow: [f32; 3],  // Emissive color of the bright grid traces in the floor texture
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: "Default".to_string(),
+            states: [
+                [0.42, 0.78, 1.00, 0.55], // 0 idle
+                [1.00, 0.75, 0.35, 0.65], // 1 compare
+                [1.00, 0.45, 0.65, 0.70], // 2 swap (target receiving a value)
+                [0.65, 1.00, 0.75, 0.60], // 3 sorted
+                [0.35, 0.95, 0.90, 0.70], // 4 source (providing a value)
+                [0.75, 0.50, 1.00, 0.65], // 5 temp array
+                [1.00, 0.40, 0.40, 0.70], // 6-13 threads 0-7
+                [1.00, 0.65, 0.30, 0.70],
+                [1.00, 0.95, 0.35, 0.70],
+                [0.40, 1.00, 0.50, 0.70],
+                [0.35, 0.95, 0.95, 0.70],
+                [0.40, 0.55, 1.00, 0.70],
+                [0.75, 0.45, 1.00, 0.70],
+                [1.00, 0.45, 0.85, 0.70],
+                [0.80, 0.30, 0.30, 0.65], // 14-21 thread temp arrays
+                [0.85, 0.50, 0.20, 0.65],
+                [0.85, 0.80, 0.25, 0.65],
+                [0.30, 0.80, 0.40, 0.65],
+                [0.25, 0.75, 0.75, 0.65],
+                [0.30, 0.45, 0.85, 0.65],
+                [0.60, 0.35, 0.85, 0.65],
+                [0.85, 0.35, 0.70, 0.65],
+                [1.00, 0.15, 0.15, 0.95], // 22-29 thread underlines
+                [1.00, 0.55, 0.00, 0.95],
+                [1.00, 0.95, 0.00, 0.95],
+                [0.15, 1.00, 0.30, 0.95],
+                [0.00, 1.00, 1.00, 0.95],
+                [0.20, 0.45, 1.00, 0.95],
+                [0.75, 0.20, 1.00, 0.95],
+                [1.00, 0.20, 0.80, 0.95],
+                [0.30, 0.50, 1.00, 0.75], // 30-37 stability tints, first to last original position
+                [0.35, 0.70, 1.00, 0.75],
+                [0.35, 0.90, 0.90, 0.75],
+                [0.40, 1.00, 0.60, 0.75],
+                [0.80, 1.00, 0.40, 0.75],
+                [1.00, 0.85, 0.30, 0.75],
+                [1.00, 0.60, 0.25, 0.75],
+                [1.00, 0.35, 0.30, 0.75],
+                [1.00, 0.90, 0.55, 0.95], // 38 search window, also the highlight accent
+                [0.25, 0.30, 0.40, 0.30], // 39 outside search window
+            ],
+            fallback: [0.70, 0.70, 0.90, 0.50],
+            background: [0.02, 0.02, 0.04],
+            floor_tint: [1.0, 1.0, 1.0],
+            grid_glow: [0.10, 0.40, 0.90],
+        }
+    }
+}
+
+impl Theme {
+    /// Themes shipped with the app: the original look and a colorblind-safe variant
+    pub fn builtin() -> Vec<Theme> {
+        let colorblind = Theme::from_toml(include_str!("../themes/colorblind.toml")).expect("built-in theme parses");
+        vec![Theme::default(), colorblind]
+    }
+
+    /// A built-in theme by name (case-insensitive, "colorblind" matches "Colorblind-safe"), else a TOML file
+    pub fn resolve(name_or_path: &str) -> Result<Theme> {
+        let wanted = name_or_path.to_ascii_lowercase();
+        let builtin = Theme::builtin();
+        if let Some(theme) = builtin.iter().find(|theme| theme.name.to_ascii_lowercase().starts_with(&wanted)) {
+            return Ok(theme.clone());
+        }
+        let path = Path::new(name_or_path);
+        if !path.exists() {
+            let names: Vec<&str> = builtin.iter().map(|theme| theme.name.as_str()).collect();
+            bail!("no theme file {name_or_path}; built-in themes are {}", names.join(", "));
+        }
+        Theme::load(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Theme> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("cannot read theme {}", path.display()))?;
+        Theme::from_toml(&text).with_context(|| format!("invalid theme {}", path.display()))
+    }
+
+    /// Parse a theme file; anything it leaves out keeps the default theme's color
+    pub fn from_toml(text: &str) -> Result<Theme> {
+        let file: ThemeFile = toml::from_str(text)?;
+        let mut theme = Theme { name: file.name.unwrap_or_else(|| "Custom".to_string()), ..Theme::default() };
+        for (name, value) in &file.states {
+            let code = match STATE_KEYS.iter().find(|(key, _)| key == name) {
+                Some(&(_, code)) => code,
+                None if name == "fallback" => {
+                    theme.fallback = value.rgba(theme.fallback).with_context(|| format!("states.{name}"))?;
+                    continue;
+                }
+                None => bail!("states.{name}: unknown state; expected fallback or one of {}", STATE_KEYS.map(|(key, _)| key).join(", ")),
+            };
+            theme.states[code] = value.rgba(theme.states[code]).with_context(|| format!("states.{name}"))?;
+        }
+        let runs = [
+            (BarState::Thread0 as u32, &file.threads.active, "threads.active"),
+            (THREAD_TEMPS, &file.threads.temp, "threads.temp"),
+            (THREAD_UNDERLINES, &file.threads.underline, "threads.underline"),
+            (STABILITY_TINTS, &file.stability.tints, "stability.tints"),
+        ];
+        for (first, colors, key) in runs {
+            if let Some(colors) = colors {
+                theme.set_run(first as usize, colors).with_context(|| key)?;
+            }
+        }
+        let scene = [
+            (&mut theme.background, &file.scene.background, "scene.background"),
+            (&mut theme.floor_tint, &file.scene.floor_tint, "scene.floor_tint"),
+            (&mut theme.grid_glow, &file.scene.grid_glow, "scene.grid_glow"),
+        ];
+        for (target, value, key) in scene {
+            if let Some(value) = value {
+                *target = value.rgb(*target).with_context(|| key)?;
+            }
+        }
+        Ok(theme)
+    }
+
+    /// Eight consecutive state codes from one array of colors (threads 0-7, stability tints)
+    fn set_run(&mut self, first: usize, colors: &[Color; 8]) -> Result<()> {
+        for (i, color) in colors.iter().enumerate() {
+            self.states[first + i] = color.rgba(self.states[first + i])?;
+        }
+        Ok(())
+    }
+
+    /// RGBA of a bar state code
+    pub fn state_color(&self, state: u32) -> [f32; 4] {
+        self.states.get(state as usize).copied().unwrap_or(self.fallback)
+    }
+
+    /// A state's color for egui text and widgets, fully opaque
+    pub fn label_color(&self, state: u32) -> egui::Color32 {
+        let [r, g, b, _] = self.state_color(state);
+        egui::Color32::from_rgb(channel(r), channel(g), channel(b))
+    }
+
+    pub fn uniform(&self) -> ThemeUniform {
+        let rgb = |[r, g, b]: [f32; 3]| [r, g, b, 1.0];
+        ThemeUniform {
+            states: self.states,
+            fallback: self.fallback,
+            background: rgb(self.background),
+            floor_tint: rgb(self.floor_tint),
+            grid_glow: rgb(self.grid_glow),
+        }
+    }
+}
+
+/// Uniform block bound next to the globals (group 0, binding 1) in bar.wgsl and floor.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ThemeUniform {
+    states: [[f32; 4]; STATE_COUNT],
+    fallback: [f32; 4],
+    background: [f32; 4],
+    floor_tint: [f32; 4],
+    grid_glow: [f32; 4],
+}
+
+/// 0..1 color channel as a byte
+pub fn channel(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// A theme file as written: every table and key is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    name: Option<String>,
+    states: BTreeMap<String, Color>, // Keys of STATE_KEYS, or fallback
+    threads: ThreadColors,
+    stability: StabilityColors,
+    scene: SceneColors,
+}
+
+/// Threads 0-7: the bars each thread works on, its temp array and its merge-level underline
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThreadColors {
+    active: Option<[Color; 8]>,
+    temp: Option<[Color; 8]>,
+    underline: Option<[Color; 8]>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StabilityColors {
+    tints: Option<[Color; 8]>, // First to last original position
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SceneColors {
+    background: Option<Color>,
+    floor_tint: Option<Color>,
+    grid_glow: Option<Color>,
+}
+
+/// `[r, g, b]`, `[r, g, b, a]` or "#rrggbb" / "#rrggbbaa"
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Color {
+    Channels(Vec<f32>),
+    Hex(String),
+}
+
+impl Color {
+    /// A missing alpha keeps `base`'s
+    fn rgba(&self, base: [f32; 4]) -> Result<[f32; 4]> {
+        let channels: Vec<f32> = match self {
+            Color::Channels(channels) => channels.clone(),
+            Color::Hex(hex) => {
+                let digits = hex.strip_prefix('#').unwrap_or(hex);
+                if !matches!(digits.len(), 6 | 8) || !digits.is_ascii() {
+                    bail!("expected #rrggbb or #rrggbbaa, found {hex}");
+                }
+                (0..digits.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map(|byte| byte as f32 / 255.0))
+                    .collect::<Result<_, _>>()
+                    .with_context(|| format!("invalid hex color {hex}"))?
+            }
+        };
+        match channels[..] {
+            [r, g, b] => Ok([r, g, b, base[3]]),
+            [r, g, b, a] => Ok([r, g, b, a]),
+            _ => bail!("a color has 3 or 4 channels, found {}", channels.len()),
+        }
+    }
+
+    fn rgb(&self, base: [f32; 3]) -> Result<[f32; 3]> {
+        let [r, g, b, _] = self.rgba([base[0], base[1], base[2], 1.0])?;
+        Ok([r, g, b])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colorblind_theme_parses() {
+        let theme = Theme::from_toml(include_str!("../themes/colorblind.toml")).unwrap();
+        let default = Theme::default();
+        assert_eq!(theme.name, "Colorblind-safe");
+        // Hex without alpha keeps the default's opacity
+        let compare = theme.states[BarState::Compare as usize];
+        assert_eq!(compare.map(channel), [0xe6, 0x9f, 0x00, channel(default.states[BarState::Compare as usize][3])]);
+        assert_eq!(theme.states[OUTSIDE_WINDOW as usize], [0.25, 0.28, 0.35, default.states[OUTSIDE_WINDOW as usize][3]]);
+        assert_eq!(theme.fallback, [0.70, 0.70, 0.70, default.fallback[3]]);
+        assert_eq!(theme.states[BarState::Thread7 as usize].map(channel)[..3], [0xe0, 0xe0, 0xe0]);
+        assert_eq!(theme.states[THREAD_TEMPS as usize].map(channel)[..3], [0xb8, 0x7f, 0x00]);
+        assert_eq!(theme.states[THREAD_UNDERLINES as usize + 7].map(channel)[..3], [0xff, 0xff, 0xff]);
+        assert_eq!(theme.states[STABILITY_TINTS as usize + 7].map(channel)[..3], [0xfd, 0xe7, 0x25]);
+        assert_eq!(theme.grid_glow, [0.30, 0.40, 0.60]);
+    }
+
+    #[test]
+    fn missing_keys_keep_the_default() {
+        let theme = Theme::from_toml("[states]\nswap = [1, 0, 0, 1]\n").unwrap();
+        let mut expected = Theme { name: "Custom".to_string(), ..Theme::default() };
+        expected.states[BarState::Swap as usize] = [1.0, 0.0, 0.0, 1.0];
+        assert_eq!(theme, expected);
+    }
+
+    #[test]
+    fn malformed_themes_are_rejected() {
+        for text in [
+            "name = ",                                   // Not TOML
+            "name = 3",                                  // Name is not a string
+            "colour = \"#ffffff\"",                      // Unknown top-level key
+            "[states]\nglow = \"#ffffff\"",              // Unknown state
+            "[states]\nidle = \"#fffff\"",               // Short hex
+            "[states]\nidle = \"#gggggg\"",              // Not hex
+            "[states]\nidle = [1, 1]",                   // Too few channels
+            "[states]\nidle = [1, 1, 1, 1, 1]",          // Too many channels
+            "[threads]\nactive = [\"#ffffff\"]",         // Not 8 colors
+            "[scene]\nbackground = 0.5",                 // Not a color
+            "[scene]\nhorizon = [0, 0, 0]",              // Unknown scene key
+        ] {
+            assert!(Theme::from_toml(text).is_err(), "accepted {text:?}");
+        }
+    }
+}
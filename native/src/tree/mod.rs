@@ -0,0 +1,95 @@
+pub mod model;
+
+use model::{Tree, TreeEvent, TreeEventKind, TreeKind, TreeOp, TreeShape};
+
+/// Live search tree plus the playback of the events its operations recorded
+#[derive(Clone, Debug)]
+pub struct TreeRun {
+    model: Tree,        // Logical tree after every queued operation
+    pub initial_keys: Vec<u32>,
+    events: Vec<TreeEvent>,
+    cursor: usize,
+    pub shape: TreeShape,           // Layout as of the last played event
+    pub active: Option<usize>,      // Node being compared against
+    pub rotated: Option<usize>,     // Node that just moved down in a rotation
+    pub changed: Option<usize>,     // Node just inserted, recolored or given a new key
+    pub found: Option<Option<usize>>, // Result of the last search
+    pub key: Option<u32>,           // Key of the operation being played
+    pub rotations: usize,
+}
+
+impl Default for TreeRun {
+    fn default() -> Self {
+        Self::new(TreeKind::Bst, Vec::new())
+    }
+}
+
+impl TreeRun {
+    /// Build a tree by inserting `keys` in order; the build itself is played back
+    pub fn new(kind: TreeKind, keys: Vec<u32>) -> Self {
+        let mut model = Tree::new(kind);
+        let mut events = Vec::new();
+        for &key in &keys {
+            model.apply(TreeOp::Insert(key), &mut events);
+        }
+        Self {
+            model,
+            initial_keys: keys,
+            events,
+            cursor: 0,
+            shape: TreeShape::default(),
+            active: None,
+            rotated: None,
+            changed: None,
+            found: None,
+            key: None,
+            rotations: 0,
+        }
+    }
+
+    /// Queue an operation; its events play after the ones already queued
+    pub fn apply(&mut self, op: TreeOp) {
+        self.model.apply(op, &mut self.events);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.events.len()
+    }
+
+    pub fn events(&self) -> &Vec<TreeEvent> {
+        &self.events
+    }
+
+    /// Number of events played so far
+    pub fn played(&self) -> usize {
+        self.cursor
+    }
+
+    /// Apply the next event, returning its kind so the engine can count its cost
+    pub fn advance(&mut self) -> Option<TreeEventKind> {
+        let event = self.events.get(self.cursor)?.clone();
+        self.cursor += 1;
+        self.active = None;
+        self.rotated = None;
+        self.changed = None;
+        self.key = Some(event.key);
+        if let Some(shape) = event.shape {
+            self.shape = shape;
+        }
+
+        match event.kind {
+            TreeEventKind::Visit => self.active = Some(event.node),
+            TreeEventKind::Found => self.found = Some(Some(event.node)),
+            TreeEventKind::NotFound => self.found = Some(None),
+            TreeEventKind::Insert | TreeEventKind::Recolor | TreeEventKind::ReplaceKey => {
+                self.changed = Some(event.node)
+            }
+            TreeEventKind::Rotate { .. } => {
+                self.rotated = Some(event.node);
+                self.rotations += 1;
+            }
+            TreeEventKind::Remove => {}
+        }
+        Some(event.kind)
+    }
+}
@@ -0,0 +1,629 @@
+use std::cmp::Ordering;
+
+use crate::algorithms::{annotate, Algorithm};
+use crate::engine::Step;
+
+/// Index of the shared sentinel leaf; it is always black and never holds a key
+const NIL: usize = 0;
+
+/// Balancing scheme of the tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeKind {
+    Bst,
+    Avl,
+    RedBlack,
+}
+
+/// Interactive operation on the live tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeOp {
+    Insert(u32),
+    Delete(u32),
+    Search(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeEventKind {
+    Visit,                  // Key compared against node
+    Found,                  // Search ended at node
+    NotFound,               // Search fell off the tree
+    Insert,                 // New leaf attached
+    Rotate { left: bool },  // Rotation around node (node moves down)
+    Recolor,                // Red-black color change
+    ReplaceKey,             // Node takes its in-order successor's key before the successor is removed
+    Remove,                 // Node with at most one child spliced out
+}
+
+/// Node of a layout snapshot, in in-order sequence
+#[derive(Clone, Copy, Debug)]
+pub struct ShapeNode {
+    pub id: usize,
+    pub key: u32,
+    pub depth: usize,
+    pub parent: Option<usize>,
+    pub red: bool,
+}
+
+/// Tree structure after an event, used for layout
+#[derive(Clone, Debug, Default)]
+pub struct TreeShape {
+    pub nodes: Vec<ShapeNode>,
+}
+
+impl TreeShape {
+    pub fn height(&self) -> usize {
+        self.nodes.iter().map(|n| n.depth + 1).max().unwrap_or(0)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TreeEvent {
+    pub kind: TreeEventKind,
+    pub node: usize,
+    pub key: u32,
+    pub shape: Option<TreeShape>, // Present when the structure or colors changed
+    pub note: Option<String>,
+    pub line: Option<usize>,
+}
+
+impl Step for TreeEvent {
+    fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    fn note_mut(&mut self) -> &mut Option<String> {
+        &mut self.note
+    }
+
+    fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    fn columns(&self) -> String {
+        format!("{:?}\t{}\t-\t{}", self.kind, self.node, self.key)
+    }
+}
+
+// Search, insert and delete are shared; AVL and red-black listings add their rebalancing after them
+const LINE_SEARCH: usize = 1;
+const LINE_FOUND: usize = 2;
+const LINE_EXISTS: usize = 3;
+const LINE_ATTACH: usize = 4;
+const LINE_ABSENT: usize = 5;
+const LINE_SUCCESSOR: usize = 6;
+const LINE_REMOVE: usize = 7;
+const LINE_REBALANCE: usize = 9;
+const LINE_INSERT_FIXUP: usize = 9;
+const LINE_DELETE_FIXUP: usize = 11;
+const LINE_ROOT_BLACK: usize = 12;
+
+/// Listing the events' lines index into
+pub fn pseudocode(algorithm: Algorithm) -> &'static [&'static str] {
+    match algorithm {
+        Algorithm::Bst => &[
+            "search(key):",
+            "    x = root; while x != nil and key != x.key: x = x.left if key < x.key else x.right",
+            "    return x, or not found once x is nil",
+            "insert(key): x = search(key); if found: return",
+            "    attach a new leaf where the search fell off",
+            "delete(key): z = search(key); if not found: return",
+            "    if z has two children: copy in the leftmost key of z.right, delete that node instead",
+            "    splice z out, its only child (if any) taking its place",
+        ],
+        Algorithm::AvlTree => &[
+            "search(key):",
+            "    x = root; while x != nil and key != x.key: x = x.left if key < x.key else x.right",
+            "    return x, or not found once x is nil",
+            "insert(key): x = search(key); if found: return",
+            "    attach a new leaf where the search fell off",
+            "delete(key): z = search(key); if not found: return",
+            "    if z has two children: copy in the leftmost key of z.right, delete that node instead",
+            "    splice z out, its only child (if any) taking its place",
+            "rebalance: from the changed node's parent up to the root, update heights",
+            "    if balance(x) is outside [-1, 1]: rotate, twice for a zig-zag",
+        ],
+        Algorithm::RedBlackTree => &[
+            "search(key):",
+            "    x = root; while x != nil and key != x.key: x = x.left if key < x.key else x.right",
+            "    return x, or not found once x is nil",
+            "insert(key): x = search(key); if found: return",
+            "    attach a new leaf where the search fell off",
+            "delete(key): z = search(key); if not found: return",
+            "    if z has two children: copy in the leftmost key of z.right, delete that node instead",
+            "    splice z out, its only child (if any) taking its place",
+            "insert fixup: while the new node's parent is red:",
+            "    recolor if the uncle is red, else rotate around the grandparent and recolor",
+            "delete fixup, if a black node was removed: while x is black and not the root:",
+            "    recolor the sibling, or rotate around the parent and recolor",
+            "color the root black (after a delete fixup: color x black)",
+        ],
+        _ => &[],
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    key: u32,
+    left: usize,
+    right: usize,
+    parent: usize,
+    red: bool,
+    height: i32, // AVL subtree height, 0 for the sentinel
+}
+
+impl Node {
+    fn nil() -> Self {
+        Self {
+            key: 0,
+            left: NIL,
+            right: NIL,
+            parent: NIL,
+            red: false,
+            height: 0,
+        }
+    }
+}
+
+/// Arena tree with a sentinel leaf, recording every step as an event
+#[derive(Clone, Debug)]
+pub struct Tree {
+    kind: TreeKind,
+    nodes: Vec<Node>,
+    root: usize,
+    free: Vec<usize>,
+}
+
+impl Tree {
+    pub fn new(kind: TreeKind) -> Self {
+        Self {
+            kind,
+            nodes: vec![Node::nil()],
+            root: NIL,
+            free: Vec::new(),
+        }
+    }
+
+    pub fn apply(&mut self, op: TreeOp, events: &mut Vec<TreeEvent>) {
+        match op {
+            TreeOp::Insert(key) => self.insert(key, events),
+            TreeOp::Delete(key) => self.delete(key, events),
+            TreeOp::Search(key) => {
+                let node = self.search(key, events);
+                if node == NIL {
+                    self.record(TreeEventKind::NotFound, node, key, false, LINE_FOUND, events);
+                    annotate(events, format!("fell off the tree: {key} is not in it"));
+                } else {
+                    self.record(TreeEventKind::Found, node, key, false, LINE_FOUND, events);
+                    annotate(events, format!("found {key}"));
+                }
+            }
+        }
+    }
+
+    /// In-order snapshot with depths and parents
+    pub fn shape(&self) -> TreeShape {
+        let mut nodes = Vec::new();
+        let mut stack = Vec::new();
+        let mut x = self.root;
+        let mut depth = 0;
+        loop {
+            while x != NIL {
+                stack.push((x, depth));
+                x = self.nodes[x].left;
+                depth += 1;
+            }
+            let Some((id, d)) = stack.pop() else {
+                break;
+            };
+            let node = self.nodes[id];
+            nodes.push(ShapeNode {
+                id,
+                key: node.key,
+                depth: d,
+                parent: (node.parent != NIL).then_some(node.parent),
+                red: node.red,
+            });
+            x = node.right;
+            depth = d + 1;
+        }
+        TreeShape { nodes }
+    }
+
+    fn record(&self, kind: TreeEventKind, node: usize, key: u32, snapshot: bool, line: usize, events: &mut Vec<TreeEvent>) {
+        let shape = snapshot.then(|| self.shape());
+        events.push(TreeEvent { kind, node, key, shape, note: None, line: Some(line) });
+    }
+
+    /// Record a visit to x while looking for key, captioned with the way the comparison goes
+    fn visit(&self, x: usize, key: u32, events: &mut Vec<TreeEvent>) -> Ordering {
+        let here = self.nodes[x].key;
+        self.record(TreeEventKind::Visit, x, key, false, LINE_SEARCH, events);
+        let order = key.cmp(&here);
+        let step = match order {
+            Ordering::Equal => format!("{key} = {here}: this node"),
+            Ordering::Less => format!("{key} < {here}: go left"),
+            Ordering::Greater => format!("{key} > {here}: go right"),
+        };
+        annotate(events, step);
+        order
+    }
+
+    fn search(&self, key: u32, events: &mut Vec<TreeEvent>) -> usize {
+        let mut x = self.root;
+        while x != NIL {
+            x = match self.visit(x, key, events) {
+                Ordering::Equal => return x,
+                Ordering::Less => self.nodes[x].left,
+                Ordering::Greater => self.nodes[x].right,
+            };
+        }
+        NIL
+    }
+
+    fn insert(&mut self, key: u32, events: &mut Vec<TreeEvent>) {
+        let mut parent = NIL;
+        let mut x = self.root;
+        while x != NIL {
+            parent = x;
+            x = match self.visit(x, key, events) {
+                Ordering::Equal => {
+                    // Keys are unique; report the existing node instead
+                    self.record(TreeEventKind::Found, x, key, false, LINE_EXISTS, events);
+                    annotate(events, format!("{key} is already in the tree"));
+                    return;
+                }
+                Ordering::Less => self.nodes[x].left,
+                Ordering::Greater => self.nodes[x].right,
+            };
+        }
+
+        let node = Node {
+            key,
+            parent,
+            red: self.kind == TreeKind::RedBlack,
+            height: 1,
+            ..Node::nil()
+        };
+        let z = match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = node;
+                id
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        if parent == NIL {
+            self.root = z;
+        } else if key < self.nodes[parent].key {
+            self.nodes[parent].left = z;
+        } else {
+            self.nodes[parent].right = z;
+        }
+        self.record(TreeEventKind::Insert, z, key, true, LINE_ATTACH, events);
+        let place = if parent == NIL {
+            "as the root".to_string()
+        } else {
+            let side = if key < self.nodes[parent].key { "left" } else { "right" };
+            format!("as the {side} child of {}", self.nodes[parent].key)
+        };
+        let color = if self.kind == TreeKind::RedBlack { ", colored red" } else { "" };
+        annotate(events, format!("attach {key} {place}{color}"));
+
+        match self.kind {
+            TreeKind::Bst => {}
+            TreeKind::Avl => self.avl_retrace(parent, events),
+            TreeKind::RedBlack => self.rb_insert_fixup(z, events),
+        }
+    }
+
+    fn delete(&mut self, key: u32, events: &mut Vec<TreeEvent>) {
+        let mut z = self.search(key, events);
+        if z == NIL {
+            self.record(TreeEventKind::NotFound, NIL, key, false, LINE_ABSENT, events);
+            annotate(events, format!("{key} is not in the tree, nothing to delete"));
+            return;
+        }
+
+        // Two children: take the successor's key, then remove the successor instead
+        if self.nodes[z].left != NIL && self.nodes[z].right != NIL {
+            let mut s = self.nodes[z].right;
+            self.record(TreeEventKind::Visit, s, key, false, LINE_SUCCESSOR, events);
+            annotate(events, format!("{key} has two children: look for its successor in the right subtree"));
+            while self.nodes[s].left != NIL {
+                s = self.nodes[s].left;
+                self.record(TreeEventKind::Visit, s, key, false, LINE_SUCCESSOR, events);
+                annotate(events, format!("successor: keep going left, to {}", self.nodes[s].key));
+            }
+            let successor = self.nodes[s].key;
+            self.nodes[z].key = successor;
+            self.record(TreeEventKind::ReplaceKey, z, successor, true, LINE_SUCCESSOR, events);
+            annotate(events, format!("copy the successor {successor} over {key}, then remove the successor's node"));
+            z = s;
+        }
+
+        let child = if self.nodes[z].left != NIL { self.nodes[z].left } else { self.nodes[z].right };
+        let parent = self.nodes[z].parent;
+        let removed_red = self.nodes[z].red;
+        let removed_key = self.nodes[z].key;
+        self.transplant(z, child);
+        self.free.push(z);
+        self.record(TreeEventKind::Remove, z, removed_key, true, LINE_REMOVE, events);
+        let splice = if child == NIL { "it is a leaf" } else { "its only child takes its place" };
+        annotate(events, format!("remove the node holding {removed_key}: {splice}"));
+
+        match self.kind {
+            TreeKind::Bst => {}
+            TreeKind::Avl => self.avl_retrace(parent, events),
+            TreeKind::RedBlack => {
+                if !removed_red {
+                    self.rb_delete_fixup(child, events);
+                }
+            }
+        }
+    }
+
+    /// Replace the subtree at u with the one at v (v may be the sentinel)
+    fn transplant(&mut self, u: usize, v: usize) {
+        let parent = self.nodes[u].parent;
+        if parent == NIL {
+            self.root = v;
+        } else if self.nodes[parent].left == u {
+            self.nodes[parent].left = v;
+        } else {
+            self.nodes[parent].right = v;
+        }
+        // Sets the sentinel's parent too, which red-black delete fixup relies on
+        self.nodes[v].parent = parent;
+    }
+
+    fn rotate(&mut self, x: usize, left: bool, line: usize, events: &mut Vec<TreeEvent>) {
+        // y is the child that moves up
+        let y = if left { self.nodes[x].right } else { self.nodes[x].left };
+        let inner = if left { self.nodes[y].left } else { self.nodes[y].right };
+        if left {
+            self.nodes[x].right = inner;
+        } else {
+            self.nodes[x].left = inner;
+        }
+        if inner != NIL {
+            self.nodes[inner].parent = x;
+        }
+        self.transplant(x, y);
+        if left {
+            self.nodes[y].left = x;
+        } else {
+            self.nodes[y].right = x;
+        }
+        self.nodes[x].parent = y;
+        self.update_height(x);
+        self.update_height(y);
+        self.record(TreeEventKind::Rotate { left }, x, self.nodes[x].key, true, line, events);
+        let direction = if left { "left" } else { "right" };
+        annotate(events, format!("rotate {direction} at {}: {} moves up", self.nodes[x].key, self.nodes[y].key));
+    }
+
+    fn update_height(&mut self, x: usize) {
+        if x != NIL {
+            let node = self.nodes[x];
+            self.nodes[x].height = 1 + self.nodes[node.left].height.max(self.nodes[node.right].height);
+        }
+    }
+
+    fn balance(&self, x: usize) -> i32 {
+        let node = self.nodes[x];
+        self.nodes[node.left].height - self.nodes[node.right].height
+    }
+
+    /// Walk up from x fixing heights and rotating any node whose balance leaves [-1, 1]
+    fn avl_retrace(&mut self, mut x: usize, events: &mut Vec<TreeEvent>) {
+        while x != NIL {
+            self.update_height(x);
+            let balance = self.balance(x);
+            if balance > 1 {
+                let left = self.nodes[x].left;
+                if self.balance(left) < 0 {
+                    self.rotate(left, true, LINE_REBALANCE, events);
+                }
+                self.rotate(x, false, LINE_REBALANCE, events);
+                explain(events, format!("balance factor {balance:+} at {} is out of range", self.nodes[x].key));
+                x = self.nodes[x].parent;
+            } else if balance < -1 {
+                let right = self.nodes[x].right;
+                if self.balance(right) > 0 {
+                    self.rotate(right, false, LINE_REBALANCE, events);
+                }
+                self.rotate(x, true, LINE_REBALANCE, events);
+                explain(events, format!("balance factor {balance:+} at {} is out of range", self.nodes[x].key));
+                x = self.nodes[x].parent;
+            }
+            x = self.nodes[x].parent;
+        }
+    }
+
+    fn is_red(&self, x: usize) -> bool {
+        self.nodes[x].red
+    }
+
+    fn set_red(&mut self, x: usize, red: bool, line: usize, events: &mut Vec<TreeEvent>) {
+        if x != NIL && self.nodes[x].red != red {
+            self.nodes[x].red = red;
+            self.record(TreeEventKind::Recolor, x, self.nodes[x].key, true, line, events);
+            let color = if red { "red" } else { "black" };
+            annotate(events, format!("recolor {} {color}", self.nodes[x].key));
+        }
+    }
+
+    /// Child of p on the given side
+    fn child(&self, p: usize, left: bool) -> usize {
+        if left { self.nodes[p].left } else { self.nodes[p].right }
+    }
+
+    fn rb_insert_fixup(&mut self, mut z: usize, events: &mut Vec<TreeEvent>) {
+        while self.is_red(self.nodes[z].parent) {
+            let p = self.nodes[z].parent;
+            let g = self.nodes[p].parent;
+            // `side` is true when the parent is a left child; mirror cases share the code
+            let side = self.nodes[g].left == p;
+            let uncle = self.child(g, !side);
+            if self.is_red(uncle) {
+                self.set_red(p, false, LINE_INSERT_FIXUP, events);
+                self.set_red(uncle, false, LINE_INSERT_FIXUP, events);
+                self.set_red(g, true, LINE_INSERT_FIXUP, events);
+                z = g;
+            } else {
+                if z == self.child(p, !side) {
+                    z = p;
+                    self.rotate(z, side, LINE_INSERT_FIXUP, events);
+                }
+                let p = self.nodes[z].parent;
+                let g = self.nodes[p].parent;
+                self.set_red(p, false, LINE_INSERT_FIXUP, events);
+                self.set_red(g, true, LINE_INSERT_FIXUP, events);
+                self.rotate(g, !side, LINE_INSERT_FIXUP, events);
+            }
+        }
+        let root = self.root;
+        self.set_red(root, false, LINE_ROOT_BLACK, events);
+    }
+
+    fn rb_delete_fixup(&mut self, mut x: usize, events: &mut Vec<TreeEvent>) {
+        while x != self.root && !self.is_red(x) {
+            let p = self.nodes[x].parent;
+            let side = self.nodes[p].left == x;
+            let mut w = self.child(p, !side);
+            if self.is_red(w) {
+                self.set_red(w, false, LINE_DELETE_FIXUP, events);
+                self.set_red(p, true, LINE_DELETE_FIXUP, events);
+                self.rotate(p, side, LINE_DELETE_FIXUP, events);
+                w = self.child(self.nodes[x].parent, !side);
+            }
+            if !self.is_red(self.nodes[w].left) && !self.is_red(self.nodes[w].right) {
+                self.set_red(w, true, LINE_DELETE_FIXUP, events);
+                x = self.nodes[x].parent;
+            } else {
+                if !self.is_red(self.child(w, !side)) {
+                    let near = self.child(w, side);
+                    self.set_red(near, false, LINE_DELETE_FIXUP, events);
+                    self.set_red(w, true, LINE_DELETE_FIXUP, events);
+                    self.rotate(w, !side, LINE_DELETE_FIXUP, events);
+                    w = self.child(self.nodes[x].parent, !side);
+                }
+                let p = self.nodes[x].parent;
+                let p_red = self.is_red(p);
+                self.set_red(w, p_red, LINE_DELETE_FIXUP, events);
+                self.set_red(p, false, LINE_DELETE_FIXUP, events);
+                let far = self.child(w, !side);
+                self.set_red(far, false, LINE_DELETE_FIXUP, events);
+                self.rotate(p, side, LINE_DELETE_FIXUP, events);
+                x = self.root;
+            }
+        }
+        self.set_red(x, false, LINE_ROOT_BLACK, events);
+    }
+}
+
+/// Put the reason for the event just recorded in front of its own caption
+fn explain(events: &mut [TreeEvent], reason: String) {
+    if let Some(event) = events.last_mut() {
+        event.note = Some(match event.note.take() {
+            Some(note) => format!("{reason}: {note}"),
+            None => reason,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    /// Check the subtree at x, whose keys must lie strictly between the bounds.
+    /// Returns its height and its black height counting the sentinel.
+    fn check(tree: &Tree, x: usize, low: Option<u32>, high: Option<u32>) -> (i32, usize) {
+        if x == NIL {
+            return (0, 1);
+        }
+        let node = tree.nodes[x];
+        assert!(low.is_none_or(|low| node.key > low) && high.is_none_or(|high| node.key < high), "BST order broken at {}", node.key);
+        for child in [node.left, node.right] {
+            if child != NIL {
+                assert_eq!(tree.nodes[child].parent, x, "parent pointer of {}", tree.nodes[child].key);
+            }
+        }
+        let (left_height, left_black) = check(tree, node.left, low, Some(node.key));
+        let (right_height, right_black) = check(tree, node.right, Some(node.key), high);
+        let height = 1 + left_height.max(right_height);
+        match tree.kind {
+            TreeKind::Bst => {}
+            TreeKind::Avl => {
+                assert_eq!(node.height, height, "stale height at {}", node.key);
+                assert!((left_height - right_height).abs() <= 1, "balance factor out of range at {}", node.key);
+            }
+            TreeKind::RedBlack => {
+                assert_eq!(left_black, right_black, "black heights differ below {}", node.key);
+                if node.red {
+                    assert!(!tree.is_red(node.left) && !tree.is_red(node.right), "red {} has a red child", node.key);
+                }
+            }
+        }
+        (height, left_black + usize::from(!node.red))
+    }
+
+    #[test]
+    fn avl_rotations_keep_their_own_caption() {
+        let mut tree = Tree::new(TreeKind::Avl);
+        let mut events = Vec::new();
+        for key in [10, 20, 30] {
+            tree.apply(TreeOp::Insert(key), &mut events);
+        }
+        let rotations: Vec<&str> =
+            events.iter().filter(|e| matches!(e.kind, TreeEventKind::Rotate { .. })).filter_map(|e| e.note.as_deref()).collect();
+        assert_eq!(rotations, ["balance factor -2 at 10 is out of range: rotate left at 10: 20 moves up"]);
+    }
+
+    #[test]
+    fn random_operations_keep_invariants() {
+        let mut rng = SmallRng::seed_from_u64(35);
+        for kind in [TreeKind::Bst, TreeKind::Avl, TreeKind::RedBlack] {
+            let mut tree = Tree::new(kind);
+            let mut model = BTreeSet::new();
+            let mut events = Vec::new();
+            for _ in 0..3000 {
+                let key = rng.gen_range(0..96);
+                match rng.gen_range(0..5) {
+                    0..=2 => {
+                        tree.apply(TreeOp::Insert(key), &mut events);
+                        model.insert(key);
+                    }
+                    3 => {
+                        tree.apply(TreeOp::Delete(key), &mut events);
+                        model.remove(&key);
+                    }
+                    _ => {
+                        events.clear();
+                        tree.apply(TreeOp::Search(key), &mut events);
+                        let found = events.last().map(|e| e.kind) == Some(TreeEventKind::Found);
+                        assert_eq!(found, model.contains(&key));
+                    }
+                }
+
+                if tree.root != NIL {
+                    assert_eq!(tree.nodes[tree.root].parent, NIL);
+                }
+                assert!(!tree.is_red(NIL), "sentinel turned red");
+                if kind == TreeKind::RedBlack {
+                    assert!(!tree.is_red(tree.root), "red root");
+                }
+                check(&tree, tree.root, None, None);
+                let keys: Vec<u32> = tree.shape().nodes.iter().map(|n| n.key).collect();
+                assert!(keys.iter().eq(model.iter()), "{kind:?} holds {keys:?}");
+            }
+        }
+    }
+}
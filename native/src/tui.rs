@@ -0,0 +1,273 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+
+use crate::engine::{BarState, Engine, SortMode};
+use crate::theme::{channel, Theme};
+
+/// Partial block characters, from empty to a full cell, for the top of each bar
+const EIGHTHS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const FRAME_TIME: Duration = Duration::from_millis(33);
+const HELP: &str = "space pause · n/→ step · r reset · q quit";
+
+enum Key {
+    Pause,
+    Step,
+    Reset,
+    Quit,
+}
+
+/// Text-mode front-end: the engine drawn with block characters and 24-bit ANSI colors.
+/// Keys match the window: space pauses, R resets; N or the right arrow plays one action.
+pub fn run(mut engine: Engine, theme: &Theme) -> Result<()> {
+    let _terminal = RawTerminal::enter()?;
+    let mut stdout = io::stdout();
+    let mut paused = false;
+    let mut last_time = Instant::now();
+
+    loop {
+        for key in read_keys(FRAME_TIME)? {
+            match key {
+                Key::Pause => paused = !paused,
+                Key::Step => engine.step(engine.step_delay()),
+                Key::Reset => engine.reset(),
+                Key::Quit => return Ok(()),
+            }
+        }
+
+        let now = Instant::now();
+        let dt = now - last_time;
+        last_time = now;
+        if !paused {
+            engine.step(dt);
+        }
+
+        let (cols, rows) = terminal_size();
+        stdout.write_all(frame(&engine, theme, paused, cols, rows).as_bytes())?;
+        stdout.flush()?;
+    }
+}
+
+/// One full screen, drawn over the previous one from the top-left corner
+fn frame(engine: &Engine, theme: &Theme, paused: bool, cols: usize, rows: usize) -> String {
+    let mut out = String::from("\x1b[H");
+    let (bars, max_value) = engine.bars();
+    let max_value = max_value.max(1) as f32;
+
+    // Counters and captions take the last lines; the temp array gets a short row above them
+    let text_lines = 5;
+    let temp_rows = 4;
+    let bar_rows = rows.saturating_sub(text_lines + temp_rows + 2).max(3);
+    let slot = (cols / bars.len().max(1)).clamp(1, 8);
+
+    let heights: Vec<usize> = bars.iter().map(|bar| (bar.value as f32 / max_value * (bar_rows * 8) as f32).round() as usize).collect();
+    let states: Vec<u32> = bars.iter().map(|bar| bar.state as u32).collect();
+    bar_block(&mut out, theme, &heights, &states, bar_rows, slot);
+    line(&mut out, &"─".repeat((slot * bars.len()).min(cols)));
+
+    // Temp arrays: one mini bar row in sequential mode, one labelled list per busy thread in parallel
+    match engine.mode {
+        SortMode::Sequential => {
+            let temp = &engine.temp_array.values;
+            let heights: Vec<usize> = temp.iter().map(|&v| (v as f32 / max_value * (temp_rows * 8) as f32).round() as usize).collect();
+            let states = vec![BarState::TempArray as u32; temp.len()];
+            bar_block(&mut out, theme, &heights, &states, temp_rows, slot);
+        }
+        SortMode::Parallel => {
+            let busy: Vec<_> = engine.multi_temp_arrays.arrays.iter().enumerate().filter(|(_, a)| !a.values.is_empty()).collect();
+            for row in 0..temp_rows {
+                match busy.get(row) {
+                    Some((thread_id, temp)) => {
+                        let values: Vec<String> = temp.values.iter().map(u32::to_string).collect();
+                        let color = fg(theme, BarState::temp_array_for_thread(*thread_id));
+                        line(&mut out, &format!("{color}T{thread_id} temp [{}]\x1b[0m", values.join(" ")));
+                    }
+                    None => line(&mut out, ""),
+                }
+            }
+        }
+    }
+
+    let status = if engine.is_finished() {
+        "finished"
+    } else if paused {
+        "paused"
+    } else {
+        "running"
+    };
+    line(&mut out, &format!("{} · {:?} · action {} · {status}", engine.algorithm.label(), engine.mode, engine.played()));
+    line(
+        &mut out,
+        &format!(
+            "comparisons {} · operations {} · memory ops {} · memory {} B (peak {} B)",
+            engine.comparisons, engine.operations, engine.memory_ops, engine.current_memory, engine.peak_memory
+        ),
+    );
+    line(&mut out, &format!("est. CPU time {} ns", engine.est_time_ns));
+    line(&mut out, engine.narration(1).first().copied().unwrap_or(""));
+    let _ = write!(out, "\x1b[2m{HELP}\x1b[0m\x1b[K\x1b[J");
+    out
+}
+
+/// Bars as columns `slot` cells wide (one cell of gap when there is room), `heights` in eighths of a row
+fn bar_block(out: &mut String, theme: &Theme, heights: &[usize], states: &[u32], rows: usize, slot: usize) {
+    let width = if slot > 1 { slot - 1 } else { 1 };
+    for row in (0..rows).rev() {
+        let mut text = String::new();
+        for (&height, &state) in heights.iter().zip(states) {
+            let fill = height.saturating_sub(row * 8).min(8);
+            let _ = write!(text, "{}{}\x1b[0m{}", fg(theme, state), EIGHTHS[fill].to_string().repeat(width), " ".repeat(slot - width));
+        }
+        line(out, &text);
+    }
+}
+
+fn fg(theme: &Theme, state: u32) -> String {
+    let [r, g, b, _] = theme.state_color(state);
+    format!("\x1b[38;2;{};{};{}m", channel(r), channel(g), channel(b))
+}
+
+/// Raw mode leaves output untranslated, so every line ends with an explicit carriage return
+fn line(out: &mut String, text: &str) {
+    let _ = write!(out, "{text}\x1b[K\r\n");
+}
+
+/// Wait up to `timeout` for input and decode every key that arrived
+fn read_keys(timeout: Duration) -> Result<Vec<Key>> {
+    let mut poll = libc::pollfd {
+        fd: libc::STDIN_FILENO,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    // SAFETY: one valid pollfd for the duration of the call
+    let ready = unsafe { libc::poll(&mut poll, 1, timeout.as_millis() as libc::c_int) };
+    if ready <= 0 {
+        return Ok(Vec::new());
+    }
+    let mut buf = [0u8; 64];
+    // SAFETY: reads at most buf.len() bytes into buf
+    let read = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+    if read <= 0 {
+        return Ok(Vec::new());
+    }
+
+    let bytes = &buf[..read as usize];
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i..] {
+            // Arrow keys; only right is bound
+            [0x1b, b'[', code, ..] => {
+                if code == b'C' {
+                    keys.push(Key::Step);
+                }
+                i += 3;
+                continue;
+            }
+            [b' ', ..] => keys.push(Key::Pause),
+            [b'n' | b'N', ..] => keys.push(Key::Step),
+            [b'r' | b'R', ..] => keys.push(Key::Reset),
+            // q, Esc or Ctrl-C: raw mode delivers Ctrl-C as a byte instead of a signal
+            [b'q' | b'Q' | 0x1b | 0x03, ..] => keys.push(Key::Quit),
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(keys)
+}
+
+fn terminal_size() -> (usize, usize) {
+    // SAFETY: winsize is plain data filled in by the ioctl
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+    if ok && size.ws_col > 0 && size.ws_row > 0 {
+        (size.ws_col as usize, size.ws_row as usize)
+    } else {
+        (80, 24)
+    }
+}
+
+/// Raw, unechoed input on the alternate screen; the terminal is restored on drop
+struct RawTerminal {
+    original: libc::termios,
+}
+
+impl RawTerminal {
+    fn enter() -> Result<Self> {
+        // SAFETY: termios is plain data filled in by tcgetattr
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+            bail!("the terminal front-end needs an interactive terminal on stdin");
+        }
+        let original = termios;
+        unsafe {
+            libc::cfmakeraw(&mut termios);
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+        }
+        // Alternate screen, hidden cursor
+        print!("\x1b[?1049h\x1b[?25l");
+        Ok(Self { original })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        // SAFETY: restores the settings read in `enter`
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Algorithm;
+
+    /// The frame's lines as they appear on screen, without escape sequences
+    fn screen(frame: &str) -> Vec<String> {
+        let mut text = String::new();
+        let mut chars = frame.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                // CSI: '[', parameters, then one final byte in '@'..='~'
+                chars.by_ref().skip(1).find(|c| ('@'..='~').contains(c));
+            } else {
+                text.push(c);
+            }
+        }
+        text.split("\r\n").map(str::to_string).collect()
+    }
+
+    #[test]
+    fn frame_draws_each_bar_at_its_height() {
+        let theme = Theme::default();
+        let mut engine = Engine::new(8);
+        engine.set_algorithm(Algorithm::BitonicSort);
+        engine.seek(5);
+        let (cols, rows) = (80, 24);
+        let lines = screen(&frame(&engine, &theme, false, cols, rows));
+
+        // 24 rows less 5 text lines, 4 temp rows and the two separators
+        let bar_rows = 13;
+        let slot = 8;
+        let (bars, max_value) = engine.bars();
+        for line in &lines[..bar_rows] {
+            assert_eq!(line.chars().count(), slot * bars.len());
+        }
+        assert_eq!(lines[bar_rows], "─".repeat(slot * bars.len()));
+        for (i, bar) in bars.iter().enumerate() {
+            let eighths: usize = lines[..bar_rows]
+                .iter()
+                .map(|line| EIGHTHS.iter().position(|&c| Some(c) == line.chars().nth(i * slot)).unwrap())
+                .sum();
+            let expected = (bar.value as f32 / max_value as f32 * (bar_rows * 8) as f32).round() as usize;
+            assert_eq!(eighths, expected, "bar {i} of value {}", bar.value);
+        }
+        assert!(lines.iter().any(|line| line.contains(&format!("action {} · running", engine.played()))));
+    }
+}